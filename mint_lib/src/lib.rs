@@ -1,20 +1,43 @@
 pub mod error;
+pub mod manifest;
 pub mod mod_info;
+pub mod profile;
 pub mod update;
 
 use std::{
     io::BufWriter,
     path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
-use anyhow::{Result};
+use anyhow::{Context, Result};
 use fs_err as fs;
+use snafu::Snafu;
 use tracing::*;
 use tracing_subscriber::fmt::format::FmtSpan;
 
-#[derive(Debug)]
+use crate::mod_info::SemverVersion;
+
+/// Prefix added to the directory name of installed mods that have been disabled.
+pub const DISABLED_PREFIX: &str = "DISABLED_";
+
+/// Problem with a DBSZ installation found by `DBSZInstallation::validate`.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum ValidationError {
+    #[snafu(display("missing binaries directory"))]
+    MissingBinaries,
+    #[snafu(display("missing content directory"))]
+    MissingContentDir,
+    #[snafu(display("missing paks directory"))]
+    MissingPaksDir,
+    #[snafu(display("{} is not writable", path.display()))]
+    NotWritable { path: PathBuf },
+}
+
+#[derive(Debug, Clone)]
 pub struct DBSZInstallation {
     pub root: PathBuf,
+    game_version: OnceLock<SemverVersion>,
 }
 
 impl DBSZInstallation {
@@ -33,17 +56,69 @@ impl DBSZInstallation {
                             .join("SparkingZERO")
                     })
             })
+            .map(|path| resolve_proton_path(&path))
             .and_then(|path| Self::from_game_path(path).ok())
     }
+    /// Returns every valid DBSZ installation found across all Steam libraries.
+    pub fn find_all() -> Vec<Self> {
+        let Ok(steamdir) = steamlocate::SteamDir::locate() else {
+            return vec![];
+        };
+        let Ok(libraries) = steamdir.libraries() else {
+            return vec![];
+        };
+        libraries
+            .filter_map(|library| library.ok())
+            .filter_map(|library| {
+                let app = library.app(1790600)?.ok()?;
+                Some(library.resolve_app_dir(&app).join("SparkingZERO"))
+            })
+            .map(|path| resolve_proton_path(&path))
+            .filter_map(|path| Self::from_game_path(path).ok())
+            .filter(|installation| installation.validate().is_ok())
+            .collect()
+    }
+    /// Returns the DBSZ installation registered with Windows, used for copies of the game that
+    /// were not installed through Steam (e.g. Game Pass).
+    #[cfg(windows)]
+    pub fn from_registry() -> Option<Self> {
+        use winreg::{enums::HKEY_LOCAL_MACHINE, RegKey};
+
+        let uninstall = RegKey::predef(HKEY_LOCAL_MACHINE)
+            .open_subkey(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall")
+            .ok()?;
+        uninstall
+            .enum_keys()
+            .filter_map(|name| uninstall.open_subkey(name.ok()?).ok())
+            .find(|key| {
+                key.get_value::<String, _>("DisplayName")
+                    .is_ok_and(|name| name.contains("Sparking! ZERO"))
+            })
+            .and_then(|key| key.get_value::<String, _>("InstallLocation").ok())
+            .map(|location| Self::from_root(PathBuf::from(location)))
+    }
+    /// Returns first DBSZ installation found, trying Steam first and then (on Windows) the
+    /// registry. The path found here is only a default and can be overridden by the user in the
+    /// config.
+    pub fn find_any() -> Option<Self> {
+        let found = Self::find();
+        #[cfg(windows)]
+        let found = found.or_else(Self::from_registry);
+        found
+    }
     pub fn from_game_path<P: AsRef<Path>>(game: P) -> Result<Self> {
         let root = game
             .as_ref()
             .parent()
             .unwrap()
             .to_path_buf();
-        Ok(Self {
+        Ok(Self::from_root(root))
+    }
+    fn from_root(root: PathBuf) -> Self {
+        Self {
             root,
-        })
+            game_version: OnceLock::new(),
+        }
     }
     pub fn binaries_directory(&self) -> PathBuf {
         self.root
@@ -56,47 +131,330 @@ impl DBSZInstallation {
     pub fn mods_path(&self) -> PathBuf {
         self.root.join("Mods")
     }
+    pub fn content_path(&self) -> PathBuf {
+        self.root.join("Content")
+    }
+    pub fn config_path(&self) -> PathBuf {
+        self.root
+            .join("Saved")
+            .join("Config")
+            .join("WindowsNoEditor")
+    }
+    /// Checks that all directories mods are installed into exist and are writable, returning
+    /// every problem found.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        fn is_dir(path: &Path) -> bool {
+            std::fs::metadata(path).is_ok_and(|m| m.is_dir())
+        }
+        fn is_writable(path: &Path) -> bool {
+            std::fs::metadata(path).is_ok_and(|m| !m.permissions().readonly())
+        }
+
+        let mut errors = vec![];
+        if !is_dir(&self.binaries_directory()) {
+            errors.push(ValidationError::MissingBinaries);
+        }
+        if !is_dir(&self.content_path()) {
+            errors.push(ValidationError::MissingContentDir);
+        }
+        let paks_path = self.paks_path();
+        if !is_dir(&paks_path) {
+            errors.push(ValidationError::MissingPaksDir);
+        } else if !is_writable(&paks_path) {
+            errors.push(ValidationError::NotWritable { path: paks_path });
+        }
+        // plugin mods are installed into `Mods` which is created on demand
+        let mods_path = self.mods_path();
+        let mods_parent = if mods_path.exists() {
+            mods_path
+        } else {
+            self.root.clone()
+        };
+        if !is_writable(&mods_parent) {
+            errors.push(ValidationError::NotWritable {
+                path: mods_parent,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    /// Directories installed mods are placed in, pak mods first.
+    fn installed_mods_directories(&self) -> [PathBuf; 2] {
+        [self.paks_path().join("~mods"), self.mods_path()]
+    }
+    /// Returns the installed directories of the mod with the given name, with or without a load
+    /// order prefix. If `disabled` is set only disabled copies are returned, otherwise only enabled
+    /// ones.
+    fn find_installed_mod(&self, mod_name: &str, disabled: bool) -> Vec<PathBuf> {
+        let mut found = vec![];
+        for dir in self.installed_mods_directories() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let file_name = entry.file_name();
+                let Some(name) = file_name.to_str() else {
+                    continue;
+                };
+                let name = match (name.strip_prefix(DISABLED_PREFIX), disabled) {
+                    (Some(name), true) => name,
+                    (None, false) => name,
+                    _ => continue,
+                };
+                if name == mod_name || parse_load_order(name).is_some_and(|(_, n)| n == mod_name) {
+                    found.push(entry.path());
+                }
+            }
+        }
+        found
+    }
+    /// Disables an installed mod without removing it by renaming its directory. The install
+    /// manifest records the mod as disabled.
+    pub fn disable_mod(&self, mod_name: &str) -> Result<()> {
+        let installed = self.find_installed_mod(mod_name, false);
+        anyhow::ensure!(!installed.is_empty(), "mod {mod_name:?} is not installed");
+        for path in installed {
+            let name = path.file_name().unwrap().to_string_lossy();
+            fs::rename(
+                &path,
+                path.with_file_name(format!("{DISABLED_PREFIX}{name}")),
+            )?;
+        }
+        self.set_disabled_in_manifest(mod_name, true)
+    }
+    /// Re-enables a mod previously disabled with `disable_mod`.
+    pub fn enable_mod(&self, mod_name: &str) -> Result<()> {
+        let disabled = self.find_installed_mod(mod_name, true);
+        anyhow::ensure!(!disabled.is_empty(), "mod {mod_name:?} is not disabled");
+        for path in disabled {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let name = name.strip_prefix(DISABLED_PREFIX).unwrap();
+            fs::rename(&path, path.with_file_name(name))?;
+        }
+        self.set_disabled_in_manifest(mod_name, false)
+    }
+    fn set_disabled_in_manifest(&self, mod_name: &str, disabled: bool) -> Result<()> {
+        let Some(mut manifest) = manifest::Manifest::load(self)? else {
+            return Ok(());
+        };
+        if let Some(entry) = manifest.mods.iter_mut().find(|m| m.name == mod_name) {
+            entry.disabled = disabled;
+            manifest.save(self)?;
+        }
+        Ok(())
+    }
+    /// Removes every installed copy of the mod, enabled or disabled, and returns the removed
+    /// directories.
+    pub fn remove_mod(&self, mod_name: &str) -> Result<Vec<PathBuf>> {
+        let mut removed = self.find_installed_mod(mod_name, false);
+        removed.extend(self.find_installed_mod(mod_name, true));
+        for path in &removed {
+            if path.is_dir() {
+                fs::remove_dir_all(path)?;
+            } else {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(removed)
+    }
+    pub fn is_mod_disabled(&self, mod_name: &str) -> bool {
+        mod_name.starts_with(DISABLED_PREFIX) || !self.find_installed_mod(mod_name, true).is_empty()
+    }
+    /// Returns the name and priority of every enabled pak mod in the order the game loads them.
+    pub fn installed_load_order(&self) -> Result<Vec<(String, i32)>> {
+        let path = self.paks_path().join("~mods");
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let mut dir_names = fs::read_dir(&path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>>>()?;
+        // the game loads paks in alphabetical order
+        dir_names.sort();
+        Ok(dir_names
+            .iter()
+            .filter_map(|name| parse_load_order(name))
+            .map(|(priority, name)| (name.to_string(), priority))
+            .collect())
+    }
+    pub fn executable_path(&self) -> PathBuf {
+        self.binaries_directory().join("SparkingZERO.exe")
+    }
+    /// Reads the file version from the game executable's version resource. The executable is a
+    /// PE file on every platform (Proton included) so this works everywhere. The result is cached.
+    pub fn game_version(&self) -> Result<SemverVersion> {
+        if let Some(version) = self.game_version.get() {
+            return Ok(version.clone());
+        }
+
+        use pelite::pe64::{Pe, PeFile};
+
+        let path = self.executable_path();
+        let map = pelite::FileMap::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        let file = PeFile::from_bytes(&map)?;
+        let version_info = file.resources()?.version_info()?;
+        let fixed = version_info
+            .fixed()
+            .context("game executable has no fixed file version info")?;
+        let version = SemverVersion {
+            major: fixed.dwFileVersion.Major.into(),
+            minor: fixed.dwFileVersion.Minor.into(),
+            patch: fixed.dwFileVersion.Patch.into(),
+            pre_release: None,
+        };
+
+        Ok(self.game_version.get_or_init(|| version).clone())
+    }
 }
 
-pub fn setup_logging<P: AsRef<Path>>(
-    log_path: P,
-    target: &str,
-) -> Result<tracing_appender::non_blocking::WorkerGuard> {
-    use tracing::metadata::LevelFilter;
-    use tracing_subscriber::prelude::*;
-    use tracing_subscriber::{
-        field::RecordFields,
-        filter,
-        fmt::{
-            self,
-            format::{Pretty, Writer},
-            FormatFields,
-        },
-        EnvFilter,
+/// Appends the files in `path` and its subdirectories to `files`, or `path` itself if it is a file.
+pub fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if fs::metadata(path)?.is_dir() {
+        for entry in fs::read_dir(path)? {
+            collect_files(&entry?.path(), files)?;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Splits an installed mod directory name of the form `0100_<mod_name>` into its load order
+/// priority and mod name.
+pub fn parse_load_order(dir_name: &str) -> Option<(i32, &str)> {
+    let (priority, name) = dir_name.split_once('_')?;
+    Some((priority.parse().ok()?, name))
+}
+
+/// Maps a path inside a Proton prefix (`steamapps/compatdata/<appid>/pfx/drive_c/...`) back to
+/// the host side `steamapps/common/...` directory the game is actually read from. Paths outside
+/// of a Proton prefix are returned unchanged.
+fn resolve_proton_path(path: &Path) -> PathBuf {
+    let components = path.components().collect::<Vec<_>>();
+    let Some(compatdata) = components
+        .iter()
+        .position(|c| c.as_os_str() == "compatdata")
+    else {
+        return path.to_path_buf();
     };
+    match components.iter().rposition(|c| c.as_os_str() == "common") {
+        Some(common) if common > compatdata => components[..compatdata]
+            .iter()
+            .chain(&components[common..])
+            .collect(),
+        _ => path.to_path_buf(),
+    }
+}
 
-    /// Workaround for <https://github.com/tokio-rs/tracing/issues/1817>.
-    struct NewType(Pretty);
+/// Format of the lines written to the log file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human readable multi-line records
+    #[default]
+    Pretty,
+    /// One JSON object per line for log aggregation tools
+    Json,
+}
+
+/// Levels of the records written to the log file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    /// Level of the target passed to `setup_logging`
+    pub default_level: Level,
+    /// Levels of other targets, e.g. to silence `repak` or get `DEBUG` records of `mint_lib`
+    pub targets: Vec<(String, Level)>,
+    /// Start a new log file every day and keep this many, see `setup_logging_rotating`
+    pub max_files: Option<usize>,
+}
 
-    impl<'writer> FormatFields<'writer> for NewType {
-        fn format_fields<R: RecordFields>(
-            &self,
-            writer: Writer<'writer>,
-            fields: R,
-        ) -> core::fmt::Result {
-            self.0.format_fields(writer, fields)
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            default_level: Level::DEBUG,
+            targets: vec![],
+            max_files: None,
         }
     }
+}
 
-    let f = fs::File::create(log_path.as_ref())?;
-    let writer = BufWriter::new(f);
-    let (log_file_appender, guard) = tracing_appender::non_blocking(writer);
-    let debug_file_log = fmt::layer()
-        .with_writer(log_file_appender)
-        .fmt_fields(NewType(Pretty::default()))
-        .with_ansi(false)
-        .with_filter(filter::Targets::new().with_target(target, Level::DEBUG));
-    let stderr_log = fmt::layer()
+impl LogConfig {
+    /// Parses the `[log]` section of a TOML file:
+    ///
+    /// ```toml
+    /// [log]
+    /// default_level = "debug"
+    /// max_files = 7
+    ///
+    /// [log.targets]
+    /// repak = "warn"
+    /// ```
+    pub fn from_toml(s: &str) -> Result<Self> {
+        #[derive(Default, serde::Deserialize)]
+        struct File {
+            #[serde(default)]
+            log: Section,
+        }
+        #[derive(Default, serde::Deserialize)]
+        struct Section {
+            default_level: Option<String>,
+            #[serde(default)]
+            targets: std::collections::BTreeMap<String, String>,
+            max_files: Option<usize>,
+        }
+
+        let parse = |target: &str, level: &str| {
+            level
+                .parse::<Level>()
+                .with_context(|| format!("invalid log level {level:?} for {target:?}"))
+        };
+        let section = toml::from_str::<File>(s)?.log;
+        Ok(Self {
+            default_level: match section.default_level {
+                Some(level) => parse("default_level", &level)?,
+                None => Level::DEBUG,
+            },
+            targets: section
+                .targets
+                .iter()
+                .map(|(target, level)| parse(target, level).map(|level| (target.clone(), level)))
+                .collect::<Result<_>>()?,
+            max_files: section.max_files,
+        })
+    }
+
+    /// Reads the config from a TOML file, falling back to the default if it does not exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(s) => Self::from_toml(&s)
+                .with_context(|| format!("failed to parse {}", path.as_ref().display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn filter(&self, target: &str) -> tracing_subscriber::filter::Targets {
+        tracing_subscriber::filter::Targets::new()
+            .with_target(target, self.default_level)
+            .with_targets(self.targets.iter().cloned())
+    }
+}
+
+/// Human readable log on stderr, filtered by `RUST_LOG` and defaulting to `INFO`.
+fn stderr_layer<S>() -> impl tracing_subscriber::Layer<S>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    use tracing::metadata::LevelFilter;
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{fmt, EnvFilter};
+
+    fmt::layer()
         .with_writer(std::io::stderr)
         .event_format(tracing_subscriber::fmt::format().without_time())
         .with_span_events(FmtSpan::CLOSE)
@@ -104,15 +462,228 @@ pub fn setup_logging<P: AsRef<Path>>(
             EnvFilter::builder()
                 .with_default_directive(LevelFilter::INFO.into())
                 .from_env_lossy(),
-        );
+        )
+}
+
+/// Workaround for <https://github.com/tokio-rs/tracing/issues/1817>.
+struct NewType(tracing_subscriber::fmt::format::Pretty);
+
+impl<'writer> tracing_subscriber::fmt::FormatFields<'writer> for NewType {
+    fn format_fields<R: tracing_subscriber::field::RecordFields>(
+        &self,
+        writer: tracing_subscriber::fmt::format::Writer<'writer>,
+        fields: R,
+    ) -> core::fmt::Result {
+        self.0.format_fields(writer, fields)
+    }
+}
+
+/// Log file layer writing records in `format`
+fn file_layer<S>(
+    writer: tracing_appender::non_blocking::NonBlocking,
+    format: LogFormat,
+    filter: tracing_subscriber::filter::Targets,
+) -> Box<dyn tracing_subscriber::Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    use tracing_subscriber::fmt::{self, format::Pretty};
+    use tracing_subscriber::prelude::*;
+
+    match format {
+        LogFormat::Pretty => fmt::layer()
+            .with_writer(writer)
+            .fmt_fields(NewType(Pretty::default()))
+            .with_ansi(false)
+            .with_filter(filter)
+            .boxed(),
+        LogFormat::Json => fmt::layer()
+            .json()
+            .with_current_span(false)
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_filter(filter)
+            .boxed(),
+    }
+}
+
+/// Logs to stderr and to the log file `writer` writes to, `location` being where that is
+fn install_subscriber(
+    writer: impl std::io::Write + Send + 'static,
+    location: &Path,
+    format: LogFormat,
+    target: &str,
+    config: &LogConfig,
+) -> Result<tracing_appender::non_blocking::WorkerGuard> {
+    use tracing_subscriber::prelude::*;
+
+    let (log_file_appender, guard) = tracing_appender::non_blocking(writer);
     let subscriber = tracing_subscriber::registry()
-        .with(stderr_log)
-        .with(debug_file_log);
+        .with(stderr_layer())
+        .with(file_layer(log_file_appender, format, config.filter(target)));
 
     tracing::subscriber::set_global_default(subscriber)?;
 
     debug!("tracing subscriber setup");
-    info!("writing logs to {:?}", log_path.as_ref().display());
+    info!("writing logs to {:?}", location.display());
 
     Ok(guard)
 }
+
+pub fn setup_logging<P: AsRef<Path>>(
+    log_path: P,
+    target: &str,
+    config: &LogConfig,
+) -> Result<tracing_appender::non_blocking::WorkerGuard> {
+    let writer = BufWriter::new(fs::File::create(log_path.as_ref())?);
+    install_subscriber(writer, log_path.as_ref(), LogFormat::Pretty, target, config)
+}
+
+/// Like `setup_logging` but starts a new `<target>.log.<date>` file in `log_dir` every day,
+/// deleting the oldest files once there are more than `max_files`. Files are pruned when logging
+/// starts and whenever the file is rotated.
+pub fn setup_logging_rotating<P: AsRef<Path>>(
+    log_dir: P,
+    target: &str,
+    max_files: usize,
+    format: LogFormat,
+    config: &LogConfig,
+) -> Result<tracing_appender::non_blocking::WorkerGuard> {
+    let prefix = format!("{target}.log");
+    prune_log_files(log_dir.as_ref(), &prefix, max_files)?;
+    let appender = tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(&prefix)
+        .max_log_files(max_files)
+        .build(log_dir.as_ref())?;
+    install_subscriber(appender, log_dir.as_ref(), format, target, config)
+}
+
+/// Deletes the least recently modified files starting with `prefix` until at most `max_files`
+/// remain.
+fn prune_log_files(log_dir: &Path, prefix: &str, max_files: usize) -> Result<()> {
+    let mut files = fs::read_dir(log_dir)?
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect::<Vec<_>>();
+    if files.len() > max_files {
+        files.sort();
+        for (_, path) in &files[..files.len() - max_files] {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Like `setup_logging` but writes every record to the log file as a single JSON object with
+/// `timestamp`, `level`, `target` and `fields` keys, the message being part of `fields`.
+pub fn setup_logging_json<P: AsRef<Path>>(
+    log_path: P,
+    target: &str,
+    config: &LogConfig,
+) -> Result<tracing_appender::non_blocking::WorkerGuard> {
+    let writer = BufWriter::new(fs::File::create(log_path.as_ref())?);
+    install_subscriber(writer, log_path.as_ref(), LogFormat::Json, target, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_config_from_toml() {
+        let config = LogConfig::from_toml(
+            r#"
+            [log]
+            default_level = "info"
+            max_files = 3
+
+            [log.targets]
+            mint_lib = "debug"
+            repak = "warn"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            LogConfig {
+                default_level: Level::INFO,
+                targets: vec![
+                    ("mint_lib".to_string(), Level::DEBUG),
+                    ("repak".to_string(), Level::WARN),
+                ],
+                max_files: Some(3),
+            }
+        );
+        assert_eq!(LogConfig::from_toml("").unwrap(), LogConfig::default());
+        assert!(LogConfig::from_toml("[log]\ndefault_level = \"loud\"").is_err());
+    }
+
+    #[test]
+    fn test_prune_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+        for day in 1..=4 {
+            let file = std::fs::File::create(dir.path().join(format!("mint.log.2024-01-0{day}")))
+                .unwrap();
+            file.set_modified(start + std::time::Duration::from_secs(day))
+                .unwrap();
+        }
+        std::fs::write(dir.path().join("other.log"), "").unwrap();
+
+        prune_log_files(dir.path(), "mint.log", 2).unwrap();
+        let mut names = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["mint.log.2024-01-03", "mint.log.2024-01-04", "other.log"]);
+    }
+
+    #[test]
+    fn test_resolve_proton_path() {
+        assert_eq!(
+            resolve_proton_path(Path::new(
+                "/home/user/SteamLibrary/steamapps/compatdata/1790600/pfx/drive_c/Program Files (x86)/Steam/steamapps/common/DRAGON BALL Sparking! ZERO/SparkingZERO"
+            )),
+            PathBuf::from(
+                "/home/user/SteamLibrary/steamapps/common/DRAGON BALL Sparking! ZERO/SparkingZERO"
+            )
+        );
+        assert_eq!(
+            resolve_proton_path(Path::new(
+                "/home/user/SteamLibrary/steamapps/common/DRAGON BALL Sparking! ZERO/SparkingZERO"
+            )),
+            PathBuf::from(
+                "/home/user/SteamLibrary/steamapps/common/DRAGON BALL Sparking! ZERO/SparkingZERO"
+            )
+        );
+    }
+
+    #[test]
+    fn test_disable_mod_updates_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let installation = DBSZInstallation::from_root(root.path().to_path_buf());
+        let mod_dir = installation.paks_path().join("~mods").join("0100_Foo");
+        std::fs::create_dir_all(&mod_dir).unwrap();
+        std::fs::write(mod_dir.join("Foo.pak"), b"pak").unwrap();
+        manifest::Manifest::rebuild(&installation)
+            .unwrap()
+            .save(&installation)
+            .unwrap();
+        let disabled = |installation: &DBSZInstallation| {
+            let manifest = manifest::Manifest::load(installation).unwrap().unwrap();
+            manifest.mods.iter().find(|m| m.name == "Foo").unwrap().disabled
+        };
+
+        installation.disable_mod("Foo").unwrap();
+        assert!(installation.is_mod_disabled("Foo"));
+        assert!(disabled(&installation));
+        assert!(manifest::Manifest::rebuild(&installation).unwrap().mods[0].disabled);
+
+        installation.enable_mod("Foo").unwrap();
+        assert!(!installation.is_mod_disabled("Foo"));
+        assert!(!disabled(&installation));
+    }
+}
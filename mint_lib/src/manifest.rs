@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::mod_info::ModType;
+use crate::{collect_files, DBSZInstallation, DISABLED_PREFIX};
+
+pub const MANIFEST_FILE_NAME: &str = "mint_manifest.json";
+
+/// `Meta` of the installed mods written next to the manifest for the game
+pub const META_FILE_NAME: &str = "mint.json";
+
+/// Directory in `~mods` the game files overwritten by merged mods are moved to
+pub const BACKUP_DIR_NAME: &str = ".mint_backup";
+
+/// Files written by `integrate` for every installed mod.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Manifest {
+    pub mods: Vec<ManifestMod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestMod {
+    pub name: String,
+    pub mod_type: ModType,
+    pub files: Vec<ManifestFile>,
+    /// Values config mods replaced so they can be restored on uninstall
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub config_defaults: Vec<ConfigDefault>,
+    /// URL the installed version was resolved to, used to detect updates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+    /// Specification the mod was installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// ISO-8601 time of the installation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<String>,
+    /// Version name given by the provider of the installed version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Files a merged mod overwrote, relative to the installation root. The originals are kept
+    /// under `Manifest::backup_dir` and restored on uninstall.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backups: Vec<PathBuf>,
+    /// Disabled with `DBSZInstallation::disable_mod`. The directories of disabled mods are
+    /// renamed so `files` are not where they were installed to.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
+
+/// Value of an INI key before a config mod overrode it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDefault {
+    /// File name within the config directory
+    pub file: String,
+    pub section: Option<String>,
+    pub key: String,
+    /// `None` if the key was not set
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Relative to the installation root
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl Manifest {
+    pub fn path(installation: &DBSZInstallation) -> PathBuf {
+        installation
+            .paks_path()
+            .join("~mods")
+            .join(MANIFEST_FILE_NAME)
+    }
+
+    pub fn meta_path(installation: &DBSZInstallation) -> PathBuf {
+        installation.paks_path().join("~mods").join(META_FILE_NAME)
+    }
+
+    /// Directory the files overwritten by the merged mod `name` are backed up to, mirroring
+    /// their paths relative to the installation root.
+    pub fn backup_dir(installation: &DBSZInstallation, name: &str) -> PathBuf {
+        installation
+            .paks_path()
+            .join("~mods")
+            .join(BACKUP_DIR_NAME)
+            .join(name)
+    }
+
+    /// Reads the manifest of the installation, `None` if mint has not written one yet.
+    pub fn load(installation: &DBSZInstallation) -> Result<Option<Self>> {
+        let path = Self::path(installation);
+        match fs::read(&path) {
+            Ok(buf) => Ok(Some(serde_json::from_slice(&buf).with_context(|| {
+                format!("failed to parse manifest {}", path.display())
+            })?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, installation: &DBSZInstallation) -> Result<()> {
+        let path = Self::path(installation);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds the mod, replacing any previous entry with the same name.
+    pub fn insert(&mut self, entry: ManifestMod) {
+        self.remove(&entry.name);
+        self.mods.push(entry);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ManifestMod> {
+        let index = self.mods.iter().position(|m| m.name == name)?;
+        Some(self.mods.remove(index))
+    }
+
+    /// Reconstructs the manifest of an installation integrated before manifests were written from
+    /// the mod directories in `~mods` and `Mods`. Blueprint and config mods are merged into shared
+    /// directories so they cannot be recovered.
+    pub fn rebuild(installation: &DBSZInstallation) -> Result<Self> {
+        let mut manifest = Self::default();
+        for (dir, mod_type) in [
+            (installation.paks_path().join("~mods"), ModType::Pak),
+            (installation.mods_path(), ModType::ModPlugin),
+        ] {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for entry in entries {
+                let path = entry?.path();
+                // skips the backups of merged mods and staging directories
+                if !path.is_dir()
+                    || path
+                        .file_name()
+                        .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+                {
+                    continue;
+                }
+                let dir_name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let (dir_name, disabled) = match dir_name.strip_prefix(DISABLED_PREFIX) {
+                    Some(name) => (name.to_string(), true),
+                    None => (dir_name, false),
+                };
+                // pak mod directories are prefixed with the load order priority
+                let name = match dir_name.split_once('_') {
+                    Some((priority, name))
+                        if mod_type == ModType::Pak
+                            && priority.trim_start_matches('-').parse::<u32>().is_ok() =>
+                    {
+                        name.to_string()
+                    }
+                    _ => dir_name,
+                };
+                let mut entry = ManifestMod::scan(installation, name, mod_type.clone(), &path)?;
+                entry.disabled = disabled;
+                manifest.insert(entry);
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+impl ManifestMod {
+    /// Records the given installed files.
+    pub fn from_files(
+        installation: &DBSZInstallation,
+        name: String,
+        mod_type: ModType,
+        paths: &[PathBuf],
+    ) -> Result<Self> {
+        let files = paths
+            .iter()
+            .map(|path| {
+                Ok(ManifestFile {
+                    path: path
+                        .strip_prefix(&installation.root)
+                        .unwrap_or(path)
+                        .to_path_buf(),
+                    size: fs::metadata(path)?.len(),
+                    sha256: hash_file(path)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            name,
+            mod_type,
+            files,
+            config_defaults: vec![],
+            resolution: None,
+            source: None,
+            installed_at: None,
+            version: None,
+            backups: vec![],
+            disabled: false,
+        })
+    }
+
+    /// Records every file under `installed`, which may be a single file or a directory.
+    pub fn scan(
+        installation: &DBSZInstallation,
+        name: String,
+        mod_type: ModType,
+        installed: &Path,
+    ) -> Result<Self> {
+        let mut paths = vec![];
+        collect_files(installed, &mut paths)?;
+        Self::from_files(installation, name, mod_type, &paths)
+    }
+}
+
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut io::BufReader::new(fs::File::open(path)?), &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// SHA-256 of files keyed by path, reused as long as the modification time and size of the file
+/// are unchanged so verifying unchanged paks does not require reading them again.
+#[derive(Debug, Default)]
+pub struct HashCache {
+    path: Option<PathBuf>,
+    /// (mtime in seconds since the epoch, size, SHA-256)
+    entries: HashMap<PathBuf, (u64, u64, [u8; 32])>,
+}
+
+impl HashCache {
+    /// Loads the cache persisted at `path`, starting empty if it is missing or unreadable.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|buf| serde_json::from_slice(&buf).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_vec(&self.entries)?)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `hash_file` but only reads the file if it changed since it was last hashed.
+    pub fn hash_file(&mut self, path: &Path) -> Result<String> {
+        let metadata = fs::metadata(path)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let size = metadata.len();
+        if let Some((_, _, hash)) = self
+            .entries
+            .get(path)
+            .filter(|(m, s, _)| *m == mtime && *s == size)
+        {
+            return Ok(hex::encode(hash));
+        }
+
+        let mut hasher = Sha256::new();
+        io::copy(&mut io::BufReader::new(fs::File::open(path)?), &mut hasher)?;
+        let hash: [u8; 32] = hasher.finalize().into();
+        self.entries.insert(path.to_path_buf(), (mtime, size, hash));
+        Ok(hex::encode(hash))
+    }
+}
+
+/// Difference between the installed files and the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationFailure {
+    MissingFile {
+        mod_name: String,
+        path: PathBuf,
+    },
+    SizeMismatch {
+        mod_name: String,
+        path: PathBuf,
+        expected: u64,
+        actual: u64,
+    },
+    HashMismatch {
+        mod_name: String,
+        path: PathBuf,
+    },
+}
+
+impl VerificationFailure {
+    pub fn mod_name(&self) -> &str {
+        match self {
+            VerificationFailure::MissingFile { mod_name, .. }
+            | VerificationFailure::SizeMismatch { mod_name, .. }
+            | VerificationFailure::HashMismatch { mod_name, .. } => mod_name,
+        }
+    }
+}
+
+/// Compares the installed files of the mods `filter` accepts against the manifest written by
+/// `integrate`, returning the checked mods along with the differences found. Hashes are only
+/// compared if `check_hashes` is set, files unchanged since they were added to `hash_cache` are
+/// not read again. If there is no manifest yet it is rebuilt from the installed files.
+pub fn verify_installation(
+    installation: &DBSZInstallation,
+    filter: impl Fn(&ManifestMod) -> bool,
+    check_hashes: bool,
+    hash_cache: &mut HashCache,
+) -> Result<(Vec<ManifestMod>, Vec<VerificationFailure>)> {
+    let manifest = match Manifest::load(installation)? {
+        Some(manifest) => manifest,
+        None => {
+            let manifest =
+                Manifest::rebuild(installation).context("failed to rebuild install manifest")?;
+            manifest.save(installation)?;
+            manifest
+        }
+    };
+    let mods = manifest.mods.into_iter().filter(filter).collect::<Vec<_>>();
+    let failures = verify_mods(installation, &mods, check_hashes, hash_cache)?;
+    Ok((mods, failures))
+}
+
+fn verify_mods(
+    installation: &DBSZInstallation,
+    mods: &[ManifestMod],
+    check_hashes: bool,
+    hash_cache: &mut HashCache,
+) -> Result<Vec<VerificationFailure>> {
+    let mut failures = vec![];
+    // the files of disabled mods have been renamed
+    for entry in mods.iter().filter(|m| !m.disabled) {
+        // config files are shared by every config mod so only their presence can be checked
+        let shared = entry.mod_type == ModType::Config;
+        for file in &entry.files {
+            let mod_name = entry.name.clone();
+            let path = installation.root.join(&file.path);
+            let Ok(metadata) = fs::metadata(&path) else {
+                failures.push(VerificationFailure::MissingFile { mod_name, path });
+                continue;
+            };
+            if shared {
+                continue;
+            }
+            if metadata.len() != file.size {
+                failures.push(VerificationFailure::SizeMismatch {
+                    mod_name,
+                    path,
+                    expected: file.size,
+                    actual: metadata.len(),
+                });
+            } else if check_hashes && hash_cache.hash_file(&path)? != file.sha256 {
+                failures.push(VerificationFailure::HashMismatch { mod_name, path });
+            }
+        }
+    }
+    Ok(failures)
+}
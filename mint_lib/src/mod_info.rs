@@ -1,6 +1,11 @@
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
 
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use snafu::{ensure, Snafu};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RequiredStatus {
@@ -9,10 +14,16 @@ pub enum RequiredStatus {
 }
 
 /// Whether a mod can be resolved by clients or not
-#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum ResolvableStatus {
     Unresolvable(String),
     Resolvable,
+    /// Only available after signing in at the contained URL, e.g. for supporter exclusive mods
+    RequiresAuth(String),
+    /// Superseded by another mod which is installed instead
+    Deprecated {
+        replaced_by: ModSpecification,
+    },
 }
 
 /// Points to a mod, optionally a specific version
@@ -22,26 +33,271 @@ pub enum ResolvableStatus {
 pub enum ModType {
     ModPlugin,
     Pak,
+    /// Loose assets merged into the game's `Content` directory
+    Blueprint,
+    /// INI overrides merged into the game's config files
+    Config,
 }
 
+/// Id of a provider. Spelled as an alias so serde does not implicitly borrow it, which would
+/// require the deserializer to outlive `'static`.
+type ProviderId = &'static str;
+
 /// Returned from ModStore
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModInfo {
-    pub provider: &'static str,
+    #[serde(deserialize_with = "deserialize_provider")]
+    pub provider: ProviderId,
     pub name: String,
     pub spec: ModSpecification,          // unpinned version
     pub versions: Vec<ModSpecification>, // pinned versions TODO make this a different type
     pub resolution: ModResolution,
     pub suggested_require: bool,
     pub suggested_dependencies: Vec<ModSpecification>, // ModResponse
+    /// Dependencies the mod works without, skipped if they cannot be resolved
+    #[serde(default)]
+    pub optional_dependencies: Vec<ModSpecification>,
+    /// Mods the author declared to be incompatible with this one
+    #[serde(default)]
+    pub conflicts_with: Vec<ModSpecification>,
     pub mod_type: ModType,
+    pub min_game_version: Option<SemverVersion>,
+    /// Load order priority, lower priorities are loaded first
+    pub priority: i32,
+    /// Release notes in markdown
+    pub changelog: Option<String>,
+    pub category: Option<ModCategory>,
+    /// Free-form labels assigned by the author or provider
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub authors: Vec<Author>,
+    /// SPDX license expression
+    pub license: Option<String>,
+    /// Page describing the mod, unlike `resolution.url` which is used to download it
+    pub homepage: Option<String>,
+    /// Summary of the mod written by the author
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Provider specific fields that do not fit the common schema, e.g. the endorsement count on
+    /// Nexus Mods. An object keyed by field name, or null if the provider has none.
+    #[serde(default)]
+    pub extra_metadata: serde_json::Value,
 }
 
+impl ModInfo {
+    pub fn builder() -> ModInfoBuilder {
+        ModInfoBuilder::default()
+    }
+
+    /// Field of `extra_metadata`, `None` if it is missing or cannot be deserialized as `T`
+    pub fn extra<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        serde_json::from_value(self.extra_metadata.get(key)?.clone()).ok()
+    }
+}
+
+/// Builds a `ModInfo`, optional fields default to empty and `priority` to `DEFAULT_PRIORITY`.
+#[derive(Debug, Default, Clone)]
+pub struct ModInfoBuilder {
+    provider: Option<&'static str>,
+    name: Option<String>,
+    spec: Option<ModSpecification>,
+    versions: Vec<ModSpecification>,
+    resolution: Option<ModResolution>,
+    suggested_require: bool,
+    suggested_dependencies: Vec<ModSpecification>,
+    optional_dependencies: Vec<ModSpecification>,
+    conflicts_with: Vec<ModSpecification>,
+    mod_type: Option<ModType>,
+    min_game_version: Option<SemverVersion>,
+    priority: Option<i32>,
+    changelog: Option<String>,
+    category: Option<ModCategory>,
+    tags: Vec<String>,
+    authors: Vec<Author>,
+    license: Option<String>,
+    homepage: Option<String>,
+    description: Option<String>,
+    extra_metadata: serde_json::Value,
+}
+
+impl ModInfoBuilder {
+    pub fn provider(mut self, provider: &'static str) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+    pub fn spec(mut self, spec: ModSpecification) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+    pub fn versions(mut self, versions: Vec<ModSpecification>) -> Self {
+        self.versions = versions;
+        self
+    }
+    pub fn resolution(mut self, resolution: ModResolution) -> Self {
+        self.resolution = Some(resolution);
+        self
+    }
+    pub fn suggested_require(mut self, suggested_require: bool) -> Self {
+        self.suggested_require = suggested_require;
+        self
+    }
+    pub fn suggested_dependencies(mut self, dependencies: Vec<ModSpecification>) -> Self {
+        self.suggested_dependencies = dependencies;
+        self
+    }
+    pub fn optional_dependencies(mut self, dependencies: Vec<ModSpecification>) -> Self {
+        self.optional_dependencies = dependencies;
+        self
+    }
+    pub fn conflicts_with(mut self, conflicts: Vec<ModSpecification>) -> Self {
+        self.conflicts_with = conflicts;
+        self
+    }
+    pub fn mod_type(mut self, mod_type: ModType) -> Self {
+        self.mod_type = Some(mod_type);
+        self
+    }
+    pub fn min_game_version(mut self, version: impl Into<Option<SemverVersion>>) -> Self {
+        self.min_game_version = version.into();
+        self
+    }
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+    pub fn changelog(mut self, changelog: impl Into<Option<String>>) -> Self {
+        self.changelog = changelog.into();
+        self
+    }
+    pub fn category(mut self, category: impl Into<Option<ModCategory>>) -> Self {
+        self.category = category.into();
+        self
+    }
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+    pub fn authors(mut self, authors: Vec<Author>) -> Self {
+        self.authors = authors;
+        self
+    }
+    pub fn license(mut self, license: impl Into<Option<String>>) -> Self {
+        self.license = license.into();
+        self
+    }
+    pub fn homepage(mut self, homepage: impl Into<Option<String>>) -> Self {
+        self.homepage = homepage.into();
+        self
+    }
+    pub fn description(mut self, description: impl Into<Option<String>>) -> Self {
+        self.description = description.into();
+        self
+    }
+    pub fn extra_metadata(mut self, extra_metadata: serde_json::Value) -> Self {
+        self.extra_metadata = extra_metadata;
+        self
+    }
+    /// Fails if `provider`, `name`, `spec`, `resolution` or `mod_type` was not set.
+    pub fn build(self) -> Result<ModInfo> {
+        let missing = |field: &str| format!("missing required mod info field `{field}`");
+        Ok(ModInfo {
+            provider: self.provider.with_context(|| missing("provider"))?,
+            name: self.name.with_context(|| missing("name"))?,
+            spec: self.spec.with_context(|| missing("spec"))?,
+            versions: self.versions,
+            resolution: self.resolution.with_context(|| missing("resolution"))?,
+            suggested_require: self.suggested_require,
+            suggested_dependencies: self.suggested_dependencies,
+            optional_dependencies: self.optional_dependencies,
+            conflicts_with: self.conflicts_with,
+            mod_type: self.mod_type.with_context(|| missing("mod_type"))?,
+            min_game_version: self.min_game_version,
+            priority: self.priority.unwrap_or(DEFAULT_PRIORITY),
+            changelog: self.changelog,
+            category: self.category,
+            tags: self.tags,
+            authors: self.authors,
+            license: self.license,
+            homepage: self.homepage,
+            description: self.description,
+            extra_metadata: self.extra_metadata,
+        })
+    }
+}
+
+/// Provider ids are `&'static str` as they are declared by the providers themselves. Deserialized
+/// ids are interned so each distinct id is only leaked once.
+fn deserialize_provider<'de, D>(deserializer: D) -> Result<&'static str, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use std::sync::Mutex;
+
+    static PROVIDERS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+    let id = String::deserialize(deserializer)?;
+    let mut providers = PROVIDERS.get_or_init(Default::default).lock().unwrap();
+    if let Some(id) = providers.get(id.as_str()) {
+        return Ok(*id);
+    }
+    let id: &'static str = id.leak();
+    providers.insert(id);
+    Ok(id)
+}
+
+/// Mods having `tag`, ignoring case.
+pub fn filter_by_tag<'a>(mods: &'a [ModInfo], tag: &str) -> Vec<&'a ModInfo> {
+    mods.iter()
+        .filter(|m| m.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .collect()
+}
+
+/// Every tag used by any of the mods, e.g. for autocompletion.
+pub fn all_tags(mods: &[ModInfo]) -> BTreeSet<&str> {
+    mods.iter()
+        .flat_map(|m| m.tags.iter().map(String::as_str))
+        .collect()
+}
+
+/// Provider independent category of a mod
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModCategory {
+    Gameplay,
+    Cosmetic,
+    Audio,
+    UI,
+    Utility,
+    Debug,
+    Other,
+}
+
+impl Display for ModCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ModCategory::Gameplay => "Gameplay",
+            ModCategory::Cosmetic => "Cosmetic",
+            ModCategory::Audio => "Audio",
+            ModCategory::UI => "UI",
+            ModCategory::Utility => "Utility",
+            ModCategory::Debug => "Debug",
+            ModCategory::Other => "Other",
+        })
+    }
+}
+
+/// Load order priority used when a mod does not specify one
+pub const DEFAULT_PRIORITY: i32 = 100;
+
 /// Returned from ModProvider
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ModResponse {
     Redirect(ModSpecification),
-    Resolve(ModInfo),
+    Resolve(Box<ModInfo>),
 }
 
 /// Points to a mod, optionally a specific version
@@ -52,18 +308,241 @@ pub struct ModSpecification {
     pub url: String,
 }
 
+/// Why `ModSpecification::checked_new` rejected a URL
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum SpecError {
+    #[snafu(display("mod URL is empty"))]
+    EmptyUrl,
+    #[snafu(display("no provider handles URLs starting with \"{scheme}:\""))]
+    UnrecognizedScheme { scheme: String },
+    #[snafu(display("mod URL is neither a path nor contains a scheme"))]
+    MalformedUrl,
+}
+
+fn url_schemes() -> &'static RwLock<HashSet<&'static str>> {
+    static URL_SCHEMES: OnceLock<RwLock<HashSet<&'static str>>> = OnceLock::new();
+    URL_SCHEMES.get_or_init(Default::default)
+}
+
+/// Registers a URL scheme accepted by `ModSpecification::checked_new`, called for every
+/// provider when the mod store is created.
+pub fn register_url_scheme(scheme: &'static str) {
+    url_schemes().write().unwrap().insert(scheme);
+}
+
 impl ModSpecification {
     pub fn new(url: String) -> Self {
         Self { url }
     }
+    /// Same as `new` but rejects URLs no provider could handle. URLs without a scheme are local
+    /// paths, single letter schemes are Windows drive letters. Relative paths without a separator
+    /// such as `mod.pak` are accepted if the file exists.
+    pub fn checked_new(url: String) -> Result<Self, SpecError> {
+        ensure!(!url.trim().is_empty(), EmptyUrlSnafu);
+        ensure!(
+            url.contains(['/', '\\', ':']) || std::path::Path::new(&url).exists(),
+            MalformedUrlSnafu
+        );
+        let scheme = url.split_once(':').map(|(scheme, _)| scheme).filter(|s| {
+            s.len() > 1
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        });
+        if let Some(scheme) = scheme {
+            ensure!(
+                url_schemes().read().unwrap().contains(scheme),
+                UnrecognizedSchemeSnafu { scheme }
+            );
+        }
+        Ok(Self { url })
+    }
+    /// Whether the segments of either URL start with all segments of the other, e.g.
+    /// `github:owner/repo` and `github:Owner/Repo@v1`, or `nexusmods:dbsz/1/` and every file of
+    /// that mod, but not `github:owner/repo-extra`
     pub fn satisfies_dependency(&self, other: &ModSpecification) -> bool {
-        // TODO this hack works surprisingly well but is still a complete hack and should be replaced
-        self.url.starts_with(&other.url) || other.url.starts_with(&self.url)
+        let this = self.dependency_segments();
+        let other = other.dependency_segments();
+        let len = this.len().min(other.len());
+        len > 0 && this[..len] == other[..len]
+    }
+    /// Normalized `/` and `@` separated segments of the URL without its version constraint
+    fn dependency_segments(&self) -> Vec<ModIdentifier> {
+        self.without_version_constraint()
+            .url
+            .split(['/', '@'])
+            .map(ModIdentifier::normalize)
+            .filter(|s| !s.0.is_empty())
+            .collect()
+    }
+    /// Appends `constraint` percent-encoded to the URL as the `v` query parameter, e.g.
+    /// `<url>?v=%5E1.2` for `^1.2`
+    pub fn with_version_constraint(base_url: &str, constraint: &str) -> Self {
+        let separator = if base_url.contains('?') { '&' } else { '?' };
+        Self::new(format!(
+            "{base_url}{separator}v={}",
+            percent_encode(constraint)
+        ))
+    }
+    /// Value of the `v` query parameter with its percent-encoding decoded
+    pub fn version_constraint_str(&self) -> Option<String> {
+        let (_, query) = self.url.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("v="))
+            .map(percent_decode)
+    }
+    /// Version constraint given by the `v` query parameter, `None` if missing or malformed
+    pub fn version_constraint(&self) -> Option<VersionConstraint> {
+        VersionConstraint::parse(&self.version_constraint_str()?).ok()
+    }
+    /// The specification without the `v` query parameter, as understood by providers
+    pub fn without_version_constraint(&self) -> Self {
+        let Some((base, query)) = self.url.split_once('?') else {
+            return self.clone();
+        };
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.starts_with("v="))
+            .collect::<Vec<_>>();
+        if query.is_empty() {
+            Self::new(base.to_string())
+        } else {
+            Self::new(format!("{base}?{}", query.join("&")))
+        }
+    }
+}
+
+/// Encodes everything but unreserved URL characters
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Range of versions accepted by a mod specification. `min` is inclusive and `max` is exclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionConstraint {
+    pub min: SemverVersion,
+    pub max: Option<SemverVersion>,
+}
+
+impl VersionConstraint {
+    /// Parses `>=1.0.0`, `^1.2`, `~1.0.0` or an exact version such as `1.2.3` or `=1.2`. Missing
+    /// components of an exact version match any value.
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (op, version) = if let Some(v) = s.strip_prefix(">=") {
+            (">=", v)
+        } else if let Some(v) = s.strip_prefix('^') {
+            ("^", v)
+        } else if let Some(v) = s.strip_prefix('~') {
+            ("~", v)
+        } else {
+            ("=", s.strip_prefix('=').unwrap_or(s))
+        };
+
+        let parts = version
+            .trim()
+            .split('.')
+            .map(|p| {
+                p.parse::<u32>()
+                    .with_context(|| format!("invalid version constraint {s:?}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let &[major, ref rest @ ..] = parts.as_slice() else {
+            bail!("invalid version constraint {s:?}");
+        };
+        if rest.len() > 2 {
+            bail!("invalid version constraint {s:?}");
+        }
+        let minor = rest.first().copied();
+        let patch = rest.get(1).copied();
+
+        let min = SemverVersion {
+            major,
+            minor: minor.unwrap_or(0),
+            patch: patch.unwrap_or(0),
+            pre_release: None,
+        };
+        let bump_major = || SemverVersion {
+            major: major + 1,
+            minor: 0,
+            patch: 0,
+            pre_release: None,
+        };
+        let bump_minor = |minor| SemverVersion {
+            major,
+            minor: minor + 1,
+            patch: 0,
+            pre_release: None,
+        };
+        let bump_patch = |minor, patch| SemverVersion {
+            major,
+            minor,
+            patch: patch + 1,
+            pre_release: None,
+        };
+        let max = match (op, minor, patch) {
+            (">=", ..) => None,
+            ("^", _, _) if major > 0 => Some(bump_major()),
+            ("^", Some(0), Some(patch)) => Some(bump_patch(0, patch)),
+            ("^", Some(minor), _) => Some(bump_minor(minor)),
+            ("~", Some(minor), _) => Some(bump_minor(minor)),
+            ("=", Some(minor), Some(patch)) => Some(bump_patch(minor, patch)),
+            ("=", Some(minor), None) => Some(bump_minor(minor)),
+            _ => Some(bump_major()),
+        };
+        Ok(Self { min, max })
+    }
+    pub fn matches(&self, version: &SemverVersion) -> bool {
+        *version >= self.min && self.max.as_ref().is_none_or(|max| version < max)
+    }
+    /// Returns the item with the highest version satisfying the constraint
+    pub fn select<T>(&self, versions: impl IntoIterator<Item = (SemverVersion, T)>) -> Option<T> {
+        versions
+            .into_iter()
+            .filter(|(v, _)| self.matches(v))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, item)| item)
+    }
+}
+
+impl Display for VersionConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.max {
+            Some(max) => write!(f, ">={}, <{}", self.min, max),
+            None => write!(f, ">={}", self.min),
+        }
     }
 }
 
 /// Points to a specific version of a specific mod
-#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct ModResolution {
     pub url: ModIdentifier,
     pub status: ResolvableStatus,
@@ -85,7 +564,9 @@ impl ModResolution {
     /// Used to get the URL if resolvable or just return the mod name if not
     pub fn get_resolvable_url_or_name(&self) -> &str {
         match &self.status {
-            ResolvableStatus::Resolvable => &self.url.0,
+            ResolvableStatus::Resolvable
+            | ResolvableStatus::RequiresAuth(_)
+            | ResolvableStatus::Deprecated { .. } => &self.url.0,
             ResolvableStatus::Unresolvable(name) => name,
         }
     }
@@ -99,6 +580,25 @@ impl ModIdentifier {
     pub fn new(s: String) -> Self {
         Self(s)
     }
+    /// Lowercases `s` and replaces every run of other characters than letters and digits with a
+    /// single `-`, so `"My Mod"`, `"my_mod"` and `"my-mod"` compare equal.
+    pub fn normalize(s: &str) -> Self {
+        let mut normalized = String::with_capacity(s.len());
+        for c in s.chars() {
+            if c.is_alphanumeric() {
+                normalized.extend(c.to_lowercase());
+            } else if !normalized.is_empty() && !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        }
+        if normalized.ends_with('-') {
+            normalized.pop();
+        }
+        Self(normalized)
+    }
+    pub fn is_normalized(&self) -> bool {
+        *self == Self::normalize(&self.0)
+    }
 }
 impl From<String> for ModIdentifier {
     fn from(value: String) -> Self {
@@ -111,33 +611,631 @@ impl From<&str> for ModIdentifier {
     }
 }
 
+/// Current `Meta::schema_version`, older metas are migrated when read
+pub const META_SCHEMA_VERSION: u32 = 1;
+
 /// Stripped down mod info stored in the mod pak to be used in game
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Meta {
+    pub schema_version: u32,
     pub version: SemverVersion,
     pub mods: Vec<MetaMod>,
     pub config: MetaConfig,
 }
-#[derive(Debug, Serialize, Deserialize)]
+
+/// Problem with a `Meta` found by `Meta::validate`
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum MetaValidationError {
+    #[snafu(display("meta contains no mods"))]
+    EmptyModList,
+    #[snafu(display("invalid meta version {found}"))]
+    InvalidVersion { found: String },
+    #[snafu(display("mod {mod_name:?} is missing `{field}`"))]
+    MissingModField {
+        field: &'static str,
+        mod_name: String,
+    },
+    #[snafu(display("invalid mod url <{url}>"))]
+    InvalidUrl { url: String },
+}
+
+impl Meta {
+    /// Checks that the meta is complete enough to be shown in game, returning every problem found.
+    pub fn validate(&self) -> Result<(), Vec<MetaValidationError>> {
+        let mut errors = vec![];
+        if self.mods.is_empty() {
+            errors.push(MetaValidationError::EmptyModList);
+        }
+        if self.version == SemverVersion::default() {
+            errors.push(MetaValidationError::InvalidVersion {
+                found: self.version.to_string(),
+            });
+        }
+        for m in &self.mods {
+            for (field, value) in [("name", &m.name), ("version", &m.version), ("url", &m.url)] {
+                if value.trim().is_empty() {
+                    errors.push(MetaValidationError::MissingModField {
+                        field,
+                        mod_name: m.name.clone(),
+                    });
+                }
+            }
+            if m.authors.iter().all(|a| a.name.trim().is_empty()) {
+                errors.push(MetaValidationError::MissingModField {
+                    field: "authors",
+                    mod_name: m.name.clone(),
+                });
+            }
+            if !m.url.trim().is_empty() && url::Url::parse(&m.url).is_err() {
+                errors.push(MetaValidationError::InvalidUrl { url: m.url.clone() });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// `Meta` written before schema versions were introduced
+#[derive(Debug, Deserialize)]
+struct OldMetaV0 {
+    version: SemverVersion,
+    mods: Vec<MetaMod>,
+}
+
+fn migrate_v0_to_v1(old: OldMetaV0) -> Meta {
+    Meta {
+        schema_version: 1,
+        version: old.version,
+        mods: old.mods,
+        config: MetaConfig::default(),
+    }
+}
+
+/// Meta read by `Meta::from_disk` along with the schema version it was stored as
+#[derive(Debug)]
+pub struct MetaMigrationResult {
+    pub migrated_from: u32,
+    pub meta: Meta,
+}
+
+impl Meta {
+    /// Reads a `mint.json` or `mint.toml` meta depending on the extension, migrating it to the
+    /// current schema if it was written by an older version.
+    pub fn from_disk<P: AsRef<std::path::Path>>(path: P) -> Result<MetaMigrationResult> {
+        let path = path.as_ref();
+        let buf =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml"))
+        {
+            Self::from_value(toml::from_str(std::str::from_utf8(&buf)?)?)
+        } else {
+            Self::from_json(&buf)
+        }
+    }
+
+    pub fn from_toml(s: &str) -> Result<MetaMigrationResult> {
+        Self::from_value(toml::from_str(s)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    fn from_json(buf: &[u8]) -> Result<MetaMigrationResult> {
+        Self::from_value(serde_json::from_slice(buf)?)
+    }
+
+    fn from_value(value: serde_json::Value) -> Result<MetaMigrationResult> {
+        let migrated_from = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        let meta = match migrated_from {
+            0 => migrate_v0_to_v1(serde_json::from_value(value)?),
+            META_SCHEMA_VERSION => serde_json::from_value(value)?,
+            version => bail!("unsupported meta schema version {version}"),
+        };
+        Ok(MetaMigrationResult {
+            migrated_from,
+            meta,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MetaConfig {
     pub disable_fix_exploding_gas: bool,
 }
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SemverVersion {
     pub major: u32,
     pub minor: u32,
     pub patch: u32,
+    /// Pre-release suffix such as `beta.1` in `1.2.3-beta.1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_release: Option<String>,
+}
+/// Pre-releases are ordered before the release of the same version and among each other by
+/// comparing their suffixes.
+impl Ord for SemverVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre_release, &other.pre_release) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+impl PartialOrd for SemverVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 impl Display for SemverVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre_release) = &self.pre_release {
+            write!(f, "-{pre_release}")?;
+        }
+        Ok(())
     }
 }
-#[derive(Debug, Serialize, Deserialize)]
+
+/// Why `SemverVersion::parse` rejected a version
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum SemverParseError {
+    #[snafu(display("version {version:?} is not of the form major.minor.patch"))]
+    InvalidFormat { version: String },
+    #[snafu(display("invalid component {component:?} in version {version:?}"))]
+    InvalidComponent { version: String, component: String },
+}
+
+impl SemverVersion {
+    /// Parses `1.2.3`, `v1.2.3` or `1.2.3-beta.1`. Build metadata after a `+` is ignored.
+    pub fn parse(s: &str) -> Result<Self, SemverParseError> {
+        let version = s.trim();
+        let core = version.strip_prefix(['v', 'V']).unwrap_or(version);
+        let core = core.split_once('+').map_or(core, |(core, _build)| core);
+        let (core, pre_release) = match core.split_once('-') {
+            Some((core, pre_release)) => {
+                ensure!(!pre_release.is_empty(), InvalidFormatSnafu { version });
+                (core, Some(pre_release.to_string()))
+            }
+            None => (core, None),
+        };
+
+        let parts = core
+            .split('.')
+            .map(|component| {
+                component
+                    .parse::<u32>()
+                    .map_err(|_| SemverParseError::InvalidComponent {
+                        version: version.to_string(),
+                        component: component.to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let &[major, minor, patch] = parts.as_slice() else {
+            return InvalidFormatSnafu { version }.fail();
+        };
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre_release,
+        })
+    }
+}
+
+impl std::str::FromStr for SemverVersion {
+    type Err = SemverParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct MetaMod {
     pub name: String,
     pub version: String,
     pub url: String,
-    pub author: String,
+    /// Also read from the `author` string of metas written before multiple authors were supported
+    #[serde(alias = "author", deserialize_with = "deserialize_authors")]
+    pub authors: Vec<Author>,
+    /// SPDX license expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Release notes of the mod version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<String>,
     pub required: bool,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Author {
+    pub name: String,
+    /// Profile page of the author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Author {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: None,
+        }
+    }
+}
+
+impl Display for Author {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.url {
+            Some(url) => write!(f, "{} <{url}>", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Accepts a bare author name as well as a list of authors.
+fn deserialize_authors<'de, D>(deserializer: D) -> Result<Vec<Author>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Authors {
+        Name(String),
+        List(Vec<Author>),
+    }
+
+    Ok(match Authors::deserialize(deserializer)? {
+        Authors::Name(name) => vec![Author::new(name)],
+        Authors::List(authors) => authors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(major: u32, minor: u32, patch: u32) -> SemverVersion {
+        SemverVersion {
+            major,
+            minor,
+            patch,
+            pre_release: None,
+        }
+    }
+
+    #[test]
+    fn test_version_constraint() {
+        let c = VersionConstraint::parse(">=1.0.0").unwrap();
+        assert!(c.matches(&v(1, 0, 0)));
+        assert!(c.matches(&v(3, 2, 1)));
+        assert!(!c.matches(&v(0, 9, 9)));
+
+        let c = VersionConstraint::parse("^1.2").unwrap();
+        assert!(c.matches(&v(1, 2, 0)));
+        assert!(c.matches(&v(1, 9, 0)));
+        assert!(!c.matches(&v(2, 0, 0)));
+        assert!(!c.matches(&v(1, 1, 9)));
+
+        let c = VersionConstraint::parse("~1.0.0").unwrap();
+        assert!(c.matches(&v(1, 0, 5)));
+        assert!(!c.matches(&v(1, 1, 0)));
+
+        let c = VersionConstraint::parse("1.2.3").unwrap();
+        assert!(c.matches(&v(1, 2, 3)));
+        assert!(!c.matches(&v(1, 2, 4)));
+
+        assert!(VersionConstraint::parse("latest").is_err());
+        assert!(VersionConstraint::parse("1.2.3.4").is_err());
+    }
+
+    #[test]
+    fn test_spec_version_constraint() {
+        let spec = ModSpecification::new("github:owner/repo?v=%5E1.2".to_string());
+        let c = spec.version_constraint().unwrap();
+        assert_eq!(
+            c.select([(v(1, 2, 0), "a"), (v(1, 5, 0), "b"), (v(2, 0, 0), "c")]),
+            Some("b")
+        );
+        assert_eq!(
+            ModSpecification::new("https://example.com/mod.pak".to_string()).version_constraint(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mod_response_round_trip() {
+        let info = ModInfo::builder()
+            .provider("github")
+            .name("Test Mod")
+            .spec(ModSpecification::new("github:owner/repo".to_string()))
+            .versions(vec![ModSpecification::new(
+                "github:owner/repo@v1.0.0".to_string(),
+            )])
+            .resolution(ModResolution::unresolvable(
+                "github:owner/repo@v1.0.0".into(),
+                "test-mod.zip".to_string(),
+            ))
+            .mod_type(ModType::Pak)
+            .min_game_version(v(1, 2, 0))
+            .category(ModCategory::Gameplay)
+            .build()
+            .unwrap();
+        for response in [
+            ModResponse::Resolve(Box::new(info.clone())),
+            ModResponse::Redirect(info.spec.clone()),
+        ] {
+            let json = serde_json::to_string(&response).unwrap();
+            assert_eq!(response, serde_json::from_str(&json).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_validate_meta() {
+        let mut meta = Meta {
+            schema_version: META_SCHEMA_VERSION,
+            version: v(0, 1, 0),
+            mods: vec![MetaMod {
+                name: "Test Mod".to_string(),
+                version: "v1.0.0".to_string(),
+                url: "https://example.org/test-mod.zip".to_string(),
+                authors: vec![Author::new("someone")],
+                license: None,
+                changelog: None,
+                required: true,
+            }],
+            config: MetaConfig {
+                disable_fix_exploding_gas: false,
+            },
+        };
+        assert_eq!(meta.validate(), Ok(()));
+
+        meta.version = v(0, 0, 0);
+        meta.mods[0].authors.clear();
+        meta.mods[0].url = "not a url".to_string();
+        assert_eq!(
+            meta.validate(),
+            Err(vec![
+                MetaValidationError::InvalidVersion {
+                    found: "0.0.0".to_string()
+                },
+                MetaValidationError::MissingModField {
+                    field: "authors",
+                    mod_name: "Test Mod".to_string()
+                },
+                MetaValidationError::InvalidUrl {
+                    url: "not a url".to_string()
+                },
+            ])
+        );
+
+        meta.mods.clear();
+        assert_eq!(
+            meta.validate().unwrap_err()[0],
+            MetaValidationError::EmptyModList
+        );
+    }
+
+    #[test]
+    fn test_migrate_meta() {
+        let legacy = r#"{"version":{"major":0,"minor":1,"patch":0},"mods":[{"name":"a","version":"1","url":"https://example.org/a","author":"someone","required":true}]}"#;
+        let result = Meta::from_json(legacy.as_bytes()).unwrap();
+        assert_eq!(result.migrated_from, 0);
+        assert_eq!(result.meta.mods[0].authors, [Author::new("someone")]);
+        assert_eq!(result.meta.schema_version, META_SCHEMA_VERSION);
+        assert!(!result.meta.config.disable_fix_exploding_gas);
+
+        let current = serde_json::to_vec(&result.meta).unwrap();
+        assert_eq!(
+            Meta::from_json(&current).unwrap().migrated_from,
+            META_SCHEMA_VERSION
+        );
+        assert!(Meta::from_json(br#"{"schema_version":99}"#).is_err());
+    }
+
+    fn arb_meta() -> impl proptest::strategy::Strategy<Value = Meta> {
+        use proptest::prelude::*;
+
+        // printable ASCII including quotes and backslashes, which TOML has to escape
+        let text = "[ -~\n\t]{0,16}";
+        let author =
+            (text, proptest::option::of(text)).prop_map(|(name, url)| Author { name, url });
+        let meta_mod = (
+            text,
+            text,
+            text,
+            proptest::collection::vec(author, 0..3),
+            proptest::option::of(text),
+            proptest::option::of(text),
+            any::<bool>(),
+        )
+            .prop_map(
+                |(name, version, url, authors, license, changelog, required)| MetaMod {
+                    name,
+                    version,
+                    url,
+                    authors,
+                    license,
+                    changelog,
+                    required,
+                },
+            );
+        let version = (
+            any::<u32>(),
+            any::<u32>(),
+            any::<u32>(),
+            proptest::option::of("[0-9A-Za-z.]{1,8}"),
+        )
+            .prop_map(|(major, minor, patch, pre_release)| SemverVersion {
+                major,
+                minor,
+                patch,
+                pre_release,
+            });
+        (
+            version,
+            proptest::collection::vec(meta_mod, 0..4),
+            any::<bool>(),
+        )
+            .prop_map(|(version, mods, disable_fix_exploding_gas)| Meta {
+                schema_version: META_SCHEMA_VERSION,
+                version,
+                mods,
+                config: MetaConfig {
+                    disable_fix_exploding_gas,
+                },
+            })
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_meta_toml_round_trip(meta in arb_meta()) {
+            let result = Meta::from_toml(&meta.to_toml().unwrap()).unwrap();
+            proptest::prop_assert_eq!(result.migrated_from, META_SCHEMA_VERSION);
+            proptest::prop_assert_eq!(result.meta, meta);
+        }
+    }
+
+    #[test]
+    fn test_parse_semver() {
+        assert_eq!(SemverVersion::parse("1.2.3"), Ok(v(1, 2, 3)));
+        assert_eq!("v1.2.3".parse(), Ok(v(1, 2, 3)));
+        let beta = SemverVersion::parse("1.2.3-beta.1").unwrap();
+        assert_eq!(beta.pre_release.as_deref(), Some("beta.1"));
+        assert_eq!(beta.to_string(), "1.2.3-beta.1");
+        assert_eq!(
+            SemverVersion::parse("1.2"),
+            Err(SemverParseError::InvalidFormat {
+                version: "1.2".to_string()
+            })
+        );
+        assert!(SemverVersion::parse("1.x.3").is_err());
+        assert!(SemverVersion::parse("1.2.3-").is_err());
+    }
+
+    #[test]
+    fn test_semver_pre_release_order() {
+        let versions = ["1.0.0-alpha", "1.0.0", "1.0.1-beta", "1.0.1"]
+            .map(|v| SemverVersion::parse(v).unwrap());
+        for pair in versions.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+        assert!(SemverVersion::parse("1.0.0-alpha").unwrap() < "1.0.0-beta".parse().unwrap());
+    }
+
+    #[test]
+    fn test_filter_by_tag() {
+        let with_tags = |name: &str, tags: &[&str]| {
+            ModInfo::builder()
+                .provider("test")
+                .name(name)
+                .spec(ModSpecification::new(name.to_string()))
+                .resolution(ModResolution::resolvable(name.into()))
+                .mod_type(ModType::Pak)
+                .tags(tags.iter().map(|t| t.to_string()).collect())
+                .build()
+                .unwrap()
+        };
+        let mods = [
+            with_tags("a", &["DragonBall", "competitive-safe"]),
+            with_tags("b", &["dragonball"]),
+            with_tags("c", &[]),
+        ];
+        let names = |mods: Vec<&ModInfo>| mods.iter().map(|m| m.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(filter_by_tag(&mods, "dragonball")), ["a", "b"]);
+        assert_eq!(names(filter_by_tag(&mods, "Competitive-Safe")), ["a"]);
+        assert_eq!(
+            all_tags(&mods),
+            BTreeSet::from(["DragonBall", "competitive-safe", "dragonball"])
+        );
+    }
+
+    #[test]
+    fn test_normalize_identifier() {
+        assert_eq!(ModIdentifier::normalize("MyMod").0, "mymod");
+        assert_eq!(ModIdentifier::normalize("-My  Mod_v2-").0, "my-mod-v2");
+        assert!(ModIdentifier::normalize("github:Owner/Repo").is_normalized());
+        assert!(!ModIdentifier::from("My Mod").is_normalized());
+        assert!(ModSpecification::new("github:Owner/Repo@v1".to_string())
+            .satisfies_dependency(&ModSpecification::new("github:owner/repo".to_string())));
+        assert!(
+            !ModSpecification::new("github:owner/repo-extra".to_string())
+                .satisfies_dependency(&ModSpecification::new("github:owner/repo".to_string()))
+        );
+        assert!(ModSpecification::new("nexusmods:dbsz/12/34".to_string())
+            .satisfies_dependency(&ModSpecification::new("nexusmods:dbsz/12/".to_string())));
+        assert!(!ModSpecification::new("nexusmods:dbsz/123/4".to_string())
+            .satisfies_dependency(&ModSpecification::new("nexusmods:dbsz/12/".to_string())));
+    }
+
+    #[test]
+    fn test_checked_new() {
+        register_url_scheme("github");
+        assert!(ModSpecification::checked_new("github:owner/repo".to_string()).is_ok());
+        assert!(ModSpecification::checked_new("./mods/test.pak".to_string()).is_ok());
+        assert!(ModSpecification::checked_new("C:\\mods\\test.pak".to_string()).is_ok());
+        // tests run in the crate directory
+        assert!(ModSpecification::checked_new("Cargo.toml".to_string()).is_ok());
+        assert_eq!(
+            ModSpecification::checked_new(" ".to_string()).unwrap_err(),
+            SpecError::EmptyUrl
+        );
+        assert_eq!(
+            ModSpecification::checked_new("test".to_string()).unwrap_err(),
+            SpecError::MalformedUrl
+        );
+        assert_eq!(
+            ModSpecification::checked_new("ftp://example.com/mod.pak".to_string()).unwrap_err(),
+            SpecError::UnrecognizedScheme {
+                scheme: "ftp".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_with_version_constraint() {
+        let spec =
+            ModSpecification::with_version_constraint("https://example.com/mod?id=1", "~1.0");
+        assert_eq!(spec.url, "https://example.com/mod?id=1&v=~1.0");
+        assert_eq!(spec.version_constraint_str().as_deref(), Some("~1.0"));
+        assert!(spec.version_constraint().is_some());
+        assert_eq!(
+            spec.without_version_constraint().url,
+            "https://example.com/mod?id=1"
+        );
+        assert_eq!(
+            ModSpecification::with_version_constraint("github:owner/repo", "^1")
+                .without_version_constraint()
+                .url,
+            "github:owner/repo"
+        );
+
+        let spec = ModSpecification::with_version_constraint("github:owner/repo", ">=1.2");
+        assert_eq!(spec.url, "github:owner/repo?v=%3E%3D1.2");
+        assert_eq!(spec.version_constraint_str().as_deref(), Some(">=1.2"));
+        assert_eq!(
+            spec.version_constraint(),
+            VersionConstraint::parse(">=1.2").ok()
+        );
+
+        for constraint in [">=1.2, <2", "^1.0.0-beta+build", "=1&v=2", "~1.0 %20"] {
+            let spec = ModSpecification::with_version_constraint("github:owner/repo", constraint);
+            assert_eq!(spec.version_constraint_str().as_deref(), Some(constraint));
+            assert_eq!(spec.without_version_constraint().url, "github:owner/repo");
+        }
+    }
+}
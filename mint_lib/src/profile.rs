@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+
+use crate::mod_info::ModSpecification;
+
+/// Named list of mods which can be shared with other users.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mods: Vec<ModSpecification>,
+    /// SHA-256 of the paks installed for each mod, keyed by mod URL. Only set for profiles
+    /// exported from an installation.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pak_hashes: BTreeMap<String, Vec<String>>,
+    /// Version of mint which exported the profile
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub generated_by: String,
+}
+
+impl Profile {
+    /// Writes the profile to `path` as TOML.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let toml = self.to_toml()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml)?;
+        Ok(())
+    }
+
+    /// Parses a TOML profile, checking that every mod specification is well-formed.
+    pub fn from_toml(s: &str) -> Result<Self> {
+        let profile: Self = toml::from_str(s).context("failed to parse profile")?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Reads a TOML profile from `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+            .with_context(|| format!("failed to load profile {}", path.display()))
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("failed to serialize profile")
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize profile")
+    }
+
+    /// Parses a JSON profile, checking that every mod specification is well-formed.
+    pub fn from_json(s: &str) -> Result<Self> {
+        let profile: Self = serde_json::from_str(s).context("failed to parse profile")?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Fetches a JSON profile shared at `url`.
+    pub async fn from_url(url: &str) -> Result<Self> {
+        let json = reqwest::get(url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("failed to fetch profile from {url}"))?
+            .text()
+            .await
+            .with_context(|| format!("failed to fetch profile from {url}"))?;
+        Self::from_json(&json)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for spec in &self.mods {
+            let url = spec.url.as_str();
+            if url.trim().is_empty() {
+                bail!("profile {:?} contains a mod with an empty URL", self.name);
+            }
+            if url.trim() != url || url.chars().any(char::is_control) {
+                bail!("profile {:?} contains malformed mod URL {url:?}", self.name);
+            }
+            if url.contains("://") {
+                url::Url::parse(url).with_context(|| {
+                    format!("profile {:?} contains malformed mod URL {url:?}", self.name)
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_save_load_round_trip() {
+        let profile = Profile {
+            name: "friends".to_string(),
+            description: Some("Mods for \"friday\" runs".to_string()),
+            mods: vec![
+                ModSpecification::new("https://example.org/mod.zip".to_string()),
+                ModSpecification::new("./local/mod.pak".to_string()),
+            ],
+            pak_hashes: BTreeMap::from([(
+                "https://example.org/mod.zip".to_string(),
+                vec!["00ff".to_string()],
+            )]),
+            generated_by: "mint 0.0.0".to_string(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles").join("friends.toml");
+        profile.save(&path).unwrap();
+        assert_eq!(Profile::load(&path).unwrap(), profile);
+    }
+
+    #[test]
+    fn test_profile_load_rejects_malformed_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.toml");
+        fs::write(
+            &path,
+            "name = \"broken\"\n[[mods]]\nurl = \" https://example.org\"\n",
+        )
+        .unwrap();
+        assert!(Profile::load(&path).is_err());
+    }
+}
@@ -1,18 +1,168 @@
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use ed25519_dalek::pkcs8::DecodePublicKey;
+use ed25519_dalek::{Signature, VerifyingKey};
+use fs_err as fs;
+use sha2::{Digest, Sha256};
+use snafu::prelude::*;
+use tracing::*;
+
 use crate::error::GenericError;
 use crate::error::ResultExt;
+use crate::mod_info::{SemverParseError, SemverVersion};
 
 pub const GITHUB_RELEASE_URL: &str = "https://api.github.com/repos/trumank/mint/releases/latest";
 pub const GITHUB_REQ_USER_AGENT: &str = "trumank/mint";
+/// Repository this fork of mint is released from
+pub const GITHUB_REPO: &str = "WistfulHopes/mint-SZ";
+
+/// Tag of the release the nightly GitHub Actions workflow replaces the assets of on every build
+const NIGHTLY_TAG: &str = "nightly";
+
+/// Which releases self-updates install.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum UpdateChannel {
+    /// Latest release
+    #[default]
+    Stable,
+    /// Highest version including pre-releases
+    Beta,
+    /// Latest build of the nightly workflow, installed whenever it differs from the running
+    /// executable
+    Nightly,
+}
+
+impl std::fmt::Display for UpdateChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+            UpdateChannel::Nightly => "nightly",
+        })
+    }
+}
+
+impl std::str::FromStr for UpdateChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stable" => Ok(UpdateChannel::Stable),
+            "beta" => Ok(UpdateChannel::Beta),
+            "nightly" => Ok(UpdateChannel::Nightly),
+            _ => Err(format!(
+                "unknown update channel {s:?}, expected stable, beta or nightly"
+            )),
+        }
+    }
+}
+
+/// PEM public key of the ed25519 key pair release archives are signed with. The release workflow
+/// signs them with the private half stored in the `RELEASE_SIGNING_KEY` secret.
+const RELEASE_PUBLIC_KEY: &[u8] = include_bytes!("../keys/public.pem");
+
+/// Suffix of the release asset holding the SHA-256 of another asset in `sha256sum` format
+const CHECKSUM_SUFFIX: &str = ".sha256";
 
 #[derive(Debug, serde::Deserialize)]
 pub struct GitHubRelease {
     pub html_url: String,
     pub tag_name: String,
     pub body: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub assets: Vec<GitHubReleaseAsset>,
 }
 
-pub async fn get_latest_release() -> Result<GitHubRelease, GenericError> {
-    reqwest::Client::builder()
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GitHubReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl GitHubRelease {
+    fn asset(&self, name: &str) -> Result<&GitHubReleaseAsset, SelfUpdateError> {
+        self.assets
+            .iter()
+            .find(|a| a.name == name)
+            .context(MissingAssetSnafu { name })
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum SelfUpdateError {
+    #[snafu(display("no release found on the {channel} channel"))]
+    NoRelease { channel: UpdateChannel },
+    #[snafu(display("request to {url} failed"))]
+    Request { source: reqwest::Error, url: String },
+    #[snafu(display("release tag {tag:?} is not a version"))]
+    InvalidTag {
+        source: SemverParseError,
+        tag: String,
+    },
+    #[snafu(display("no release binary is built for this platform"))]
+    UnsupportedPlatform,
+    #[snafu(display("release does not contain {name}"))]
+    MissingAsset { name: String },
+    #[snafu(display("{name}{CHECKSUM_SUFFIX} does not contain a SHA-256"))]
+    MissingChecksum { name: String },
+    #[snafu(display("SHA-256 of {name} does not match {name}{CHECKSUM_SUFFIX}"))]
+    ChecksumMismatch { name: String },
+    #[snafu(display("failed to extract {binary} from {name}"))]
+    Extract {
+        source: zip::result::ZipError,
+        name: String,
+        binary: String,
+    },
+    #[snafu(display("signature of {name} is invalid"))]
+    Signature {
+        source: SignatureError,
+        name: String,
+    },
+    #[snafu(display("failed to replace the executable"))]
+    Replace { source: std::io::Error },
+}
+
+#[derive(Debug, Snafu)]
+pub enum SignatureError {
+    #[snafu(display("embedded release public key is malformed: {message}"))]
+    InvalidPublicKey { message: String },
+    #[snafu(display("signature is not a 64 byte ed25519 signature"))]
+    MalformedSignature {
+        source: ed25519_dalek::SignatureError,
+    },
+    #[snafu(display("signature does not match the binary"))]
+    Mismatch {
+        source: ed25519_dalek::SignatureError,
+    },
+}
+
+/// Checks `binary` against a raw 64 byte ed25519 signature made with the key the PEM `public_key`
+/// belongs to, as written by `openssl pkeyutl -sign -rawin`.
+fn verify_signature(
+    public_key: &[u8],
+    binary: &[u8],
+    signature: &[u8],
+) -> Result<(), SignatureError> {
+    let key = std::str::from_utf8(public_key)
+        .map_err(|e| e.to_string())
+        .and_then(|pem| VerifyingKey::from_public_key_pem(pem).map_err(|e| e.to_string()))
+        .map_err(|message| SignatureError::InvalidPublicKey { message })?;
+    let signature = Signature::from_slice(signature).context(MalformedSignatureSnafu)?;
+    key.verify_strict(binary, &signature).context(MismatchSnafu)
+}
+
+/// `client` is configured by the caller, e.g. to send requests through a proxy
+pub async fn get_latest_release(
+    client: reqwest::ClientBuilder,
+) -> Result<GitHubRelease, GenericError> {
+    client
         .user_agent(GITHUB_REQ_USER_AGENT)
         .build()
         .generic("failed to construct reqwest client".to_string())?
@@ -24,3 +174,251 @@ pub async fn get_latest_release() -> Result<GitHubRelease, GenericError> {
         .await
         .generic("check self update response is error".to_string())
 }
+
+/// Names of the cargo-dist archive built for the platform mint is running on and of the binary
+/// inside it
+fn release_archive_name() -> Option<(&'static str, &'static str)> {
+    if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+        Some(("mint-x86_64-pc-windows-msvc.zip", "mint.exe"))
+    } else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some(("mint-x86_64-unknown-linux-gnu.zip", "mint"))
+    } else {
+        None
+    }
+}
+
+/// Hash of `sha256sum` output for a single file
+pub fn parse_checksum(checksum: &str) -> Option<String> {
+    let hash = checksum.split_whitespace().next()?;
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_lowercase())
+}
+
+/// Reads `binary` from the zip archive, which may place it in a subdirectory.
+fn extract_binary(archive: &[u8], binary: &str) -> zip::result::ZipResult<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive))?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.name().rsplit(['/', '\\']).next() == Some(binary) {
+            let mut buf = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut buf)?;
+            return Ok(buf);
+        }
+    }
+    Err(zip::result::ZipError::FileNotFound)
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> Result<reqwest::Response, SelfUpdateError> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context(RequestSnafu { url })
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<T, SelfUpdateError> {
+    get(client, url)
+        .await?
+        .json()
+        .await
+        .context(RequestSnafu { url })
+}
+
+/// Release of the channel with its version. Nightly builds are versioned by the name of their
+/// release, or the current version marked as `nightly` if it has none.
+async fn channel_release(
+    client: &reqwest::Client,
+    github_repo: &str,
+    channel: UpdateChannel,
+    current_version: &SemverVersion,
+) -> Result<(GitHubRelease, SemverVersion), SelfUpdateError> {
+    let releases = format!("https://api.github.com/repos/{github_repo}/releases");
+    match channel {
+        UpdateChannel::Stable => {
+            let release: GitHubRelease = get_json(client, &format!("{releases}/latest")).await?;
+            let version = SemverVersion::parse(&release.tag_name).context(InvalidTagSnafu {
+                tag: &release.tag_name,
+            })?;
+            Ok((release, version))
+        }
+        UpdateChannel::Beta => get_json::<Vec<GitHubRelease>>(client, &releases)
+            .await?
+            .into_iter()
+            .filter(|r| !r.draft && r.tag_name != NIGHTLY_TAG)
+            .filter_map(|r| {
+                let version = SemverVersion::parse(&r.tag_name).ok()?;
+                Some((r, version))
+            })
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .context(NoReleaseSnafu { channel }),
+        UpdateChannel::Nightly => {
+            let release: GitHubRelease =
+                get_json(client, &format!("{releases}/tags/{NIGHTLY_TAG}")).await?;
+            let version = release
+                .name
+                .as_deref()
+                .and_then(|name| SemverVersion::parse(name).ok())
+                .unwrap_or_else(|| SemverVersion {
+                    pre_release: Some(NIGHTLY_TAG.to_string()),
+                    ..current_version.clone()
+                });
+            Ok((release, version))
+        }
+    }
+}
+
+/// Replaces the running executable with the release of `github_repo` on `channel` if it is newer
+/// than `current_version`, returning the version running after the update. The release archive
+/// is checked against its `.sha256` asset and its `.sig` signature before the binary inside it is
+/// installed, releases without a signature are not installed. Requests are sent by a client built
+/// from `client`.
+pub async fn self_update(
+    current_version: &SemverVersion,
+    github_repo: &str,
+    channel: UpdateChannel,
+    client: reqwest::ClientBuilder,
+) -> Result<SemverVersion, SelfUpdateError> {
+    let client = client
+        .user_agent(GITHUB_REQ_USER_AGENT)
+        .build()
+        .context(RequestSnafu { url: github_repo })?;
+    let (release, latest) = channel_release(&client, github_repo, channel, current_version).await?;
+    // nightly builds share versions so they are compared by hash below instead
+    if channel != UpdateChannel::Nightly && latest <= *current_version {
+        info!("mint {current_version} is up to date");
+        return Ok(current_version.clone());
+    }
+
+    let (name, binary_name) = release_archive_name().context(UnsupportedPlatformSnafu)?;
+    let checksum = release.asset(&format!("{name}{CHECKSUM_SUFFIX}"))?;
+    let checksum = get(&client, &checksum.browser_download_url)
+        .await?
+        .text()
+        .await
+        .context(RequestSnafu {
+            url: &checksum.browser_download_url,
+        })?;
+    let expected = parse_checksum(&checksum).context(MissingChecksumSnafu { name })?;
+
+    info!("downloading mint {latest}");
+    let asset = release.asset(name)?;
+    let archive = get(&client, &asset.browser_download_url)
+        .await?
+        .bytes()
+        .await
+        .context(RequestSnafu {
+            url: &asset.browser_download_url,
+        })?;
+    ensure!(
+        hex::encode(Sha256::digest(&archive)) == expected,
+        ChecksumMismatchSnafu { name }
+    );
+    let signature_asset = release.asset(&format!("{name}.sig"))?;
+    let signature = get(&client, &signature_asset.browser_download_url)
+        .await?
+        .bytes()
+        .await
+        .context(RequestSnafu {
+            url: &signature_asset.browser_download_url,
+        })?;
+    let verified = verify_signature(RELEASE_PUBLIC_KEY, &archive, &signature);
+    match &verified {
+        Ok(()) => debug!("signature of {name} verified"),
+        Err(e) => debug!("signature of {name} is invalid: {e}"),
+    }
+    verified.context(SignatureSnafu { name })?;
+    let binary = extract_binary(&archive, binary_name).context(ExtractSnafu {
+        name,
+        binary: binary_name,
+    })?;
+
+    let current_exe = std::env::current_exe().context(ReplaceSnafu)?;
+    if channel == UpdateChannel::Nightly
+        && fs::read(&current_exe).is_ok_and(|exe| Sha256::digest(exe) == Sha256::digest(&binary))
+    {
+        info!("mint {current_version} is the latest nightly build");
+        return Ok(current_version.clone());
+    }
+
+    // written next to the executable so it does not have to be copied across file systems
+    let new_exe = current_exe.with_file_name(format!(".{binary_name}.update"));
+    let result = replace_executable(&new_exe, &binary);
+    fs::remove_file(&new_exe).ok();
+    result?;
+
+    info!("updated mint from {current_version} to {latest}");
+    Ok(latest)
+}
+
+/// Writes the downloaded binary to `new_exe` and installs it. `new_exe` is removed by the caller
+/// whether or not it was installed.
+fn replace_executable(new_exe: &Path, binary: &[u8]) -> Result<(), SelfUpdateError> {
+    fs::write(new_exe, binary).context(ReplaceSnafu)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(new_exe, std::fs::Permissions::from_mode(0o755))
+            .context(ReplaceSnafu)?;
+    }
+    self_replace::self_replace(new_exe).context(ReplaceSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksum() {
+        let hash = "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+        assert_eq!(
+            parse_checksum(&format!("{hash}  mint-linux.zip\n")),
+            Some(hash.to_lowercase())
+        );
+        assert_eq!(parse_checksum(hash), Some(hash.to_lowercase()));
+        assert_eq!(parse_checksum(&hash[1..]), None);
+        assert_eq!(parse_checksum(&hash.replace('F', "g")), None);
+        assert_eq!(parse_checksum(""), None);
+    }
+
+    #[test]
+    fn test_release_public_key_is_valid() {
+        let pem = std::str::from_utf8(RELEASE_PUBLIC_KEY).unwrap();
+        assert!(VerifyingKey::from_public_key_pem(pem).is_ok());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+        use ed25519_dalek::pkcs8::EncodePublicKey;
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let pem = key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        let binary = b"mint binary";
+        let signature = key.sign(binary).to_bytes();
+
+        assert!(verify_signature(pem.as_bytes(), binary, &signature).is_ok());
+        assert!(matches!(
+            verify_signature(pem.as_bytes(), b"tampered binary", &signature),
+            Err(SignatureError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            verify_signature(pem.as_bytes(), binary, &signature[..63]),
+            Err(SignatureError::MalformedSignature { .. })
+        ));
+        assert!(matches!(
+            verify_signature(RELEASE_PUBLIC_KEY, binary, &signature),
+            Err(SignatureError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            verify_signature(b"not a key", binary, &signature),
+            Err(SignatureError::InvalidPublicKey { .. })
+        ));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{HashSet, VecDeque};
+
+use snafu::prelude::*;
+use tracing::warn;
+
+use crate::providers::{ModIdentifier, ModInfo, ModSpecification, ModStore, ProviderError};
+
+#[derive(Debug, Snafu)]
+#[snafu(display(
+    "dependency cycle detected: {}",
+    cycle.iter().map(|m| m.0.as_str()).collect::<Vec<_>>().join(" -> ")
+))]
+pub struct CycleError {
+    pub cycle: Vec<ModIdentifier>,
+}
+
+/// Indices of the mods each mod depends on.
+fn dependency_edges(mods: &[ModInfo]) -> Vec<Vec<usize>> {
+    mods.iter()
+        .enumerate()
+        .map(|(i, m)| {
+            m.suggested_dependencies
+                .iter()
+                .chain(&m.optional_dependencies)
+                .filter_map(|dep| {
+                    mods.iter()
+                        .enumerate()
+                        .position(|(j, other)| i != j && other.spec.satisfies_dependency(dep))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+fn find_cycle(
+    node: usize,
+    edges: &[Vec<usize>],
+    visits: &mut [Visit],
+    stack: &mut Vec<usize>,
+) -> Option<Vec<usize>> {
+    visits[node] = Visit::InProgress;
+    stack.push(node);
+    for &next in &edges[node] {
+        match visits[next] {
+            Visit::InProgress => {
+                let start = stack.iter().position(|&n| n == next).unwrap();
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(next);
+                return Some(cycle);
+            }
+            Visit::Unvisited => {
+                if let Some(cycle) = find_cycle(next, edges, visits, stack) {
+                    return Some(cycle);
+                }
+            }
+            Visit::Done => {}
+        }
+    }
+    stack.pop();
+    visits[node] = Visit::Done;
+    None
+}
+
+/// Checks that the dependencies between `mods` do not form a cycle. The returned error contains
+/// the first cycle found starting and ending with the same mod.
+pub fn check_dependency_cycles(mods: &[ModInfo]) -> Result<(), CycleError> {
+    let edges = dependency_edges(mods);
+    let mut visits = vec![Visit::Unvisited; mods.len()];
+    for node in 0..mods.len() {
+        if visits[node] == Visit::Unvisited
+            && let Some(cycle) = find_cycle(node, &edges, &mut visits, &mut vec![])
+        {
+            return CycleSnafu {
+                cycle: cycle
+                    .into_iter()
+                    .map(|i| mods[i].resolution.url.clone())
+                    .collect::<Vec<_>>(),
+            }
+            .fail();
+        }
+    }
+    Ok(())
+}
+
+fn topological_visit(
+    node: usize,
+    edges: &[Vec<usize>],
+    visited: &mut [bool],
+    order: &mut Vec<usize>,
+) {
+    if visited[node] {
+        return;
+    }
+    visited[node] = true;
+    for &next in &edges[node] {
+        topological_visit(next, edges, visited, order);
+    }
+    order.push(node);
+}
+
+/// Resolves `root` and every mod it depends on, directly or through other dependencies. Each
+/// specification is only resolved once even if several mods depend on it. The mods are returned
+/// with dependencies before their dependents, `root` last. Cycles are not reported, see
+/// `check_dependency_cycles`.
+///
+/// Optional dependencies which cannot be resolved are added to `skipped_optional` instead of
+/// failing, as are all optional dependencies if `skip_optional` is set.
+pub async fn resolve_transitive(
+    root: &ModSpecification,
+    store: &ModStore,
+    skip_optional: bool,
+    skipped_optional: &mut Vec<ModSpecification>,
+) -> Result<Vec<ModInfo>, ProviderError> {
+    let mut mods: Vec<ModInfo> = vec![];
+    let mut visited = HashSet::from([root.clone()]);
+    let mut queue = VecDeque::from([(root.clone(), false)]);
+    while let Some((spec, optional)) = queue.pop_front() {
+        let info = match store.resolve_mod(spec.clone(), false).await {
+            Ok((_, info)) => info,
+            Err(e) if optional => {
+                warn!("optional dependency {} not found, skipping: {e}", spec.url);
+                skipped_optional.push(spec);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let optional_deps = info.optional_dependencies.iter().map(|d| (d, true));
+        for (dep, optional) in info
+            .suggested_dependencies
+            .iter()
+            .map(|d| (d, false))
+            .chain(optional_deps)
+        {
+            let resolved = mods
+                .iter()
+                .chain([&info])
+                .any(|m| m.spec.satisfies_dependency(dep));
+            if resolved || !visited.insert(dep.clone()) {
+                continue;
+            }
+            if optional && skip_optional {
+                skipped_optional.push(dep.clone());
+            } else {
+                queue.push_back((dep.clone(), optional));
+            }
+        }
+        mods.push(info);
+    }
+
+    let edges = dependency_edges(&mods);
+    let mut visited = vec![false; mods.len()];
+    let mut order = vec![];
+    for node in 0..mods.len() {
+        topological_visit(node, &edges, &mut visited, &mut order);
+    }
+    let mut mods = mods.into_iter().map(Some).collect::<Vec<_>>();
+    Ok(order.into_iter().filter_map(|i| mods[i].take()).collect())
+}
+
+/// Node-link graph of the mods and their dependencies as used by D3.js force layouts. Nodes are
+/// identified by the mod URL and `version` is the URL the mod resolved to. Dependencies which are
+/// not part of `mods` are included as nodes without a name or version.
+pub fn deps_to_node_link(mods: &[ModInfo]) -> serde_json::Value {
+    let mut nodes = mods
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "id": m.spec.url,
+                "name": m.name,
+                "version": m.resolution.url.0,
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut links = vec![];
+    let mut missing = HashSet::new();
+    for m in mods {
+        let optional_deps = m.optional_dependencies.iter().map(|d| (d, true));
+        for (dep, optional) in m
+            .suggested_dependencies
+            .iter()
+            .map(|d| (d, false))
+            .chain(optional_deps)
+        {
+            let target = match mods
+                .iter()
+                .find(|other| other.spec.satisfies_dependency(dep))
+            {
+                Some(other) => &other.spec.url,
+                None => {
+                    if missing.insert(&dep.url) {
+                        nodes.push(serde_json::json!({
+                            "id": dep.url,
+                            "name": null,
+                            "version": null,
+                        }));
+                    }
+                    &dep.url
+                }
+            };
+            links.push(serde_json::json!({
+                "source": m.spec.url,
+                "target": target,
+                "optional": optional,
+            }));
+        }
+    }
+    serde_json::json!({ "nodes": nodes, "links": links })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ModResolution, ModSpecification, ModType, DEFAULT_PRIORITY};
+
+    fn mod_info(url: &str, dependencies: &[&str]) -> ModInfo {
+        ModInfo {
+            provider: "test",
+            name: url.to_string(),
+            spec: ModSpecification::new(url.to_string()),
+            versions: vec![],
+            resolution: ModResolution::resolvable(url.into()),
+            suggested_require: false,
+            suggested_dependencies: dependencies
+                .iter()
+                .map(|d| ModSpecification::new(d.to_string()))
+                .collect(),
+            optional_dependencies: vec![],
+            conflicts_with: vec![],
+            mod_type: ModType::Pak,
+            min_game_version: None,
+            priority: DEFAULT_PRIORITY,
+            changelog: None,
+            category: None,
+            tags: vec![],
+            authors: vec![],
+            license: None,
+            homepage: None,
+            description: None,
+            extra_metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_no_cycle() {
+        let mods = [
+            mod_info("a", &["b", "c"]),
+            mod_info("b", &["c"]),
+            mod_info("c", &[]),
+        ];
+        assert!(check_dependency_cycles(&mods).is_ok());
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mods = [
+            mod_info("a", &["b"]),
+            mod_info("b", &["c"]),
+            mod_info("c", &["a"]),
+        ];
+        let err = check_dependency_cycles(&mods).unwrap_err();
+        assert_eq!(
+            err.cycle,
+            vec!["a".into(), "b".into(), "c".into(), "a".into()]
+        );
+    }
+
+    #[test]
+    fn test_deps_to_node_link() {
+        let mut a = mod_info("a", &["b", "x"]);
+        a.optional_dependencies = vec![ModSpecification::new("y".into())];
+        let graph = deps_to_node_link(&[a, mod_info("b", &[])]);
+        assert_eq!(graph["nodes"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            graph["links"],
+            serde_json::json!([
+                { "source": "a", "target": "b", "optional": false },
+                { "source": "a", "target": "x", "optional": false },
+                { "source": "a", "target": "y", "optional": true },
+            ])
+        );
+    }
+}
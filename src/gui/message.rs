@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::DerefMut;
 use std::time::SystemTime;
 use std::{collections::HashMap, sync::Arc};
@@ -17,11 +17,11 @@ use super::{
 };
 use crate::gui::LastAction;
 use crate::integrate::*;
-use crate::mod_lints::{LintId, LintReport};
+use crate::mod_lints::{LintConfig, LintId, LintReport};
 use crate::state::{ModData_v0_1_0 as ModData, ModOrGroup};
 use crate::*;
 use crate::{
-    providers::{FetchProgress, ModInfo, ModStore},
+    providers::{http_client, http_client_builder, FetchProgress, ModInfo, ModStore},
     state::ModConfig,
 };
 use mint_lib::error::GenericError;
@@ -41,7 +41,7 @@ pub enum Message {
     FetchModProgress(FetchModProgress),
     UpdateCache(UpdateCache),
     CheckUpdates(CheckUpdates),
-    LintMods(LintMods),
+    LintMods(Box<LintMods>),
     SelfUpdate(SelfUpdate),
     FetchSelfUpdateProgress(FetchSelfUpdateProgress),
 }
@@ -177,7 +177,8 @@ impl ResolveMods {
 #[derive(Debug)]
 pub struct Integrate {
     rid: RequestID,
-    result: Result<(), IntegrationError>,
+    /// Installed mods
+    result: Result<Vec<ModInfo>, IntegrationError>,
 }
 
 impl Integrate {
@@ -186,6 +187,7 @@ impl Integrate {
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
         fsd_pak: PathBuf,
+        options: IntegrateOptions,
         tx: Sender<Message>,
         ctx: egui::Context,
     ) -> MessageHandle<HashMap<ModSpecification, SpecFetchProgress>> {
@@ -194,7 +196,7 @@ impl Integrate {
             rid,
             handle: tokio::task::spawn(async move {
                 let res =
-                    integrate_async(store, ctx.clone(), mods, fsd_pak, rid, tx.clone())
+                    integrate_async(store, ctx.clone(), mods, fsd_pak, &options, rid, tx.clone())
                         .await;
                 tx.send(Message::Integrate(Integrate { rid, result: res }))
                     .await
@@ -208,8 +210,9 @@ impl Integrate {
     fn receive(self, app: &mut App) {
         if Some(self.rid) == app.integrate_rid.as_ref().map(|r| r.rid) {
             match self.result {
-                Ok(()) => {
+                Ok(installed) => {
                     info!("integration complete");
+                    app.state.record_installed(installed);
                     app.last_action = Some(LastAction::success("integration complete".to_string()));
                 }
                 Err(ref e)
@@ -230,6 +233,58 @@ impl Integrate {
     }
 }
 
+/// Switches the installed mods to a shareable profile. The result is received as an `Integrate`
+/// message of the mods installed by the switch.
+pub struct SwitchProfile;
+
+impl SwitchProfile {
+    pub fn send(
+        rc: &mut RequestCounter,
+        store: Arc<ModStore>,
+        profile: Profile,
+        fsd_pak: PathBuf,
+        options: IntegrateOptions,
+        tx: Sender<Message>,
+        ctx: egui::Context,
+    ) -> MessageHandle<HashMap<ModSpecification, SpecFetchProgress>> {
+        let rid = rc.next();
+        MessageHandle {
+            rid,
+            handle: tokio::task::spawn(async move {
+                let res = switch_profile_async(&store, &profile, &fsd_pak, &options).await;
+                tx.send(Message::Integrate(Integrate { rid, result: res }))
+                    .await
+                    .unwrap();
+                ctx.request_repaint();
+            }),
+            state: Default::default(),
+        }
+    }
+}
+
+async fn switch_profile_async(
+    store: &ModStore,
+    profile: &Profile,
+    fsd_pak: &Path,
+    options: &IntegrateOptions,
+) -> Result<Vec<ModInfo>, IntegrationError> {
+    let generic = |e: anyhow::Error| IntegrationError::GenericError {
+        msg: format!("{e:#}"),
+    };
+    let installation = DBSZInstallation::from_game_path(fsd_pak).map_err(generic)?;
+    match installed_profile(&installation, "installed").map_err(generic)? {
+        Some(from) => {
+            let report =
+                switch_profile(&installation, Some(&from), profile, store, options).await?;
+            Ok(report.added)
+        }
+        None => {
+            apply_profile(&installation, profile, store, options).await?;
+            Ok(vec![])
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FetchModProgress {
     rid: RequestID,
@@ -312,7 +367,7 @@ impl CheckUpdates {
         let handle = tokio::spawn(async move {
             tx.send(Message::CheckUpdates(Self {
                 rid,
-                result: mint_lib::update::get_latest_release().await,
+                result: mint_lib::update::get_latest_release(http_client_builder()).await,
             }))
             .await
             .unwrap();
@@ -354,13 +409,16 @@ async fn integrate_async(
     ctx: egui::Context,
     mod_specs: Vec<ModSpecification>,
     project: PathBuf,
+    options: &IntegrateOptions,
     rid: RequestID,
     message_tx: Sender<Message>,
-) -> Result<(), IntegrationError> {
+) -> Result<Vec<ModInfo>, IntegrationError> {
     let update = false;
 
     let mods = store.resolve_mods(&mod_specs, update).await?;
 
+    crate::dependencies::check_dependency_cycles(&mods.values().cloned().collect::<Vec<_>>())?;
+
     let to_integrate = mod_specs
         .iter()
         .map(|u| mods[u].clone())
@@ -394,15 +452,21 @@ async fn integrate_async(
 
     let paths = store.fetch_mods_ordered(&urls, update, Some(tx)).await?;
 
-    tokio::task::spawn_blocking(|| {
-        crate::integrate::integrate(
-            project,
-            to_integrate.into_iter().zip(paths).collect(),
+    let mods: Vec<_> = to_integrate.iter().cloned().zip(paths).collect();
+    crate::integrate::retry_integration(&options.retry, || {
+        crate::integrate::integrate_with_lint_config(
+            &project,
+            mods.clone(),
+            &options.lints,
+            |_, _| {},
         )
     })
-    .await??;
+    .await?;
+    if let Err(e) = record_installation(&project, &store, &to_integrate, &options.meta) {
+        warn!("failed to record installed versions: {e:#}");
+    }
 
-    Ok(())
+    Ok(to_integrate)
 }
 
 #[derive(Debug)]
@@ -412,12 +476,14 @@ pub struct LintMods {
 }
 
 impl LintMods {
+    #[allow(clippy::too_many_arguments)]
     pub fn send(
         rc: &mut RequestCounter,
         store: Arc<ModStore>,
         mods: Vec<ModSpecification>,
         enabled_lints: BTreeSet<LintId>,
         game_pak_path: Option<PathBuf>,
+        lint_config: LintConfig,
         tx: Sender<Message>,
         ctx: egui::Context,
     ) -> MessageHandle<()> {
@@ -425,7 +491,16 @@ impl LintMods {
 
         let handle = tokio::task::spawn(async move {
             let paths_res =
-                resolve_async_ordered(store, ctx.clone(), mods.clone(), rid, tx.clone()).await;
+                resolve_async_ordered(store.clone(), ctx.clone(), mods.clone(), rid, tx.clone())
+                    .await;
+            let metas = mods
+                .iter()
+                .filter_map(|spec| {
+                    let info = store.get_mod_info(spec)?;
+                    let meta = crate::meta_mod(&info, store.get_version_name(spec));
+                    Some((spec.clone(), meta))
+                })
+                .collect::<BTreeMap<_, _>>();
             let mod_path_pairs_res =
                 paths_res.map(|paths| mods.into_iter().zip(paths).collect::<Vec<_>>());
 
@@ -434,7 +509,9 @@ impl LintMods {
                     crate::mod_lints::run_lints(
                         &enabled_lints,
                         pairs.into_iter().collect(),
+                        &metas,
                         game_pak_path,
+                        &lint_config,
                     )
                 })
                 .await
@@ -443,10 +520,10 @@ impl LintMods {
                 Err(e) => Err(e),
             };
 
-            tx.send(Message::LintMods(LintMods {
+            tx.send(Message::LintMods(Box::new(LintMods {
                 rid,
                 result: report_res,
-            }))
+            })))
             .await
             .unwrap();
             ctx.request_repaint();
@@ -616,7 +693,7 @@ async fn self_update_async(
         }
     });
 
-    let client = reqwest::Client::new();
+    let client = http_client();
 
     let asset_name = if cfg!(target_os = "windows") {
         "mint-x86_64-pc-windows-msvc.zip"
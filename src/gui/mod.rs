@@ -26,13 +26,14 @@ use eframe::{
 use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
 use itertools::Itertools as _;
 use mint_lib::error::ResultExt as _;
+use mint_lib::profile::Profile;
 use mint_lib::update::GitHubRelease;
 use strum::{EnumIter, IntoEnumIterator};
 use tokio::{
     sync::mpsc::{self, Receiver, Sender},
     task::JoinHandle,
 };
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 use crate::gui::find_string::searchable_text;
 use crate::mod_lints::{LintId, LintReport, SplitAssetPair};
@@ -40,10 +41,11 @@ use crate::providers::ProviderError;
 use crate::state::SortingConfig;
 use crate::Dirs;
 use crate::{
-    integrate::uninstall,
+    integrate::{uninstall, IntegrateOptions},
     is_drg_pak,
     providers::{
-        FetchProgress, ModInfo, ModSpecification, ModStore, ProviderFactory,
+        load_credentials, store_credentials, FetchProgress, ModInfo, ModSpecification, ModStore,
+        ProviderFactory,
     },
     state::{ModConfig, ModData_v0_1_0 as ModData, ModOrGroup, ModProfile, State},
     MintError,
@@ -162,6 +164,8 @@ struct LintOptions {
     non_asset_files: bool,
     split_asset_pairs: bool,
     unmodified_game_assets: bool,
+    oversized_files: bool,
+    meta_mod: bool,
 }
 
 struct LastAction {
@@ -486,12 +490,21 @@ impl App {
                             }
                             ui.add(img);
                         }
-                        "http" => {
+                        "http" | "direct" => {
                             ui.label("🌐");
                         }
-                        "file" => {
+                        "file" | "local" => {
                             ui.label("📁");
                         }
+                        "nexusmods" => {
+                            ui.label("🔶");
+                        }
+                        "github" => {
+                            ui.label("🐙");
+                        }
+                        "gamebanana" => {
+                            ui.label("🍌");
+                        }
                         _ => unimplemented!("unimplemented provider kind"),
                     }
 
@@ -771,10 +784,16 @@ impl App {
                 match res {
                     Ok(()) => {
                         let window = self.window_provider_parameters.take().unwrap();
-                        self.state
-                            .config
-                            .provider_parameters
-                            .insert(window.factory.id.to_string(), window.parameters);
+                        let id = window.factory.id.to_string();
+                        if let Err(e) = store_credentials(&id, &window.parameters) {
+                            warn!("storing credentials in the config file instead: {e}");
+                            self.state
+                                .config
+                                .provider_parameters
+                                .insert(id, window.parameters);
+                        } else {
+                            self.state.config.provider_parameters.remove(&id);
+                        }
                         self.state.config.save().unwrap();
                         return;
                     }
@@ -1049,6 +1068,14 @@ impl App {
                                 "This lint requires DRG pak path to be specified",
                             );
                             ui.end_row();
+
+                            ui.label("Mods containing oversized files");
+                            ui.add(toggle_switch(&mut self.lint_options.oversized_files));
+                            ui.end_row();
+
+                            ui.label("Mods with incomplete metadata");
+                            ui.add(toggle_switch(&mut self.lint_options.meta_mod));
+                            ui.end_row();
                         });
                     });
 
@@ -1095,6 +1122,8 @@ impl App {
                                     LintId::UNMODIFIED_GAME_ASSETS,
                                     self.lint_options.unmodified_game_assets,
                                 ),
+                                (LintId::OVERSIZED_FILES, self.lint_options.oversized_files),
+                                (LintId::META_MOD, self.lint_options.meta_mod),
                             ]);
 
                             trace!(?lint_options);
@@ -1118,6 +1147,7 @@ impl App {
                                         .filter_map(|(lint, enabled)| enabled.then_some(lint)),
                                 ),
                                 self.state.config.dbsz_path.clone(),
+                                self.state.config.lint_config(),
                                 self.tx.clone(),
                                 ctx.clone(),
                             ));
@@ -1418,6 +1448,60 @@ impl App {
                                         });
                                     }
                                 }
+
+                                if let Some(oversized_file_mods) = &report.oversized_file_mods {
+                                    if !oversized_file_mods.is_empty() {
+                                        CollapsingHeader::new(
+                                            RichText::new("⚠ Mod(s) with oversized files detected")
+                                                .color(AMBER),
+                                        )
+                                        .default_open(true)
+                                        .show(ui, |ui| {
+                                            oversized_file_mods.iter().for_each(|(r#mod, errors)| {
+                                                CollapsingHeader::new(
+                                                    RichText::new(format!(
+                                                        "⚠ {} includes oversized files",
+                                                        r#mod.url
+                                                    ))
+                                                    .color(AMBER),
+                                                )
+                                                .show(ui, |ui| {
+                                                    errors.iter().for_each(|error| {
+                                                        ui.label(error.to_string());
+                                                    });
+                                                });
+                                            });
+                                        });
+                                    }
+                                }
+
+                                if let Some(meta_mod_errors) = &report.meta_mod_errors {
+                                    if !meta_mod_errors.is_empty() {
+                                        CollapsingHeader::new(
+                                            RichText::new(
+                                                "⚠ Mod(s) with incomplete metadata detected",
+                                            )
+                                            .color(AMBER),
+                                        )
+                                        .default_open(true)
+                                        .show(ui, |ui| {
+                                            meta_mod_errors.iter().for_each(|(r#mod, errors)| {
+                                                CollapsingHeader::new(
+                                                    RichText::new(format!(
+                                                        "⚠ {} has incomplete metadata",
+                                                        r#mod.url
+                                                    ))
+                                                    .color(AMBER),
+                                                )
+                                                .show(ui, |ui| {
+                                                    errors.iter().for_each(|error| {
+                                                        ui.label(error.to_string());
+                                                    });
+                                                });
+                                            });
+                                        });
+                                    }
+                                }
                             });
                     } else {
                         ui.spinner();
@@ -1513,7 +1597,10 @@ impl WindowProviderParameters {
                 .provider_parameters
                 .get(factory.id)
                 .cloned()
-                .unwrap_or_default(),
+                .unwrap_or_default()
+                .into_iter()
+                .chain(load_credentials(factory.id))
+                .collect(),
             factory,
         }
     }
@@ -1649,6 +1736,11 @@ impl eframe::App for App {
                                     self.state.store.clone(),
                                     mods,
                                     self.state.config.dbsz_path.as_ref().unwrap().clone(),
+                                    IntegrateOptions {
+                                        lints: self.state.config.lint_config(),
+                                        meta: (&*self.state.config).into(),
+                                        ..Default::default()
+                                    },
                                     self.tx.clone(),
                                     ctx.clone(),
                                 ));
@@ -1656,6 +1748,53 @@ impl eframe::App for App {
                             }
                         });
 
+                        ui.add_enabled_ui(self.state.config.dbsz_path.is_some(), |ui| {
+                            ui.menu_button("Switch profile", |ui| {
+                                let profiles = std::fs::read_dir(self.state.dirs.profiles_dir())
+                                    .into_iter()
+                                    .flatten()
+                                    .flatten()
+                                    .map(|e| e.path())
+                                    .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+                                    .collect::<Vec<_>>();
+                                if profiles.is_empty() {
+                                    ui.label("No profiles saved");
+                                }
+                                for path in profiles {
+                                    let name = path.file_stem().unwrap().to_string_lossy();
+                                    if !ui.button(name.as_ref()).clicked() {
+                                        continue;
+                                    }
+                                    ui.close_menu();
+                                    match Profile::load(&path) {
+                                        Ok(profile) => {
+                                            self.last_action = None;
+                                            self.integrate_rid =
+                                                Some(message::SwitchProfile::send(
+                                                    &mut self.request_counter,
+                                                    self.state.store.clone(),
+                                                    profile,
+                                                    self.state.config.dbsz_path.clone().unwrap(),
+                                                    IntegrateOptions {
+                                                        lints: self.state.config.lint_config(),
+                                                        meta: (&*self.state.config).into(),
+                                                        ..Default::default()
+                                                    },
+                                                    self.tx.clone(),
+                                                    ctx.clone(),
+                                                ));
+                                        }
+                                        Err(e) => {
+                                            self.last_action =
+                                                Some(LastAction::failure(format!("{e:#}")));
+                                        }
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("Switch to a profile saved with `mint profile save`");
+                        });
+
                         ui.add_enabled_ui(self.state.config.dbsz_path.is_some(), |ui| {
                             let mut button = ui.button("Uninstall mods");
                             if self.state.config.dbsz_path.is_none() {
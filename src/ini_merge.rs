@@ -0,0 +1,94 @@
+//! Merging of config mods into the game's INI files.
+
+use std::io;
+use std::path::Path;
+
+use ini::{EscapePolicy, Ini, ParseOption, WriteOption};
+use mint_lib::manifest::ConfigDefault;
+
+/// UE config files use backslashes in paths and quotes in values which must be kept as is.
+fn load(path: &Path) -> io::Result<Ini> {
+    match Ini::load_from_file_opt(
+        path,
+        ParseOption {
+            enabled_quote: false,
+            enabled_escape: false,
+        },
+    ) {
+        Ok(ini) => Ok(ini),
+        Err(ini::Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(Ini::new()),
+        Err(ini::Error::Io(e)) => Err(e),
+        Err(ini::Error::Parse(e)) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )),
+    }
+}
+
+fn save(ini: &Ini, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    ini.write_to_file_opt(
+        path,
+        WriteOption {
+            escape_policy: EscapePolicy::Nothing,
+            ..Default::default()
+        },
+    )
+}
+
+/// Merges every `[Section] Key=Value` of `overrides` into the INI file `target`, adding missing
+/// keys and overwriting existing ones. Keys the override does not mention are kept. Returns the
+/// values that were replaced.
+pub fn merge_ini(target: &Path, overrides: &Path) -> io::Result<Vec<ConfigDefault>> {
+    let file = target
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let overrides = load(overrides)?;
+    let mut ini = load(target)?;
+
+    let mut defaults = vec![];
+    for (section, properties) in overrides.iter() {
+        for (key, value) in properties.iter() {
+            defaults.push(ConfigDefault {
+                file: file.clone(),
+                section: section.map(|s| s.to_string()),
+                key: key.to_string(),
+                value: ini
+                    .section(section)
+                    .and_then(|p| p.get(key))
+                    .map(|v| v.to_string()),
+            });
+            ini.with_section(section).set(key, value);
+        }
+    }
+    save(&ini, target)?;
+    Ok(defaults)
+}
+
+/// Restores the values replaced by `merge_ini` in the INI files of `config_dir`.
+pub fn restore_config_defaults(config_dir: &Path, defaults: &[ConfigDefault]) -> io::Result<()> {
+    let mut files = defaults.iter().map(|d| &d.file).collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+
+    for file in files {
+        let path = config_dir.join(file);
+        let mut ini = load(&path)?;
+        for default in defaults.iter().filter(|d| &d.file == file) {
+            let section = default.section.as_deref();
+            match &default.value {
+                Some(value) => {
+                    ini.with_section(section).set(&default.key, value);
+                }
+                None => {
+                    ini.delete_from(section, &default.key);
+                }
+            }
+        }
+        save(&ini, &path)?;
+    }
+    Ok(())
+}
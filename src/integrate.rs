@@ -1,24 +1,37 @@
-use std::collections::{HashSet};
-use std::io;
-use std::io::{ErrorKind};
+use std::collections::{BTreeSet, HashSet};
+use std::io::{self, BufReader, ErrorKind};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use chrono::SecondsFormat;
 use fs_err as fs;
-
+use futures::future::BoxFuture;
 use snafu::{prelude::*, Whatever};
-use tracing::info;
-
-use crate::mod_lints::LintError;
-use crate::providers::{ModInfo, ProviderError};
-use mint_lib::mod_info::{ModType};
-use mint_lib::DBSZInstallation;
+use tempfile::TempDir;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
 
-use crate::integrate::IntegrationError::IoError;
+use crate::dependencies::CycleError;
+use crate::ini_merge::{merge_ini, restore_config_defaults};
+use crate::mod_lints::{
+    lint_declared_conflicts, lint_duplicate_files, lint_license, lint_mod_dir, lint_pak_paths,
+    lint_pak_signatures, mod_file_paths, LintConfig, LintError,
+};
+use crate::providers::{ModCategory, ModInfo, ProviderError, SemverVersion};
+use mint_lib::manifest::{ConfigDefault, Manifest, ManifestMod};
+use mint_lib::mod_info::{MetaConfig, ModType};
+use mint_lib::{collect_files, DBSZInstallation, ValidationError};
 
 #[tracing::instrument(level = "debug", skip(path_pak))]
 pub fn uninstall<P: AsRef<Path>>(path_pak: P, modio_mods: HashSet<u32>) -> Result<(), Whatever> {
     let installation = DBSZInstallation::from_game_path(path_pak)
         .whatever_context("failed to get DBSZ installation")?;
+    // the backups of merged mods are kept in ~mods so they are restored before it is removed
+    let manifest =
+        Manifest::load(&installation).whatever_context("failed to read install manifest")?;
+    for entry in manifest.iter().flat_map(|m| &m.mods) {
+        restore_backups(&installation, entry)?;
+    }
     let path_mods = installation.mods_path();
     match fs::remove_dir_all(&path_mods) {
         Ok(()) => Ok(()),
@@ -48,6 +61,124 @@ pub fn uninstall<P: AsRef<Path>>(path_pak: P, modio_mods: HashSet<u32>) -> Resul
     Ok(())
 }
 
+/// Removes the directories containing `path` that are empty, stopping at `root`.
+fn remove_empty_parents(path: &Path, root: &Path) {
+    let mut dir = path.parent();
+    while let Some(d) = dir
+        && d != root
+        && d.starts_with(root)
+        && fs::remove_dir(d).is_ok()
+    {
+        dir = d.parent();
+    }
+}
+
+/// Moves the game files a merged mod overwrote back from its backup directory.
+fn restore_backups(installation: &DBSZInstallation, entry: &ManifestMod) -> Result<(), Whatever> {
+    let backup_dir = Manifest::backup_dir(installation, &entry.name);
+    for file in &entry.backups {
+        let (backup, path) = (backup_dir.join(file), installation.root.join(file));
+        if !backup.exists() {
+            warn!("backup of {} is missing", path.display());
+            continue;
+        }
+        fs::create_dir_all(path.parent().unwrap())
+            .and_then(|()| fs::rename(&backup, &path))
+            .with_whatever_context(|_| format!("failed to restore {}", path.display()))?;
+        info!("restored {}", path.display());
+    }
+    match fs::remove_dir_all(&backup_dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+    .with_whatever_context(|_| format!("failed to remove {}", backup_dir.display()))
+}
+
+/// Removes a single installed mod from `~mods` or `Mods`, leaving every other mod in place.
+pub fn uninstall_mod<P: AsRef<Path>>(path_pak: P, mod_name: &str) -> Result<(), Whatever> {
+    let installation = DBSZInstallation::from_game_path(path_pak)
+        .whatever_context("failed to get DBSZ installation")?;
+    let mut manifest =
+        Manifest::load(&installation).whatever_context("failed to read install manifest")?;
+    let entry = manifest.as_mut().and_then(|m| m.remove(mod_name));
+    let in_manifest = entry.is_some();
+
+    let removed = match entry {
+        Some(entry) if entry.mod_type == ModType::Config => {
+            restore_config_defaults(&installation.config_path(), &entry.config_defaults)
+                .whatever_context("failed to restore config defaults")?;
+            entry
+                .files
+                .into_iter()
+                .map(|f| installation.root.join(f.path))
+                .collect()
+        }
+        // only the files recorded in the manifest are removed, leaving anything else in shared
+        // directories such as Content in place
+        Some(entry) => {
+            let root = match entry.mod_type {
+                ModType::Pak => installation.paks_path().join("~mods"),
+                ModType::ModPlugin => installation.mods_path(),
+                _ => installation.content_path(),
+            };
+            let mut removed = vec![];
+            for file in &entry.files {
+                let path = installation.root.join(&file.path);
+                match fs::remove_file(&path) {
+                    Ok(()) => {
+                        remove_empty_parents(&path, &root);
+                        removed.push(path);
+                    }
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => {
+                        return Err(e).with_whatever_context(|_| {
+                            format!("failed to remove {}", path.display())
+                        })
+                    }
+                }
+            }
+            restore_backups(&installation, &entry)?;
+            removed
+        }
+        None => installation
+            .remove_mod(mod_name)
+            .with_whatever_context(|_| format!("failed to remove mod {mod_name:?}"))?,
+    };
+    if removed.is_empty() {
+        warn!("mod {mod_name:?} is not installed");
+    }
+    for path in removed {
+        info!("removed {}", path.display());
+    }
+
+    if in_manifest && let Some(manifest) = manifest {
+        manifest
+            .save(&installation)
+            .whatever_context("failed to write install manifest")?;
+    }
+    Ok(())
+}
+
+/// Options for integrating mods resolved from specifications.
+#[derive(Debug, Default, Clone)]
+pub struct IntegrateOptions {
+    /// Only validate that the mods could be integrated.
+    pub dry_run: bool,
+    /// Don't report mods that modify the same files.
+    pub ignore_conflicts: bool,
+    /// Severity overrides of the lints run before mods are copied.
+    pub lints: LintConfig,
+    /// Also integrate the dependencies of the mods which are not listed themselves.
+    pub with_deps: bool,
+    /// Don't integrate optional dependencies when `with_deps` is set.
+    pub skip_optional_deps: bool,
+    /// Config written to the `Meta` of the installed mods.
+    pub meta: MetaConfig,
+    /// How integrations that failed on the filesystem are retried.
+    pub retry: RetryConfig,
+}
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
 pub enum IntegrationError {
@@ -62,12 +193,12 @@ pub enum IntegrationError {
     #[snafu(display("mod {:?}: I/O error encountered during its processing", mod_info.name))]
     CtxtIoError {
         source: std::io::Error,
-        mod_info: ModInfo,
+        mod_info: Box<ModInfo>,
     },
     #[snafu(display("mod {:?}: repak error encountered during its processing", mod_info.name))]
     CtxtRepakError {
         source: repak::Error,
-        mod_info: ModInfo,
+        mod_info: Box<ModInfo>,
     },
     #[snafu(display(
         "mod {:?}: modfile {} contains unexpected prefix",
@@ -75,7 +206,7 @@ pub enum IntegrationError {
         modfile_path
     ))]
     ModfileInvalidPrefix {
-        mod_info: ModInfo,
+        mod_info: Box<ModInfo>,
         modfile_path: String,
     },
     #[snafu(display(
@@ -84,7 +215,7 @@ pub enum IntegrationError {
     ))]
     CtxtGenericError {
         source: Box<dyn std::error::Error + Send + Sync>,
-        mod_info: ModInfo,
+        mod_info: Box<ModInfo>,
     },
     #[snafu(transparent)]
     ProviderError { source: ProviderError },
@@ -94,36 +225,1103 @@ pub enum IntegrationError {
     JoinError { source: tokio::task::JoinError },
     #[snafu(transparent)]
     LintError { source: LintError },
+    #[snafu(transparent)]
+    CycleError { source: CycleError },
     #[snafu(display("self update failed: {source:?}"))]
     SelfUpdateFailed {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
+    #[snafu(display("mod {:?}: source {} does not exist", mod_info.name, path.display()))]
+    SourceNotFound {
+        mod_info: Box<ModInfo>,
+        path: PathBuf,
+    },
+    #[snafu(display("installation directory {} is not writable", path.display()))]
+    DestinationNotWritable { path: PathBuf },
+    #[snafu(display(
+        "insufficient disk space: {required} bytes required but only {available} bytes available"
+    ))]
+    InsufficientDiskSpace { required: u64, available: u64 },
+    #[snafu(display(
+        "invalid DBSZ installation at {}: {}",
+        path.display(),
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    ))]
+    InvalidInstallation {
+        path: PathBuf,
+        errors: Vec<ValidationError>,
+    },
+    #[snafu(display(
+        "mod {mod_name:?} requires game version {required} but {found} is installed"
+    ))]
+    IncompatibleGameVersion {
+        mod_name: String,
+        required: SemverVersion,
+        found: SemverVersion,
+    },
+    #[snafu(display("another integration is already running in process {pid}"))]
+    AlreadyRunning { pid: u32 },
+    #[snafu(display("pak signatures are required but no pak_signing_key is configured"))]
+    MissingPakSigningKey,
 }
 
-fn copy_dir_all(src: &PathBuf, dst: &PathBuf) -> io::Result<()> {
-    fs::create_dir_all(&dst)?;
+impl From<mint_lib::update::SelfUpdateError> for IntegrationError {
+    /// Invalid signatures are reported as the `SignatureError` itself so it can be told apart
+    fn from(source: mint_lib::update::SelfUpdateError) -> Self {
+        let source: Box<dyn std::error::Error + Send + Sync> = match source {
+            mint_lib::update::SelfUpdateError::Signature { source, .. } => Box::new(source),
+            source => Box::new(source),
+        };
+        Self::SelfUpdateFailed { source }
+    }
+}
+
+impl IntegrationError {
+    /// Whether the error was caused by the filesystem, e.g. files locked by the running game.
+    pub fn is_io(&self) -> bool {
+        matches!(self, Self::IoError { .. } | Self::CtxtIoError { .. })
+    }
+
+    pub fn is_repak(&self) -> bool {
+        matches!(self, Self::RepakError { .. } | Self::CtxtRepakError { .. })
+    }
+
+    /// Mod that caused the error if it is known.
+    pub fn mod_info(&self) -> Option<&ModInfo> {
+        match self {
+            Self::CtxtIoError { mod_info, .. }
+            | Self::CtxtRepakError { mod_info, .. }
+            | Self::ModfileInvalidPrefix { mod_info, .. }
+            | Self::CtxtGenericError { mod_info, .. }
+            | Self::SourceNotFound { mod_info, .. } => Some(mod_info.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Innermost error of the `source` chain, the error itself if it has no source.
+    pub fn root_cause(&self) -> &(dyn std::error::Error + 'static) {
+        let mut cause: &(dyn std::error::Error + 'static) = self;
+        while let Some(source) = cause.source() {
+            cause = source;
+        }
+        cause
+    }
+}
+
+/// How files are copied into the installation.
+#[derive(Debug, Clone, Copy)]
+pub struct CopyOptions {
+    /// Copy the permissions of source files, e.g. the executable bit on Unix and the read-only
+    /// attribute on Windows
+    pub preserve_permissions: bool,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            preserve_permissions: true,
+        }
+    }
+}
+
+fn copy_dir_all<'a>(
+    src: &'a Path,
+    dst: &'a Path,
+    options: CopyOptions,
+) -> BoxFuture<'a, io::Result<()>> {
+    Box::pin(async move {
+        tokio::fs::create_dir_all(dst).await?;
+        let mut entries = tokio::fs::read_dir(src).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let ty = entry.file_type().await?;
+            if ty.is_dir() {
+                copy_dir_all(&entry.path(), &dst.join(entry.file_name()), options).await?;
+            } else {
+                let (src, dst) = (entry.path(), dst.join(entry.file_name()));
+                tokio::task::spawn_blocking(move || copy_file_atomic(&src, &dst, options))
+                    .await
+                    .map_err(io::Error::other)??;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Unique sibling of `dst` that a file is written to before being renamed into place.
+fn temp_sibling(dst: &Path) -> PathBuf {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let mut name = dst.file_name().unwrap_or_default().to_owned();
+    name.push(format!(
+        ".tmp.{:x}{nanos:x}{:x}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    dst.with_file_name(name)
+}
+
+/// Copies `src` to a temporary sibling of `dst` which is then renamed over `dst`, so an
+/// interrupted copy never leaves a truncated file behind.
+fn copy_file_atomic(src: &Path, dst: &Path, options: CopyOptions) -> io::Result<()> {
+    let temp = temp_sibling(dst);
+    let copied = fs::copy(src, &temp).and_then(|_| {
+        if options.preserve_permissions {
+            fs::set_permissions(&temp, fs::metadata(src)?.permissions())?;
+        }
+        Ok(())
+    });
+    if let Err(e) = copied {
+        fs::remove_file(&temp).ok();
+        return Err(e);
+    }
+    match replace_file(&temp, dst) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            let result = fs::copy(&temp, dst).map(|_| ());
+            fs::remove_file(&temp).ok();
+            result
+        }
+        Err(e) => {
+            fs::remove_file(&temp).ok();
+            Err(e)
+        }
+    }
+}
+
+fn hardlink_dir(src: &Path, dst: &Path, link: bool) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
         let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
+        let dst = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            hardlink_dir(&entry.path(), &dst, link)?;
+            continue;
+        }
+        if link {
+            if dst.exists() {
+                fs::remove_file(&dst)?;
+            }
+            if std::fs::hard_link(entry.path(), &dst).is_ok() {
+                continue;
+            }
+        }
+        copy_file_atomic(&entry.path(), &dst, CopyOptions::default())?;
+    }
+    Ok(())
+}
+
+/// Hard links the files of a mod into its staging directory, copying them if that fails. Linked
+/// files are only ever replaced by renaming over them, so the source stays intact.
+async fn stage_mod_files(src: &Path, dst: &Path) -> io::Result<()> {
+    let linked = {
+        let (src, dst) = (src.to_path_buf(), dst.to_path_buf());
+        tokio::task::spawn_blocking(move || copy_dir_all_hardlink(&src, &dst))
+            .await
+            .map_err(io::Error::other)?
+    };
+    if let Err(e) = linked {
+        debug!("failed to hard link {}, copying it: {e}", src.display());
+        copy_dir_all(src, dst, CopyOptions::default()).await?;
+    }
+    Ok(())
+}
+
+/// Recursively copies `src` to `dst`, hard linking files instead of copying them if both are on
+/// the same filesystem. Linked files share their contents with `src`, so they must not be
+/// modified in place. Files that cannot be linked are copied.
+pub fn copy_dir_all_hardlink(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    let link = same_filesystem(src, dst).unwrap_or(false);
+    hardlink_dir(src, dst, link)
+}
+
+#[cfg(unix)]
+fn same_filesystem(a: &Path, b: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    Ok(fs::metadata(a)?.dev() == fs::metadata(b)?.dev())
+}
+
+#[cfg(windows)]
+fn same_filesystem(a: &Path, b: &Path) -> io::Result<bool> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetVolumePathNameW;
+
+    let volume = |path: &Path| {
+        let path = path
+            .as_os_str()
+            .encode_wide()
+            .chain(Some(0))
+            .collect::<Vec<u16>>();
+        let mut volume = vec![0u16; 1024];
+        // SAFETY: `path` is NUL-terminated and `volume` is as long as the length passed
+        if unsafe { GetVolumePathNameW(path.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) }
+            == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        volume.truncate(volume.iter().position(|&c| c == 0).unwrap_or(volume.len()));
+        Ok(String::from_utf16_lossy(&volume).to_lowercase())
+    };
+    Ok(volume(a)? == volume(b)?)
+}
+
+/// Renames `from` to `to`, atomically replacing `to` if it exists.
+#[cfg(not(windows))]
+fn replace_file(from: &Path, to: &Path) -> io::Result<()> {
+    std::fs::rename(from, to)
+}
+
+#[cfg(windows)]
+fn replace_file(from: &Path, to: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{MoveFileExW, MOVEFILE_REPLACE_EXISTING};
+
+    let wide = |path: &Path| {
+        path.as_os_str()
+            .encode_wide()
+            .chain(Some(0))
+            .collect::<Vec<u16>>()
+    };
+    let (from, to) = (wide(from), wide(to));
+    // SAFETY: both paths are NUL-terminated
+    if unsafe { MoveFileExW(from.as_ptr(), to.as_ptr(), MOVEFILE_REPLACE_EXISTING) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> io::Result<u64> {
+    let metadata = fs::metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += dir_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+#[cfg(unix)]
+fn available_space(path: &Path) -> io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` is only read on success
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let stat = unsafe { stat.assume_init() };
+    // the field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn available_space(path: &Path) -> io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let path = path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<u16>>();
+    let mut available = 0;
+    // SAFETY: `path` is NUL-terminated and the unused out parameters may be null
+    if unsafe {
+        GetDiskFreeSpaceExW(
+            path.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(available)
+}
+
+/// Creates a temporary directory next to `dst` so the staged mod can be renamed into place
+/// without crossing filesystems.
+async fn create_staging_dir(dst: &Path) -> io::Result<TempDir> {
+    let parent = dst
+        .parent()
+        .expect("mod destination has a parent directory");
+    tokio::fs::create_dir_all(parent).await?;
+    tempfile::Builder::new()
+        .prefix(".mint_tmp_")
+        .tempdir_in(parent)
+}
+
+/// INI files in `dir` and its subdirectories
+fn ini_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    collect_files(dir, &mut files)?;
+    files.retain(|f| f.extension().is_some_and(|e| e.eq_ignore_ascii_case("ini")));
+    Ok(files)
+}
+
+/// Mod that has been fully copied into a staging directory. Any previously installed copy of the
+/// mod is kept in the staging directory until the whole integration succeeds so it can be
+/// restored on failure.
+struct StagedMod {
+    mod_info: ModInfo,
+    dst: PathBuf,
+    staging: TempDir,
+    /// Values replaced by a committed config mod
+    config_defaults: Vec<ConfigDefault>,
+    /// Where game files overwritten by a merged mod are kept until it is uninstalled
+    backup_dir: PathBuf,
+    /// Files whose originals were moved into `backup_dir` by this commit
+    backed_up: Vec<PathBuf>,
+}
+
+impl StagedMod {
+    fn new_path(&self) -> PathBuf {
+        self.staging.path().join("new")
+    }
+
+    fn previous_path(&self) -> PathBuf {
+        self.staging.path().join("previous")
+    }
+
+    /// Whether the mod is merged into a directory shared with other files instead of replacing
+    /// its own directory.
+    fn is_merged(&self) -> bool {
+        self.mod_info.mod_type == ModType::Blueprint
+    }
+
+    /// Backup of the installed file `path`, see `Manifest::backup_dir`. Merged mods are installed
+    /// into a directory of the installation root.
+    fn backup_path(&self, path: &Path) -> PathBuf {
+        let root = self.dst.parent().unwrap_or(&self.dst);
+        self.backup_dir
+            .join(path.strip_prefix(root).unwrap_or(path))
+    }
+
+    /// INI files of a staged config mod.
+    fn config_files(&self) -> io::Result<Vec<PathBuf>> {
+        ini_files(&self.new_path())
+    }
+
+    /// Marks that the config files have been backed up and merging started.
+    fn config_marker_path(&self) -> PathBuf {
+        self.previous_path().join(".mint_merged")
+    }
+
+    /// Backs up the config files the mod touches and merges its INI files into them.
+    async fn merge_config(&self) -> io::Result<Vec<ConfigDefault>> {
+        let (new, dst, previous, marker) = (
+            self.new_path(),
+            self.dst.clone(),
+            self.previous_path(),
+            self.config_marker_path(),
+        );
+        tokio::task::spawn_blocking(move || {
+            let files = ini_files(&new)?;
+            fs::create_dir_all(&previous)?;
+            for file in &files {
+                let name = file.file_name().unwrap();
+                let (target, backup) = (dst.join(name), previous.join(name));
+                if target.exists() && !backup.exists() {
+                    fs::copy(&target, &backup)?;
+                }
+            }
+            fs::write(marker, b"")?;
+
+            let mut defaults = vec![];
+            for file in &files {
+                defaults.extend(merge_ini(&dst.join(file.file_name().unwrap()), file)?);
+            }
+            Ok(defaults)
+        })
+        .await?
+    }
+
+    /// Moves the staged mod into its final location.
+    async fn commit(&mut self) -> Result<(), IntegrationError> {
+        if self.mod_info.mod_type == ModType::Config {
+            self.config_defaults = self.merge_config().await.with_context(|_| CtxtIoSnafu {
+                mod_info: self.mod_info.clone(),
+            })?;
+            return Ok(());
+        }
+        async {
+            if self.is_merged() {
+                let (new, backup, previous) = (
+                    self.new_path(),
+                    self.backup_path(&self.dst),
+                    self.previous_path(),
+                );
+                let mut merge = MergeDir {
+                    shared: &self.dst,
+                    backup: &backup,
+                    previous: &previous,
+                    backed_up: &mut self.backed_up,
+                };
+                return merge.merge(&new, &self.dst).await;
+            }
+            if tokio::fs::try_exists(&self.dst).await? {
+                tokio::fs::rename(&self.dst, self.previous_path()).await?;
+            }
+            tokio::fs::rename(self.new_path(), &self.dst).await
+        }
+        .await
+        .with_context(|_| CtxtIoSnafu {
+            mod_info: self.mod_info.clone(),
+        })
+    }
+
+    /// Files the committed mod placed in the installation.
+    fn installed_files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        if self.mod_info.mod_type == ModType::Config {
+            files = self
+                .config_files()?
+                .into_iter()
+                .map(|f| self.dst.join(f.file_name().unwrap()))
+                .collect();
+            files.sort();
+            files.dedup();
+        } else if self.is_merged() {
+            let new = self.new_path();
+            collect_files(&new, &mut files)?;
+            for file in &mut files {
+                *file = self.dst.join(file.strip_prefix(&new).unwrap());
+            }
         } else {
-            fs::copy(entry.path(), dst.join(entry.file_name()))?;
+            collect_files(&self.dst, &mut files)?;
+        }
+        Ok(files)
+    }
+
+    /// Undoes a (possibly partial) `commit`, restoring the previously installed copy if any.
+    async fn rollback(&self) -> io::Result<()> {
+        if self.mod_info.mod_type == ModType::Config {
+            let (new, dst, previous, marker) = (
+                self.new_path(),
+                self.dst.clone(),
+                self.previous_path(),
+                self.config_marker_path(),
+            );
+            return tokio::task::spawn_blocking(move || {
+                // nothing has been merged yet if the backups were not completed
+                if !marker.exists() {
+                    return Ok(());
+                }
+                for file in ini_files(&new)? {
+                    let name = file.file_name().unwrap();
+                    let (target, backup) = (dst.join(name), previous.join(name));
+                    if backup.exists() {
+                        fs::copy(&backup, &target)?;
+                    } else if target.exists() {
+                        fs::remove_file(&target)?;
+                    }
+                }
+                Ok(())
+            })
+            .await?;
+        }
+        if self.is_merged() {
+            unmerge_dir(&self.new_path(), &self.dst, &self.previous_path()).await?;
+            // originals backed up by this commit are restored, older backups belong to a
+            // previously installed version of the mod
+            for path in &self.backed_up {
+                tokio::fs::rename(self.backup_path(path), path).await?;
+            }
+            return Ok(());
+        }
+        // only remove the destination if it is actually the staged copy
+        if !tokio::fs::try_exists(self.new_path()).await?
+            && tokio::fs::try_exists(&self.dst).await?
+        {
+            tokio::fs::remove_dir_all(&self.dst).await?;
+        }
+        if tokio::fs::try_exists(self.previous_path()).await? {
+            tokio::fs::rename(self.previous_path(), &self.dst).await?;
         }
+        Ok(())
     }
+}
+
+/// Merges the files of a staged mod into a shared directory.
+struct MergeDir<'a> {
+    shared: &'a Path,
+    /// Persistent backups of the game files, mirroring `shared`
+    backup: &'a Path,
+    /// Files of a previously installed version of the mod, mirroring `shared`
+    previous: &'a Path,
+    /// Files whose originals were moved into `backup`
+    backed_up: &'a mut Vec<PathBuf>,
+}
+
+impl MergeDir<'_> {
+    /// Copies every file of `src` into `dst`. Overwritten files are moved into the persistent
+    /// backup unless it already holds the original, in which case they were installed by a
+    /// previous version of the mod and are moved into `previous` instead. The staged files are
+    /// kept in `src` so `unmerge_dir` knows which files to restore.
+    fn merge<'b>(&'b mut self, src: &'b Path, dst: &'b Path) -> BoxFuture<'b, io::Result<()>> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(dst).await?;
+            let mut entries = tokio::fs::read_dir(src).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let dst = dst.join(entry.file_name());
+                if entry.file_type().await?.is_dir() {
+                    self.merge(&entry.path(), &dst).await?;
+                    continue;
+                }
+                if tokio::fs::try_exists(&dst).await? {
+                    let relative = dst.strip_prefix(self.shared).unwrap();
+                    let backup = self.backup.join(relative);
+                    let target = if tokio::fs::try_exists(&backup).await? {
+                        self.previous.join(relative)
+                    } else {
+                        self.backed_up.push(dst.clone());
+                        backup
+                    };
+                    tokio::fs::create_dir_all(target.parent().unwrap()).await?;
+                    tokio::fs::rename(&dst, &target).await?;
+                }
+                tokio::fs::copy(entry.path(), &dst).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Reverts `merge_dir`, removing the merged files and restoring any that were overwritten.
+fn unmerge_dir<'a>(
+    src: &'a Path,
+    dst: &'a Path,
+    backup: &'a Path,
+) -> BoxFuture<'a, io::Result<()>> {
+    Box::pin(async move {
+        let mut entries = tokio::fs::read_dir(src).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let (dst, backup) = (dst.join(entry.file_name()), backup.join(entry.file_name()));
+            if entry.file_type().await?.is_dir() {
+                unmerge_dir(&entry.path(), &dst, &backup).await?;
+            } else {
+                if tokio::fs::try_exists(&dst).await? {
+                    tokio::fs::remove_file(&dst).await?;
+                }
+                if tokio::fs::try_exists(&backup).await? {
+                    tokio::fs::rename(&backup, &dst).await?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Directory a mod is installed into. Pak mods are prefixed with their priority as the game
+/// loads them in alphabetical order.
+fn mod_destination(installation: &DBSZInstallation, mod_info: &ModInfo) -> PathBuf {
+    match mod_info.mod_type {
+        ModType::ModPlugin => installation.mods_path().join(&mod_info.name),
+        ModType::Pak => installation
+            .paks_path()
+            .join("~mods")
+            .join(format!("{:04}_{}", mod_info.priority, mod_info.name)),
+        ModType::Blueprint => installation.content_path(),
+        ModType::Config => installation.config_path(),
+    }
+}
+
+/// Installs a mod by linking its installation directory to `src`, so changes to `src` are visible
+/// to the game without integrating again. Only mods installed into a directory of their own can
+/// be linked. A previous link is replaced but an installed copy is left alone.
+pub fn link_mod(src: &Path, installation: &DBSZInstallation, mod_info: &ModInfo) -> io::Result<()> {
+    if matches!(mod_info.mod_type, ModType::Blueprint | ModType::Config) {
+        return Err(io::Error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "mod {:?} is merged into a shared directory and cannot be linked",
+                mod_info.name
+            ),
+        ));
+    }
+    let dst = mod_destination(installation, mod_info);
+    if let Ok(metadata) = std::fs::symlink_metadata(&dst) {
+        if !metadata.file_type().is_symlink() {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} is already installed", dst.display()),
+            ));
+        }
+        #[cfg(unix)]
+        fs::remove_file(&dst)?;
+        #[cfg(windows)]
+        fs::remove_dir(&dst)?;
+    }
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let src = fs::canonicalize(src)?;
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&src, &dst)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_dir(&src, &dst)?;
+    info!("linked {} to {}", dst.display(), src.display());
+    Ok(())
+}
+
+/// Directory whose contents are copied for a mod. Blueprint mods may ship their assets under
+/// `SparkingZERO/Content/` which maps onto the installation's `Content` directory.
+fn mod_source(mod_info: &ModInfo, path: &Path) -> PathBuf {
+    let content = path.join("SparkingZERO").join("Content");
+    if mod_info.mod_type == ModType::Blueprint && content.is_dir() {
+        content
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Records the files of freshly installed mods in the install manifest.
+fn update_manifest(installation: &DBSZInstallation, staged: Vec<StagedMod>) -> anyhow::Result<()> {
+    let mut manifest = Manifest::load(installation)?.unwrap_or_default();
+    for staged_mod in staged {
+        let files = staged_mod.installed_files()?;
+        let mut entry = ManifestMod::from_files(
+            installation,
+            staged_mod.mod_info.name,
+            staged_mod.mod_info.mod_type,
+            &files,
+        )?;
+        entry.config_defaults = staged_mod.config_defaults;
+        entry.backups = staged_mod
+            .backed_up
+            .iter()
+            .map(|path| {
+                path.strip_prefix(&installation.root)
+                    .unwrap_or(path)
+                    .to_path_buf()
+            })
+            .collect();
+        entry.resolution = Some(staged_mod.mod_info.resolution.url.0.clone());
+        entry.source = Some(staged_mod.mod_info.spec.url.clone());
+        entry.installed_at = Some(chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+        // when reinstalling, the values recorded by the previous install are the actual defaults
+        if let Some(previous) = manifest.remove(&entry.name) {
+            if let Some(from) = &previous.resolution
+                && Some(from) != entry.resolution.as_ref()
+                && let Some(changelog) = staged_mod
+                    .mod_info
+                    .changelog
+                    .as_deref()
+                    .filter(|c| !c.trim().is_empty())
+            {
+                info!(
+                    "updated {} from {from} to {}:\n{changelog}",
+                    entry.name, staged_mod.mod_info.resolution.url.0
+                );
+            }
+            // the originals backed up by the previous install are still in the backup
+            for backup in previous.backups {
+                if !entry.backups.contains(&backup) {
+                    entry.backups.push(backup);
+                }
+            }
+            for default in &mut entry.config_defaults {
+                if let Some(original) = previous.config_defaults.iter().find(|d| {
+                    (&d.file, &d.section, &d.key) == (&default.file, &default.section, &default.key)
+                }) {
+                    default.value = original.value.clone();
+                }
+            }
+        }
+        manifest.insert(entry);
+    }
+    manifest.save(installation)
+}
+
+/// Sorts mods into load order, lowest priority first.
+pub fn reorder(mods: &mut [(ModInfo, PathBuf)]) {
+    mods.sort_by_key(|(mod_info, _)| mod_info.priority);
+}
+
+/// Performs every check `integrate` depends on without writing anything.
+fn check_integration(
+    installation: &DBSZInstallation,
+    mods: &[(ModInfo, PathBuf)],
+) -> Result<(), IntegrationError> {
+    let mut required = 0;
+    for (mod_info, path) in mods {
+        ensure!(
+            path.exists(),
+            SourceNotFoundSnafu {
+                mod_info: mod_info.clone(),
+                path: path.clone(),
+            }
+        );
+        required += dir_size(path).with_context(|_| CtxtIoSnafu {
+            mod_info: mod_info.clone(),
+        })?;
+
+        // the destination itself usually doesn't exist yet so check the closest directory that
+        // would have to be written to
+        let dst = mod_destination(installation, mod_info);
+        let existing = dst
+            .ancestors()
+            .find(|p| p.exists())
+            .unwrap_or(&installation.root);
+        ensure!(
+            !fs::metadata(existing)?.permissions().readonly(),
+            DestinationNotWritableSnafu { path: existing }
+        );
+    }
+
+    let available = available_space(&installation.root)?;
+    ensure!(
+        required <= available,
+        InsufficientDiskSpaceSnafu {
+            required,
+            available,
+        }
+    );
+
+    Ok(())
+}
+
+/// Validates that `mods` could be integrated into the installation at `path_project` without
+/// modifying any files. Returns the same errors `integrate` would.
+#[tracing::instrument(skip_all)]
+pub async fn integrate_dry_run<P: AsRef<Path>>(
+    path_project: P,
+    mods: Vec<(ModInfo, PathBuf)>,
+) -> Result<(), IntegrationError> {
+    let Ok(installation) = DBSZInstallation::from_game_path(&path_project) else {
+        return Err(IntegrationError::DrgInstallationNotFound {
+            path: path_project.as_ref().to_path_buf(),
+        });
+    };
+    installation
+        .validate()
+        .map_err(|errors| IntegrationError::InvalidInstallation {
+            path: installation.root.clone(),
+            errors,
+        })?;
+
+    check_game_version(&installation, &mods)?;
+
+    let count = mods.len();
+    tokio::task::spawn_blocking(move || check_integration(&installation, &mods)).await??;
+
+    info!("dry run: {} mods can be installed", count);
+
     Ok(())
 }
 
+/// Fails if a mod requires a newer game version than the installed one. Mods are not checked if
+/// the installed version cannot be determined.
+fn check_game_version(
+    installation: &DBSZInstallation,
+    mods: &[(ModInfo, PathBuf)],
+) -> Result<(), IntegrationError> {
+    if !mods.iter().any(|(m, _)| m.min_game_version.is_some()) {
+        return Ok(());
+    }
+    let game_version = match installation.game_version() {
+        Ok(game_version) => game_version,
+        Err(e) => {
+            warn!("failed to determine installed game version: {e}");
+            return Ok(());
+        }
+    };
+    for (mod_info, _) in mods {
+        if let Some(required) = &mod_info.min_game_version
+            && *required > game_version
+        {
+            return IncompatibleGameVersionSnafu {
+                mod_name: mod_info.name.clone(),
+                required: required.clone(),
+                found: game_version,
+            }
+            .fail();
+        }
+    }
+    Ok(())
+}
+
+/// How likely a conflict is to break the game, derived from the categories of the mods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConflictSeverity {
+    /// Both mods are cosmetic so at worst one of them is not visible
+    Low,
+    Medium,
+    /// Both mods change gameplay and one of them is likely to behave incorrectly
+    High,
+}
+
+impl ConflictSeverity {
+    fn of(a: Option<ModCategory>, b: Option<ModCategory>) -> Self {
+        match (a, b) {
+            (Some(ModCategory::Gameplay), Some(ModCategory::Gameplay)) => Self::High,
+            (Some(ModCategory::Cosmetic), Some(ModCategory::Cosmetic)) => Self::Low,
+            _ => Self::Medium,
+        }
+    }
+}
+
+impl std::fmt::Display for ConflictSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ConflictSeverity::Low => "low",
+            ConflictSeverity::Medium => "medium",
+            ConflictSeverity::High => "high",
+        })
+    }
+}
+
+/// Pair of mods that install files to the same paths.
+#[derive(Debug, Clone)]
+pub struct PakConflict {
+    pub mod_a: ModInfo,
+    pub mod_b: ModInfo,
+    pub conflicting_paths: Vec<String>,
+    pub severity: ConflictSeverity,
+}
+
+/// Finds every pair of mods that would overwrite each other's files.
+pub fn detect_conflicts(mods: &[(ModInfo, PathBuf)]) -> Result<Vec<PakConflict>, IntegrationError> {
+    let files = mods
+        .iter()
+        .map(|(mod_info, path)| {
+            mod_file_paths(mod_info.mod_type, path).map_err(|e| match e {
+                LintError::IoError { source } => IntegrationError::CtxtIoError {
+                    source,
+                    mod_info: Box::new(mod_info.clone()),
+                },
+                LintError::RepakError { source } => IntegrationError::CtxtRepakError {
+                    source,
+                    mod_info: Box::new(mod_info.clone()),
+                },
+                source => IntegrationError::LintError { source },
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut conflicts = vec![];
+    for (a, files_a) in files.iter().enumerate() {
+        for (b, files_b) in files.iter().enumerate().skip(a + 1) {
+            let conflicting_paths = files_a.intersection(files_b).cloned().collect::<Vec<_>>();
+            if !conflicting_paths.is_empty() {
+                conflicts.push(PakConflict {
+                    mod_a: mods[a].0.clone(),
+                    mod_b: mods[b].0.clone(),
+                    conflicting_paths,
+                    severity: ConflictSeverity::of(mods[a].0.category, mods[b].0.category),
+                });
+            }
+        }
+    }
+    Ok(conflicts)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn integrate<P: AsRef<Path>>(
+    path_project: P,
+    mods: Vec<(ModInfo, PathBuf)>,
+) -> Result<(), IntegrationError> {
+    integrate_with_progress(path_project, mods, |_, _| {}).await
+}
+
+/// Written to the mods directory while an integration is running
+const LOCK_FILE_NAME: &str = "mint.lock";
+
+/// Locks older than this are left over from a crashed integration and ignored
+const LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn process_alive(pid: u32) -> bool {
+    sysinfo::System::new().refresh_process(sysinfo::Pid::from_u32(pid))
+}
+
+/// PID of the process holding the lock at `path` if the lock is recent and the process is still
+/// running.
+fn lock_holder(path: &Path) -> Option<u32> {
+    let age = fs::metadata(path).ok()?.modified().ok()?.elapsed().ok()?;
+    if age >= LOCK_TIMEOUT {
+        return None;
+    }
+    let pid = fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .next()?
+        .trim()
+        .parse::<u32>()
+        .ok()?;
+    process_alive(pid).then_some(pid)
+}
+
+/// Creates the lock file of the installation containing the PID and start time of this process,
+/// failing if a recent lock of another running process exists.
+fn acquire_install_lock(installation: &DBSZInstallation) -> Result<PathBuf, IntegrationError> {
+    let path = installation.mods_path().join(LOCK_FILE_NAME);
+    if let Some(pid) = lock_holder(&path)
+        && pid != std::process::id()
+    {
+        return AlreadyRunningSnafu { pid }.fail();
+    }
+
+    fs::create_dir_all(installation.mods_path())?;
+    fs::write(
+        &path,
+        format!(
+            "{}\n{}\n",
+            std::process::id(),
+            chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+        ),
+    )?;
+    Ok(path)
+}
+
+/// Lock file left behind by an integration which is no longer running.
+pub fn stale_install_lock(installation: &DBSZInstallation) -> Option<PathBuf> {
+    let path = installation.mods_path().join(LOCK_FILE_NAME);
+    (path.exists() && lock_holder(&path).is_none()).then_some(path)
+}
+
+/// Temporary files and staging directories left in `~mods` and `Mods` by interrupted
+/// integrations. Only meaningful while no integration is running.
+pub fn orphaned_temp_files(installation: &DBSZInstallation) -> Vec<PathBuf> {
+    fn collect(dir: &Path, found: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(".mint_tmp_") || name.contains(".tmp.") {
+                found.push(entry.path());
+            } else if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                // linked mods are symlinks and not descended into
+                collect(&entry.path(), found);
+            }
+        }
+    }
+
+    let mut found = vec![];
+    collect(&installation.paks_path().join("~mods"), &mut found);
+    collect(&installation.mods_path(), &mut found);
+    found
+}
+
+/// How `integrate_with_retry` retries integrations that failed on the filesystem.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Attempts including the first one
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Random delay between half and all of `delay` so concurrent retries don't line up.
+fn with_jitter(delay: Duration) -> Duration {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let random = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default() as f64
+        / 1e9;
+    delay.mul_f64(0.5 + random / 2.0)
+}
+
+/// Same as `integrate`, but retries as configured by `config` with exponential backoff if the
+/// integration failed on the filesystem, which usually means files were temporarily locked by the
+/// game or another process.
+pub async fn integrate_with_retry<P: AsRef<Path>>(
+    path_project: P,
+    mods: Vec<(ModInfo, PathBuf)>,
+    config: RetryConfig,
+) -> Result<(), IntegrationError> {
+    retry_integration(&config, || integrate(path_project.as_ref(), mods.clone())).await
+}
+
+/// Runs the integration returned by `run` until it succeeds, fails with an error that isn't an I/O
+/// error or `config.max_attempts` are used up. Lets callers retry other variants than
+/// `integrate`, e.g. `integrate_with_lint_config`.
+pub async fn retry_integration<F, Fut>(
+    config: &RetryConfig,
+    mut run: F,
+) -> Result<(), IntegrationError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), IntegrationError>>,
+{
+    let mut delay = config.initial_delay;
+    let mut attempt = 1;
+    loop {
+        match run().await {
+            Err(e) if e.is_io() && attempt < config.max_attempts => {
+                warn!(
+                    "integration failed, retrying ({} attempts left): {e}",
+                    config.max_attempts - attempt
+                );
+                tokio::time::sleep(with_jitter(delay)).await;
+                delay = (delay * 2).min(config.max_delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Same as `integrate` but calls `progress` with `(mods_completed, mods_total)` once before any
+/// mod is copied and then after each mod has been copied.
+#[tracing::instrument(skip_all)]
+pub async fn integrate_with_progress<P: AsRef<Path>>(
+    path_project: P,
+    mods: Vec<(ModInfo, PathBuf)>,
+    progress: impl Fn(usize, usize) + Send,
+) -> Result<(), IntegrationError> {
+    integrate_with_lint_config(path_project, mods, &LintConfig::default(), progress).await
+}
+
+/// Same as `integrate_with_progress` but reports the lints run before copying according to
+/// `lint_config`. Integration is aborted if any lint is an error.
 #[tracing::instrument(skip_all)]
-pub fn integrate<P: AsRef<Path>>(
+pub async fn integrate_with_lint_config<P: AsRef<Path>>(
     path_project: P,
     mods: Vec<(ModInfo, PathBuf)>,
+    lint_config: &LintConfig,
+    progress: impl Fn(usize, usize) + Send,
 ) -> Result<(), IntegrationError> {
     let Ok(installation) = DBSZInstallation::from_game_path(&path_project) else {
         return Err(IntegrationError::DrgInstallationNotFound {
             path: path_project.as_ref().to_path_buf(),
         });
     };
+    installation
+        .validate()
+        .map_err(|errors| IntegrationError::InvalidInstallation {
+            path: installation.root.clone(),
+            errors,
+        })?;
+    let lock = acquire_install_lock(&installation)?;
+    let _lock = scopeguard::guard(lock, |lock| {
+        fs::remove_file(lock).ok();
+    });
 
     /*
     #[cfg(feature = "hook")]
@@ -141,30 +1339,225 @@ pub fn integrate<P: AsRef<Path>>(
         }
     }*/
 
+    let mut mods = mods
+        .into_iter()
+        .filter(|(mod_info, _)| {
+            let disabled = installation.is_mod_disabled(&mod_info.name);
+            if disabled {
+                info!("skipping disabled mod {:?}", mod_info.name);
+            }
+            !disabled
+        })
+        .collect::<Vec<_>>();
+    reorder(&mut mods);
+
+    lint_config.report(
+        "",
+        lint_declared_conflicts(&mods.iter().map(|(m, _)| m.clone()).collect::<Vec<_>>()),
+    )?;
+
+    check_game_version(&installation, &mods)?;
+
+    // without a key signatures cannot be verified, so paks are not checked at all
+    let pak_signing_key = match &lint_config.pak_signing_key {
+        Some(path) => Some(tokio::fs::read(path).await?),
+        None if lint_config.require_pak_signatures => return MissingPakSigningKeySnafu.fail(),
+        None => None,
+    };
     for (mod_info, path) in &mods {
-        match mod_info.mod_type
-        {
-            ModType::ModPlugin => {
-                let result = copy_dir_all(path, &installation.mods_path().join(&mod_info.name));
-                match result {
-                    Err(e) => return Err(IoError {source: e }),
-                    _ => {}
-                }
+        let mut lints = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || lint_pak_paths(&path)).await??
+        };
+        lints.extend({
+            let (mod_info, path) = (mod_info.clone(), path.clone());
+            tokio::task::spawn_blocking(move || lint_mod_dir(&mod_info, &path))
+                .await??
+                .into_iter()
+                .map(|(lint, _fix)| lint)
+        });
+        lints.extend(lint_license(mod_info));
+        if let Some(key) = &pak_signing_key {
+            let (path, key) = (path.clone(), key.clone());
+            lints.extend(
+                tokio::task::spawn_blocking(move || lint_pak_signatures(&path, &key)).await??,
+            );
+        }
+        lint_config
+            .report(&format!("mod {:?}: ", mod_info.name), lints)
+            .map_err(|error| IntegrationError::CtxtGenericError {
+                source: Box::new(error),
+                mod_info: mod_info.clone(),
+            })?;
+    }
+
+    let duplicates = {
+        let mods = mods.clone();
+        tokio::task::spawn_blocking(move || lint_duplicate_files(&mods)).await??
+    };
+    lint_config.report("", duplicates)?;
+
+    let count = mods.len();
+    progress(0, count);
+
+    // mods are installed into separate directories so they can be copied concurrently into
+    // their staging directories without touching the installation
+    let mut tasks = JoinSet::new();
+    for (mod_info, path) in mods {
+        let path = mod_source(&mod_info, &path);
+        let dst = mod_destination(&installation, &mod_info);
+        let backup_dir = Manifest::backup_dir(&installation, &mod_info.name);
+        let staging = create_staging_dir(&dst)
+            .await
+            .with_context(|_| CtxtIoSnafu {
+                mod_info: mod_info.clone(),
+            })?;
+        tasks.spawn(async move {
+            match stage_mod_files(&path, &staging.path().join("new")).await {
+                Ok(()) => Ok(StagedMod {
+                    mod_info,
+                    dst,
+                    staging,
+                    config_defaults: vec![],
+                    backup_dir,
+                    backed_up: vec![],
+                }),
+                Err(source) => Err(IntegrationError::CtxtIoError {
+                    source,
+                    mod_info: Box::new(mod_info),
+                }),
             }
-            ModType::Pak => {
-                let result = copy_dir_all(path, &installation.paks_path().join("~mods").join(&mod_info.name));
-                match result {
-                    Err(e) => return Err(IoError {source: e }),
-                    _ => {}
+        });
+    }
+    let mut staged = Vec::with_capacity(count);
+    while let Some(result) = tasks.join_next().await {
+        staged.push(result??);
+        progress(staged.len(), count);
+    }
+
+    for i in 0..staged.len() {
+        if let Err(e) = staged[i].commit().await {
+            for committed in staged[..=i].iter().rev() {
+                if let Err(rollback_err) = committed.rollback().await {
+                    warn!(
+                        "failed to roll back mod {:?}: {rollback_err}",
+                        committed.mod_info.name
+                    );
                 }
             }
+            return Err(e);
         }
     }
 
-    info!(
-        "{} mods installed",
-        mods.len(),
-    );
+    if let Err(e) =
+        tokio::task::spawn_blocking(move || update_manifest(&installation, staged)).await?
+    {
+        warn!("failed to update install manifest: {e:#}");
+    }
+
+    info!("{} mods installed", count);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ModIdentifier, ModResolution, ModSpecification};
+
+    fn blueprint_mod(name: &str) -> ModInfo {
+        ModInfo::builder()
+            .provider("file")
+            .name(name)
+            .spec(ModSpecification::new(name.to_string()))
+            .resolution(ModResolution::resolvable(ModIdentifier::new(
+                name.to_string(),
+            )))
+            .mod_type(ModType::Blueprint)
+            .license("MIT".to_string())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_uninstall_restores_overwritten_blueprint_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("game");
+        let installation = DBSZInstallation::from_game_path(root.join("SparkingZERO")).unwrap();
+        fs::create_dir_all(installation.binaries_directory()).unwrap();
+        fs::create_dir_all(installation.paks_path()).unwrap();
+        let original = installation.content_path().join("Movies").join("Intro.mp4");
+        fs::create_dir_all(original.parent().unwrap()).unwrap();
+        fs::write(&original, "original").unwrap();
+
+        let mod_dir = dir.path().join("mod");
+        fs::create_dir_all(mod_dir.join("Movies")).unwrap();
+        fs::write(mod_dir.join("Movies").join("Intro.mp4"), "modded").unwrap();
+        fs::write(mod_dir.join("Movies").join("Outro.mp4"), "added").unwrap();
+
+        let game_path = root.join("SparkingZERO");
+        integrate(&game_path, vec![(blueprint_mod("intro"), mod_dir)])
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(&original).unwrap(), "modded");
+        let entry = Manifest::load(&installation)
+            .unwrap()
+            .unwrap()
+            .mods
+            .remove(0);
+        assert_eq!(
+            entry.backups,
+            [PathBuf::from("Content").join("Movies").join("Intro.mp4")]
+        );
+
+        uninstall_mod(&game_path, "intro").unwrap();
+        assert_eq!(fs::read_to_string(&original).unwrap(), "original");
+        assert!(!original.with_file_name("Outro.mp4").exists());
+        assert!(!Manifest::backup_dir(&installation, "intro").exists());
+    }
+
+    #[tokio::test]
+    async fn test_retry_integration_only_retries_io_errors() {
+        let config = RetryConfig {
+            max_attempts: 3,
+            initial_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        };
+
+        let attempts = std::cell::Cell::new(0);
+        let result = retry_integration(&config, || {
+            attempts.set(attempts.get() + 1);
+            async { Err(std::io::Error::from(std::io::ErrorKind::WouldBlock).into()) }
+        })
+        .await;
+        assert!(result.unwrap_err().is_io());
+        assert_eq!(attempts.get(), 3);
+
+        attempts.set(0);
+        let result = retry_integration(&config, || {
+            attempts.set(attempts.get() + 1);
+            let result = if attempts.get() < 2 {
+                Err(std::io::Error::from(std::io::ErrorKind::WouldBlock).into())
+            } else {
+                Ok(())
+            };
+            async { result }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 2);
+
+        attempts.set(0);
+        let result = retry_integration(&config, || {
+            attempts.set(attempts.get() + 1);
+            async {
+                Err(IntegrationError::DrgInstallationNotFound {
+                    path: PathBuf::new(),
+                })
+            }
+        })
+        .await;
+        assert!(!result.unwrap_err().is_io());
+        assert_eq!(attempts.get(), 1);
+    }
+}
@@ -1,9 +1,12 @@
 #![feature(let_chains)]
 #![feature(if_let_guard)]
 
+pub mod dependencies;
 pub mod gui;
+pub mod ini_merge;
 pub mod integrate;
 pub mod mod_lints;
+pub mod pak;
 pub mod providers;
 pub mod state;
 
@@ -14,8 +17,14 @@ use std::{
 
 use directories::ProjectDirs;
 use fs_err as fs;
-use integrate::IntegrationError;
-use providers::{ModResolution, ModSpecification, ProviderError, ProviderFactory};
+use integrate::{IntegrateOptions, IntegrationError};
+use mint_lib::manifest::Manifest;
+use mint_lib::mod_info::{Meta, MetaConfig, MetaMod, SemverVersion, META_SCHEMA_VERSION};
+use mint_lib::profile::Profile;
+use mint_lib::DBSZInstallation;
+use providers::{
+    ModInfo, ModResolution, ModSpecification, ModStore, ProviderError, ProviderFactory,
+};
 use snafu::prelude::*;
 use state::{State, StateError};
 use tracing::*;
@@ -91,6 +100,16 @@ impl Dirs {
             data_dir: data_dir.as_ref().to_path_buf(),
         })
     }
+
+    /// Directory shareable profiles are saved to by default.
+    pub fn profiles_dir(&self) -> PathBuf {
+        self.config_dir.join("profiles")
+    }
+
+    /// File the shareable profile `name` is saved to
+    pub fn profile_path(&self, name: &str) -> PathBuf {
+        self.profiles_dir().join(format!("{name}.toml"))
+    }
 }
 
 pub fn is_drg_pak<P: AsRef<Path>>(path: P) -> Result<(), MintError> {
@@ -105,8 +124,68 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
     state: &State,
     mod_specs: &[ModSpecification],
     update: bool,
+    options: &IntegrateOptions,
 ) -> Result<(), IntegrationError> {
-    let mods = state.store.resolve_mods(mod_specs, update).await?;
+    let installed = resolve_unordered_and_integrate_with_store(
+        game_path,
+        &state.store,
+        mod_specs,
+        update,
+        options,
+    )
+    .await?;
+    state.record_installed(installed);
+    Ok(())
+}
+
+/// Returns the installed mods, which is empty for dry runs.
+pub async fn resolve_unordered_and_integrate_with_store<P: AsRef<Path>>(
+    game_path: P,
+    store: &ModStore,
+    mod_specs: &[ModSpecification],
+    update: bool,
+    options: &IntegrateOptions,
+) -> Result<Vec<ModInfo>, IntegrationError> {
+    let mods = resolve_and_fetch_with_store(store, mod_specs, update, options).await?;
+    integrate_fetched(game_path, store, mods, options).await
+}
+
+/// Resolves the mods, with their dependencies if `options.with_deps` is set, and downloads them
+/// without touching the installation.
+async fn resolve_and_fetch_with_store(
+    store: &ModStore,
+    mod_specs: &[ModSpecification],
+    update: bool,
+    options: &IntegrateOptions,
+) -> Result<Vec<(ModInfo, PathBuf)>, IntegrationError> {
+    let mut with_deps = vec![];
+    let mut skipped_optional = vec![];
+    let mod_specs: &[ModSpecification] = if options.with_deps {
+        for spec in mod_specs {
+            for m in dependencies::resolve_transitive(
+                spec,
+                store,
+                options.skip_optional_deps,
+                &mut skipped_optional,
+            )
+            .await?
+            {
+                let spec = if m.spec == spec.without_version_constraint() {
+                    spec.clone()
+                } else {
+                    m.spec
+                };
+                if !with_deps.contains(&spec) {
+                    with_deps.push(spec);
+                }
+            }
+        }
+        &with_deps
+    } else {
+        mod_specs
+    };
+
+    let mods = store.resolve_mods(mod_specs, update).await?;
 
     let mods_set = mod_specs
         .iter()
@@ -130,6 +209,8 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
         }
     }
 
+    dependencies::check_dependency_cycles(&mods.values().cloned().collect::<Vec<_>>())?;
+
     let to_integrate = mod_specs
         .iter()
         .map(|u| mods[u].clone())
@@ -140,12 +221,273 @@ pub async fn resolve_unordered_and_integrate<P: AsRef<Path>>(
         .collect::<Vec<_>>();
 
     info!("fetching mods...");
-    let paths = state.store.fetch_mods(&urls, update, None).await?;
+    let paths = store.fetch_mods(&urls, update, None).await?;
 
-    integrate::integrate(
-        game_path,
-        to_integrate.into_iter().zip(paths).collect(),
-    )
+    skipped_optional.sort();
+    skipped_optional.dedup();
+    if !skipped_optional.is_empty() {
+        info!("the following optional dependencies were not installed:");
+        for d in skipped_optional {
+            info!("  {}", d.url);
+        }
+    }
+    Ok(to_integrate.into_iter().zip(paths).collect())
+}
+
+/// Integrates mods returned by `resolve_and_fetch_with_store`, returning the installed mods, which
+/// is empty for dry runs.
+async fn integrate_fetched<P: AsRef<Path>>(
+    game_path: P,
+    store: &ModStore,
+    mods: Vec<(ModInfo, PathBuf)>,
+    options: &IntegrateOptions,
+) -> Result<Vec<ModInfo>, IntegrationError> {
+    let mut installed = mods.iter().map(|(m, _)| m.clone()).collect::<Vec<_>>();
+
+    if !options.ignore_conflicts {
+        let conflicts = {
+            let mods = mods.clone();
+            tokio::task::spawn_blocking(move || integrate::detect_conflicts(&mods)).await??
+        };
+        for conflict in conflicts {
+            warn!(
+                "mods {:?} and {:?} both modify the following files ({} severity):",
+                conflict.mod_a.name, conflict.mod_b.name, conflict.severity
+            );
+            for path in conflict.conflicting_paths {
+                warn!("  {path}");
+            }
+        }
+    }
+
+    if options.dry_run {
+        integrate::integrate_dry_run(game_path, mods).await?;
+        installed.clear();
+    } else {
+        integrate::retry_integration(&options.retry, || {
+            integrate::integrate_with_lint_config(
+                &game_path,
+                mods.clone(),
+                &options.lints,
+                |_, _| {},
+            )
+        })
+        .await?;
+        if let Err(e) = record_installation(game_path.as_ref(), store, &installed, &options.meta) {
+            warn!("failed to record installed versions: {e:#}");
+        }
+    }
+    Ok(installed)
+}
+
+/// Stores the version names of freshly installed mods in the install manifest, which `integrate`
+/// writes without access to the providers, and writes the `Meta` of every installed mod.
+pub(crate) fn record_installation(
+    game_path: &Path,
+    store: &ModStore,
+    installed: &[ModInfo],
+    meta_config: &MetaConfig,
+) -> anyhow::Result<()> {
+    let installation = DBSZInstallation::from_game_path(game_path)?;
+    let Some(mut manifest) = Manifest::load(&installation)? else {
+        return Ok(());
+    };
+    for info in installed {
+        if let Some(entry) = manifest.mods.iter_mut().find(|m| m.name == info.name) {
+            entry.version = store.get_version_name(&info.spec);
+        }
+    }
+    manifest.save(&installation)?;
+
+    let meta_path = Manifest::meta_path(&installation);
+    if meta_path.exists() {
+        match Meta::from_disk(&meta_path) {
+            Ok(previous) if previous.migrated_from < META_SCHEMA_VERSION => info!(
+                "upgrading meta of the installed mods from schema version {}",
+                previous.migrated_from
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("replacing unreadable meta of the installed mods: {e}"),
+        }
+    }
+    let meta = installation_meta(&manifest, store, meta_config);
+    if let Err(errors) = meta.validate() {
+        for error in errors {
+            warn!("meta of the installed mods: {error}");
+        }
+    }
+    fs::write(meta_path, serde_json::to_vec_pretty(&meta)?)?;
+    Ok(())
+}
+
+/// `MetaMod` entry of a resolved mod, `version` being the version name it was installed as
+pub fn meta_mod(info: &ModInfo, version: Option<String>) -> MetaMod {
+    MetaMod {
+        name: info.name.clone(),
+        version: version.unwrap_or_default(),
+        url: info
+            .homepage
+            .clone()
+            .unwrap_or_else(|| info.resolution.url.0.clone()),
+        authors: info.authors.clone(),
+        license: info.license.clone(),
+        changelog: info.changelog.clone(),
+        required: info.suggested_require,
+    }
+}
+
+/// Meta of every mod in the install manifest, filled in from the cached mod info
+fn installation_meta(manifest: &Manifest, store: &ModStore, config: &MetaConfig) -> Meta {
+    let mods = manifest
+        .mods
+        .iter()
+        .map(|entry| {
+            let info = entry
+                .source
+                .as_ref()
+                .and_then(|url| store.get_mod_info(&ModSpecification::new(url.clone())));
+            match info {
+                Some(info) => MetaMod {
+                    name: entry.name.clone(),
+                    ..meta_mod(&info, entry.version.clone())
+                },
+                None => MetaMod {
+                    name: entry.name.clone(),
+                    version: entry.version.clone().unwrap_or_default(),
+                    url: entry.resolution.clone().unwrap_or_default(),
+                    authors: vec![],
+                    license: None,
+                    changelog: None,
+                    required: false,
+                },
+            }
+        })
+        .collect();
+    Meta {
+        schema_version: META_SCHEMA_VERSION,
+        version: SemverVersion::parse(env!("CARGO_PKG_VERSION")).unwrap_or_default(),
+        mods,
+        config: config.clone(),
+    }
+}
+
+/// Profile of the mods installed in `installation` as they were specified when installing them,
+/// `None` if mint has not installed any mods.
+pub fn installed_profile(
+    installation: &DBSZInstallation,
+    name: &str,
+) -> anyhow::Result<Option<Profile>> {
+    let Some(manifest) = Manifest::load(installation)? else {
+        return Ok(None);
+    };
+    Ok(Some(Profile {
+        name: name.to_string(),
+        description: None,
+        mods: manifest
+            .mods
+            .into_iter()
+            .filter_map(|m| m.source.map(ModSpecification::new))
+            .collect(),
+        pak_hashes: Default::default(),
+        generated_by: format!("mint {}", env!("CARGO_PKG_VERSION")),
+    }))
+}
+
+/// Replaces the mods installed by mint with the mods of `profile`, leaving mods installed by other
+/// means in place. Nothing is removed before every mod of the profile has been fetched, so the
+/// installed mods are kept if resolving or downloading fails.
+pub async fn apply_profile(
+    installation: &DBSZInstallation,
+    profile: &Profile,
+    store: &ModStore,
+    options: &IntegrateOptions,
+) -> Result<(), IntegrationError> {
+    info!("applying profile {:?}", profile.name);
+    let game_path = installation.mods_path();
+    let mods = resolve_and_fetch_with_store(store, &profile.mods, false, options).await?;
+    if !options.dry_run {
+        let manifest =
+            Manifest::load(installation).map_err(|e| IntegrationError::GenericError {
+                msg: format!("{e:#}"),
+            })?;
+        // mods of the profile are reinstalled over their previous install
+        for entry in manifest
+            .iter()
+            .flat_map(|m| &m.mods)
+            .filter(|entry| !mods.iter().any(|(m, _)| m.name == entry.name))
+        {
+            info!("removing mod {:?}", entry.name);
+            integrate::uninstall_mod(&game_path, &entry.name)
+                .map_err(|e| IntegrationError::GenericError { msg: e.to_string() })?;
+        }
+    }
+    integrate_fetched(game_path, store, mods, options).await?;
+    Ok(())
+}
+
+/// Mods affected by `switch_profile`.
+#[derive(Debug, Default)]
+pub struct SwitchReport {
+    pub removed: Vec<ModInfo>,
+    pub added: Vec<ModInfo>,
+    /// Mods in both profiles which were left installed.
+    pub reused: Vec<ModInfo>,
+}
+
+/// Switches the installed mods from the `from` profile to the `to` profile. Only mods which are
+/// not part of both profiles are removed or installed.
+pub async fn switch_profile(
+    installation: &DBSZInstallation,
+    from: Option<&Profile>,
+    to: &Profile,
+    store: &ModStore,
+    options: &IntegrateOptions,
+) -> Result<SwitchReport, IntegrationError> {
+    let from_specs = from.map(|p| p.mods.as_slice()).unwrap_or_default();
+    let all_specs = from_specs
+        .iter()
+        .chain(&to.mods)
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let mods = store.resolve_mods(&all_specs, false).await?;
+
+    let mut report = SwitchReport::default();
+    for spec in from_specs.iter().filter(|s| !to.mods.contains(s)) {
+        let info = &mods[spec];
+        info!("removing mod {:?}", info.name);
+        installation
+            .remove_mod(&info.name)
+            .map_err(|e| IntegrationError::GenericError {
+                msg: format!("{e:#}"),
+            })?;
+        report.removed.push(info.clone());
+    }
+    let mut added_specs = vec![];
+    for spec in &to.mods {
+        let info = &mods[spec];
+        if from_specs.contains(spec) {
+            info!("keeping mod {:?}", info.name);
+            report.reused.push(info.clone());
+        } else {
+            info!("adding mod {:?}", info.name);
+            report.added.push(info.clone());
+            added_specs.push(spec.clone());
+        }
+    }
+
+    if !added_specs.is_empty() {
+        resolve_unordered_and_integrate_with_store(
+            installation.mods_path(),
+            store,
+            &added_specs,
+            false,
+            options,
+        )
+        .await?;
+    }
+    Ok(report)
 }
 
 async fn resolve_into_urls<'b>(
@@ -201,6 +543,7 @@ pub async fn resolve_unordered_and_integrate_with_provider_init<P, F>(
     state: &mut State,
     mod_specs: &[ModSpecification],
     update: bool,
+    options: IntegrateOptions,
     init: F,
 ) -> Result<(), MintError>
 where
@@ -208,7 +551,8 @@ where
     F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
 {
     loop {
-        match resolve_unordered_and_integrate(&game_path, state, mod_specs, update).await {
+        match resolve_unordered_and_integrate(&game_path, state, mod_specs, update, &options).await
+        {
             Ok(()) => return Ok(()),
             Err(ref e)
                 if let IntegrationError::ProviderError { ref source } = e
@@ -221,6 +565,24 @@ where
     }
 }
 
+/// Resolves the mods and all of their dependencies, initializing providers as needed.
+pub async fn resolve_with_provider_init<F>(
+    state: &mut State,
+    mod_specs: &[ModSpecification],
+    init: F,
+) -> Result<Vec<ModInfo>, MintError>
+where
+    F: Fn(&mut State, String, &ProviderFactory) -> Result<(), MintError>,
+{
+    loop {
+        match state.store.resolve_mods(mod_specs, false).await {
+            Ok(mods) => return Ok(mods.into_values().collect()),
+            Err(ProviderError::NoProvider { url, factory }) => init(state, url, factory)?,
+            Err(e) => Err(e)?,
+        }
+    }
+}
+
 #[allow(clippy::needless_pass_by_ref_mut)]
 pub async fn resolve_ordered_with_provider_init<F>(
     state: &mut State,
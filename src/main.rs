@@ -1,17 +1,43 @@
-use std::collections::BTreeSet;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
-use clap::{Parser, Subcommand};
-use tracing::{debug, info};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
-use mint::mod_lints::{run_lints, LintId};
-use mint::providers::ProviderFactory;
-use mint::{gui::gui, providers::ModSpecification, state::State};
+use mint::dependencies::deps_to_node_link;
+use mint::integrate::{
+    link_mod, orphaned_temp_files, stale_install_lock, IntegrateOptions, IntegrationError,
+};
+use mint::mod_lints::{apply_lint_fix, lint_mod_dir, run_lints, LintError, LintId};
+use mint::pak::{extract_pak, list_pak_contents, list_pak_entry_names};
+use mint::providers::{
+    active_proxy, check_for_mod_updates, check_proxy, configure_proxy, filter_by_tag, fuzzy_search,
+    http_client_builder, load_credentials, resolve_batch, search_by_author,
+    spawn_background_update_check, store_credentials, DownloadCache, InstalledVersion, ModInfo,
+    ModSource, ModStore, ModType, OfflineModeProvider, PendingUpdates, ProviderError,
+    ProviderFactory, ProviderStatus, ResolutionCache, SemverVersion, AUTHOR_SEARCH_TTL,
+    DEFAULT_RESOLUTION_TTL,
+};
+use mint::state::recent::RecentlyInstalled;
+use mint::state::{read_config, read_log_format, read_update_channel, Config, State};
 use mint::{
-    resolve_ordered_with_provider_init, resolve_unordered_and_integrate_with_provider_init, Dirs,
-    MintError,
+    apply_profile, installed_profile, resolve_ordered_with_provider_init,
+    resolve_unordered_and_integrate_with_provider_init, resolve_with_provider_init, switch_profile,
+    Dirs, MintError,
 };
+use mint::{gui::gui, providers::ModSpecification};
+use mint_lib::manifest::{
+    verify_installation, HashCache, Manifest, ManifestMod, VerificationFailure,
+};
+use mint_lib::profile::Profile;
+use mint_lib::update::{self_update, UpdateChannel, GITHUB_REPO};
+use mint_lib::{DBSZInstallation, LogConfig, LogFormat};
 
 /// Command line integration tool.
 #[derive(Parser, Debug)]
@@ -27,6 +53,23 @@ struct ActionIntegrate {
     #[arg(short, long)]
     update: bool,
 
+    /// Check that the mods could be integrated without modifying the game installation.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Don't warn about mods that modify the same files.
+    #[arg(long)]
+    ignore_conflicts: bool,
+
+    /// Also integrate the dependencies of the given mods.
+    #[arg(long)]
+    with_deps: bool,
+
+    /// Use previously resolved and downloaded mods regardless of their age and only contact
+    /// providers for mods that were never resolved.
+    #[arg(long, conflicts_with = "update")]
+    offline: bool,
+
     /// Paths of mods to integrate
     ///
     /// Can be a file path or URL to a .pak or .zip file or a URL to a mod on https://mod.io/g/drg
@@ -40,7 +83,11 @@ struct ActionIntegrate {
 
 /// Integrate a profile
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct ActionIntegrateProfile {
+    #[command(subcommand)]
+    action: Option<ProfileAction>,
+
     /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
     /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
     /// necessary if it cannot be found automatically.
@@ -52,8 +99,54 @@ struct ActionIntegrateProfile {
     #[arg(short, long)]
     update: bool,
 
+    /// Check that the mods could be integrated without modifying the game installation.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Don't warn about mods that modify the same files.
+    #[arg(long)]
+    ignore_conflicts: bool,
+
     /// Profile to integrate.
-    profile: String,
+    #[arg(required = true)]
+    profile: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileAction {
+    Save(ActionProfileSave),
+    Switch(ActionProfileSwitch),
+}
+
+/// Save the installed mods as a shareable profile in the profiles directory.
+#[derive(Parser, Debug)]
+struct ActionProfileSave {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Description of the profile.
+    #[arg(long)]
+    description: Option<String>,
+
+    /// Name of the profile.
+    name: String,
+}
+
+/// Switch the installed mods to a profile saved in the profiles directory. Only mods which are
+/// not installed already are downloaded.
+#[derive(Parser, Debug)]
+struct ActionProfileSwitch {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Name of the profile.
+    name: String,
 }
 
 /// Launch via steam
@@ -75,12 +168,360 @@ struct ActionLint {
     profile: String,
 }
 
+/// Install the mods of a shared profile.
+#[derive(Parser, Debug)]
+#[command(group(clap::ArgGroup::new("profile").required(true).args(["input", "url"])))]
+struct ActionImport {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// TOML or JSON profile file to install, JSON if the extension is `.json`.
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// URL of a JSON profile to install.
+    #[arg(long, alias = "from-url")]
+    url: Option<String>,
+
+    /// Also reinstall mods which are already installed at the version of the profile.
+    #[arg(long)]
+    force: bool,
+}
+
+/// Show the dependencies between the mods of a profile.
+#[derive(Parser, Debug)]
+struct ActionDeps {
+    /// Print a node-link graph as JSON instead of a list.
+    #[arg(long)]
+    json: bool,
+
+    /// Profile to show.
+    profile: String,
+}
+
+/// Resolve every mod of a profile and report the ones that fail.
+#[derive(Parser, Debug)]
+struct ActionResolve {
+    /// Number of mods to resolve at once.
+    #[arg(long, default_value_t = 5)]
+    concurrency: usize,
+
+    /// Use previously resolved mods regardless of their age and only query providers for mods
+    /// that were never resolved.
+    #[arg(long)]
+    offline: bool,
+
+    /// Profile to resolve.
+    profile: String,
+}
+
+/// Search the mods that were resolved before.
+#[derive(Parser, Debug)]
+struct ActionSearch {
+    /// Only show mods whose name contains this, similar names are suggested if there are none.
+    query: Option<String>,
+
+    /// Search the providers for mods by this author instead.
+    #[arg(long)]
+    author: Option<String>,
+
+    /// Only show mods with this tag.
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Only show mods with this SPDX license expression.
+    #[arg(long)]
+    license: Option<String>,
+}
+
+/// Show the release notes of a mod.
+#[derive(Parser, Debug)]
+struct ActionChangelog {
+    /// Use the release notes of the last resolution regardless of its age.
+    #[arg(long)]
+    offline: bool,
+
+    /// URL of the mod.
+    mod_url: String,
+}
+
+/// Show the metadata of a mod.
+#[derive(Parser, Debug)]
+struct ActionInfo {
+    /// Also show fields specific to the provider of the mod.
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// URL of the mod.
+    mod_url: String,
+}
+
+/// Install a local mod as a link to its directory so changes are picked up without integrating.
+#[derive(Parser, Debug)]
+struct ActionLink {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Directory of the mod.
+    path: PathBuf,
+}
+
+/// Disable an installed mod without uninstalling it.
+#[derive(Parser, Debug)]
+struct ActionDisable {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Name of the installed mod.
+    name: String,
+}
+
+/// Enable a mod disabled with `mint disable`.
+#[derive(Parser, Debug)]
+struct ActionEnable {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Name of the installed mod.
+    name: String,
+}
+
+/// Update the installed mods to their latest versions.
+#[derive(Parser, Debug)]
+struct ActionUpdate {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Only print the available updates without downloading anything.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Print a table of the mods with newer versions and remember them without resolving the
+    /// updates for installation.
+    #[arg(long, conflicts_with = "dry_run")]
+    check: bool,
+
+    /// Only update the installed mod with this name.
+    #[arg(long = "mod")]
+    mod_name: Option<String>,
+
+    /// Update to the latest versions resolved before, e.g. by `--check`, without contacting
+    /// providers. Updates that were not downloaded before cannot be installed.
+    #[arg(long, conflicts_with = "check")]
+    offline: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+enum ProfileFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+/// Save the installed mods as a profile which installs the same versions elsewhere.
+#[derive(Parser, Debug)]
+struct ActionExport {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// File to write the profile to instead of stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Format of the profile.
+    #[arg(long, value_enum, default_value_t)]
+    format: ProfileFormat,
+}
+
+/// Check that the files of the installed mods are unchanged.
+#[derive(Parser, Debug)]
+struct ActionVerify {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Only verify the installed mod with this name.
+    #[arg(long = "mod")]
+    mod_name: Option<String>,
+
+    /// Also compare the SHA-256 of every file, which is slow but detects changes that keep the
+    /// size. By default only the presence and size of files is checked.
+    #[arg(long)]
+    check_hashes: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum ListFilterType {
+    Pak,
+    Plugin,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+enum ListSort {
+    #[default]
+    Name,
+    /// Largest first
+    Size,
+    /// Most recently installed first
+    Date,
+    /// In the order the game loads the paks
+    LoadOrder,
+}
+
+/// List the mods installed in the game.
+#[derive(Parser, Debug)]
+struct ActionList {
+    /// Path to FSD-WindowsNoEditor.pak (FSD-WinGDK.pak for Microsoft Store version) located
+    /// inside the "Deep Rock Galactic" installation directory under FSD/Content/Paks. Only
+    /// necessary if it cannot be found automatically.
+    #[arg(short, long)]
+    fsd_pak: Option<PathBuf>,
+
+    /// Print a JSON array instead of a table.
+    #[arg(long)]
+    json: bool,
+
+    /// Only list mods of this type.
+    #[arg(long, value_enum)]
+    filter_type: Option<ListFilterType>,
+
+    /// Order of the listed mods.
+    #[arg(long, value_enum, default_value_t)]
+    sort: ListSort,
+}
+
+/// Show the most recently installed mods.
+#[derive(Parser, Debug)]
+struct ActionRecent {
+    /// Forget the installed mods instead.
+    #[arg(long)]
+    clear: bool,
+}
+
+/// Update mint to the latest release.
+#[derive(Parser, Debug)]
+struct ActionSelfUpdate {
+    /// Switch to another update channel: stable, beta or nightly. The channel is remembered for
+    /// later updates.
+    #[arg(long)]
+    channel: Option<UpdateChannel>,
+}
+
+/// Check the game installation, providers and cache for common problems.
+#[derive(Parser, Debug)]
+struct ActionDoctor {}
+
+/// List the files of a pak.
+#[derive(Parser, Debug)]
+struct ActionPakList {
+    /// Only print the paths, which does not require decompressing the entries.
+    #[arg(long)]
+    names_only: bool,
+
+    /// Pak to list.
+    file: PathBuf,
+}
+
+/// Extract the files of a pak.
+#[derive(Parser, Debug)]
+struct ActionPakExtract {
+    /// Only extract files whose path matches this glob, e.g. `**/*.uasset`.
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Pak to extract.
+    file: PathBuf,
+
+    /// Directory to extract the files into.
+    output: PathBuf,
+}
+
+/// Remove cached downloads and resolutions which have not been used recently.
+#[derive(Parser, Debug)]
+struct ActionCacheClean {
+    /// Only print the files which would be removed.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Remove entries not used for this many days.
+    #[arg(long, default_value_t = 30)]
+    older_than: u64,
+}
+
+/// Show the size of the cache.
+#[derive(Parser, Debug)]
+struct ActionCacheStats {}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    Clean(ActionCacheClean),
+    Stats(ActionCacheStats),
+}
+
+/// Manage cached downloads and provider responses.
+#[derive(Parser, Debug)]
+struct ActionCache {
+    #[command(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum PakAction {
+    List(ActionPakList),
+    Extract(ActionPakExtract),
+}
+
+/// Inspect pak files.
+#[derive(Parser, Debug)]
+struct ActionPak {
+    #[command(subcommand)]
+    action: PakAction,
+}
+
 #[derive(Subcommand, Debug)]
 enum Action {
     Integrate(ActionIntegrate),
     Profile(ActionIntegrateProfile),
     Launch(ActionLaunch),
     Lint(ActionLint),
+    Import(ActionImport),
+    Deps(ActionDeps),
+    Resolve(ActionResolve),
+    Search(ActionSearch),
+    Changelog(ActionChangelog),
+    Info(ActionInfo),
+    Pak(ActionPak),
+    Link(ActionLink),
+    Doctor(ActionDoctor),
+    Recent(ActionRecent),
+    List(ActionList),
+    Update(ActionUpdate),
+    Export(ActionExport),
+    Verify(ActionVerify),
+    Enable(ActionEnable),
+    Disable(ActionDisable),
+    Cache(ActionCache),
+    SelfUpdate(ActionSelfUpdate),
 }
 
 #[derive(Parser, Debug)]
@@ -92,6 +533,10 @@ struct Args {
     /// Location to store configs and data
     #[arg(long)]
     appdata: Option<PathBuf>,
+
+    /// Don't check for mod updates in the background or show updates found by the last check.
+    #[arg(long)]
+    no_update_check: bool,
 }
 
 fn main() -> Result<()> {
@@ -102,7 +547,12 @@ fn main() -> Result<()> {
         let _res = ansi_term::enable_ansi_support();
     }
 
-    let args = Args::parse();
+    // --appdata has not been parsed yet so the channel is read from the default config
+    let channel = Dirs::default_xdg()
+        .map(|dirs| read_update_channel(&dirs))
+        .unwrap_or_default();
+    let version = format!("{} ({channel} channel)", env!("CARGO_PKG_VERSION"));
+    let args = Args::from_arg_matches(&Args::command().version(version).get_matches())?;
 
     let dirs = args
         .appdata
@@ -112,7 +562,19 @@ fn main() -> Result<()> {
 
     std::env::set_var("RUST_BACKTRACE", "1");
 
-    let _guard = mint_lib::setup_logging(dirs.data_dir.join("mint.log"), "mint")?;
+    let log_path = dirs.data_dir.join("mint.log");
+    let log_config = LogConfig::load(dirs.config_dir.join("config.toml"))?;
+    let _guard = match (read_log_format(&dirs), log_config.max_files) {
+        (format, Some(max_files)) => mint_lib::setup_logging_rotating(
+            &dirs.data_dir,
+            "mint",
+            max_files,
+            format,
+            &log_config,
+        )?,
+        (LogFormat::Pretty, None) => mint_lib::setup_logging(log_path, "mint", &log_config)?,
+        (LogFormat::Json, None) => mint_lib::setup_logging_json(log_path, "mint", &log_config)?,
+    };
     debug!("logging setup complete");
 
     info!("config dir = {}", dirs.config_dir.display());
@@ -125,6 +587,10 @@ fn main() -> Result<()> {
 
     debug!(?args);
 
+    if !args.no_update_check && wants_update_check(&args.action) {
+        periodic_update_check(&dirs);
+    }
+
     match args.action {
         Some(Action::Integrate(action)) => rt.block_on(async {
             action_integrate(dirs, action).await?;
@@ -145,6 +611,54 @@ fn main() -> Result<()> {
             action_lint(dirs, action).await?;
             Ok(())
         }),
+        Some(Action::Import(action)) => rt.block_on(async {
+            action_import(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Deps(action)) => rt.block_on(async {
+            action_deps(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Resolve(action)) => rt.block_on(async {
+            action_resolve(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Search(action)) => rt.block_on(async {
+            action_search(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Changelog(action)) => rt.block_on(async {
+            action_changelog(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Info(action)) => rt.block_on(async {
+            action_info(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Pak(action)) => action_pak(action),
+        Some(Action::Link(action)) => rt.block_on(async {
+            action_link(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Doctor(action)) => rt.block_on(async {
+            action_doctor(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Recent(action)) => action_recent(dirs, action),
+        Some(Action::List(action)) => action_list(dirs, action),
+        Some(Action::Export(action)) => action_export(dirs, action),
+        Some(Action::Verify(action)) => action_verify(dirs, action),
+        Some(Action::Enable(action)) => action_enable(dirs, action),
+        Some(Action::Disable(action)) => action_disable(dirs, action),
+        Some(Action::Cache(action)) => action_cache(dirs, action),
+        Some(Action::SelfUpdate(action)) => rt.block_on(async {
+            action_self_update(dirs, action).await?;
+            Ok(())
+        }),
+        Some(Action::Update(action)) => rt.block_on(async {
+            action_update(dirs, action).await?;
+            Ok(())
+        }),
         None => {
             std::thread::spawn(move || {
                 rt.block_on(std::future::pending::<()>());
@@ -155,6 +669,101 @@ fn main() -> Result<()> {
     }
 }
 
+/// How long the updates found by a periodic check are shown before mods are checked again
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Whether the command works with the installed mods and may contact providers. Offline commands
+/// and commands unrelated to mods do not start a periodic update check, `mint update` checks for
+/// updates itself.
+fn wants_update_check(action: &Option<Action>) -> bool {
+    match action {
+        None
+        | Some(
+            Action::Profile(_)
+            | Action::Launch(_)
+            | Action::Lint(_)
+            | Action::Deps(_)
+            | Action::List(_)
+            | Action::Verify(_)
+            | Action::Enable(_)
+            | Action::Disable(_),
+        ) => true,
+        Some(Action::Integrate(action)) => !action.offline,
+        Some(Action::Resolve(action)) => !action.offline,
+        _ => false,
+    }
+}
+
+/// Prints the updates found by the last periodic check if it is recent, otherwise starts a check
+/// in the background which the command does not wait for. A check still running when the command
+/// exits is abandoned and started again next time.
+fn periodic_update_check(dirs: &Dirs) {
+    match PendingUpdates::load(&dirs.cache_dir) {
+        Some(pending)
+            if pending
+                .checked_at
+                .elapsed()
+                .is_ok_and(|age| age < UPDATE_CHECK_INTERVAL) =>
+        {
+            if !pending.updates.is_empty() {
+                let names = pending
+                    .updates
+                    .iter()
+                    .map(|u| u.current.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                eprintln!("updates are available for {names}, run `mint update` to install them");
+            }
+        }
+        _ => {
+            let (config, cache_dir) = (read_config(dirs), dirs.cache_dir.clone());
+            // opening the mod store reads the provider cache from disk
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = start_update_check(config, cache_dir) {
+                    debug!("update check not started: {e:#}");
+                }
+            });
+        }
+    }
+}
+
+/// Spawns an update check of each provider for the mods installed by mint at the configured game
+/// path. Only cached mod info is used, so nothing is resolved before the check.
+fn start_update_check(config: Config, cache_dir: PathBuf) -> Result<Vec<JoinHandle<()>>> {
+    let game_path = config
+        .dbsz_path
+        .as_ref()
+        .context("game path is not configured")?;
+    let installation = DBSZInstallation::from_game_path(game_path)?;
+    let manifest = Manifest::load(&installation)?.context("no mods have been installed by mint")?;
+    configure_proxy(config.proxy.as_ref());
+    let store = ModStore::new(&cache_dir, &config.provider_parameters)?;
+
+    let mut by_source: BTreeMap<&'static str, (Arc<dyn ModSource>, Vec<ModInfo>)> = BTreeMap::new();
+    for m in manifest.mods {
+        let Some(info) = m
+            .source
+            .and_then(|url| store.get_mod_info(&ModSpecification::new(url)))
+        else {
+            continue;
+        };
+        let Ok(source) = store.source(&info.spec.url) else {
+            continue;
+        };
+        by_source
+            .entry(source.id())
+            .or_insert_with(|| (source, vec![]))
+            .1
+            .push(info);
+    }
+    Ok(by_source
+        .into_values()
+        .map(|(source, installed)| {
+            spawn_background_update_check(installed, source, cache_dir.clone())
+        })
+        .collect())
+}
+
 #[tracing::instrument(skip(state))]
 fn init_provider(
     state: &mut State,
@@ -163,73 +772,234 @@ fn init_provider(
 ) -> Result<(), MintError> {
     info!("initializing provider for {:?}", url);
 
-    let params = state
+    let mut params = state
         .config
         .provider_parameters
-        .entry(factory.id.to_owned())
-        .or_default();
+        .get(factory.id)
+        .cloned()
+        .unwrap_or_default();
+    let mut credentials = load_credentials(factory.id);
+    params.extend(credentials.clone());
+    let mut prompted = false;
     for p in factory.parameters {
-        if !params.contains_key(p.name) {
+        if !p.optional && !params.contains_key(p.id) {
             // this blocks but since we're calling it on the main thread it'll be fine
             let value =
                 dialoguer::Password::with_theme(&dialoguer::theme::ColorfulTheme::default())
                     .with_prompt(p.description)
                     .interact()
                     .unwrap();
+            credentials.insert(p.id.to_owned(), value.clone());
             params.insert(p.id.to_owned(), value);
+            prompted = true;
         }
     }
-    Ok(state.store.add_provider(factory, params)?)
+    if prompted && let Err(e) = store_credentials(factory.id, &credentials) {
+        warn!("credentials will not be remembered: {e}");
+    }
+    Ok(state.store.add_provider(factory, &params)?)
 }
 
 fn get_pak_path(state: &State, arg: &Option<PathBuf>) -> Result<PathBuf> {
-    arg.as_ref()
-        .or_else(|| state.config.dbsz_path.as_ref())
-        .cloned()
+    if let Some(path) = arg.as_ref().or(state.config.dbsz_path.as_ref()) {
+        return Ok(path.clone());
+    }
+
+    let mut installations = DBSZInstallation::find_all();
+    let installation = if installations.len() > 1 {
+        let selection = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+            .with_prompt("Multiple game installations found, select one")
+            .items(
+                &installations
+                    .iter()
+                    .map(|i| i.root.display().to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .default(0)
+            .interact()?;
+        Some(installations.swap_remove(selection))
+    } else {
+        installations.pop()
+    };
+    installation
+        .map(|i| i.mods_path())
         .context("Could not find DRG pak file, please specify manually with the --fsd_pak flag")
 }
 
 async fn action_integrate(dirs: Dirs, action: ActionIntegrate) -> Result<()> {
     let mut state = State::init(dirs)?;
+    state.store.set_offline(action.offline);
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
 
-    let mod_specs = action
+    let mut mod_specs = action
         .mods
         .into_iter()
-        .map(ModSpecification::new)
-        .collect::<Vec<_>>();
+        .map(ModSpecification::checked_new)
+        .collect::<Result<Vec<_>, _>>()?;
 
-    resolve_unordered_and_integrate_with_provider_init(
-        game_pak_path,
-        &mut state,
-        &mod_specs,
-        action.update,
-        init_provider,
-    )
-    .await
-    .map_err(|e| anyhow!("{}", e))
+    loop {
+        let options = IntegrateOptions {
+            dry_run: action.dry_run,
+            ignore_conflicts: action.ignore_conflicts,
+            lints: state.config.lint_config(),
+            meta: (&*state.config).into(),
+            with_deps: action.with_deps,
+            skip_optional_deps: state.config.skip_optional_deps,
+            ..Default::default()
+        };
+        match resolve_unordered_and_integrate_with_provider_init(
+            &game_pak_path,
+            &mut state,
+            &mod_specs,
+            action.update,
+            options,
+            init_provider,
+        )
+        .await
+        {
+            Err(e) if remove_declared_conflict(&e, &mut mod_specs)? => continue,
+            result => return result.map_err(integration_failure),
+        }
+    }
 }
 
 async fn action_integrate_profile(dirs: Dirs, action: ActionIntegrateProfile) -> Result<()> {
+    let profile = match (action.action, action.profile) {
+        (Some(ProfileAction::Save(action)), _) => return action_profile_save(dirs, action),
+        (Some(ProfileAction::Switch(action)), _) => {
+            return action_profile_switch(dirs, action).await
+        }
+        (None, profile) => profile.context("no profile given")?,
+    };
     let mut state = State::init(dirs)?;
     let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
     debug!(?game_pak_path);
 
     let mut mods = Vec::new();
-    state.mod_data.for_each_enabled_mod(&action.profile, |mc| {
+    state.mod_data.for_each_enabled_mod(&profile, |mc| {
         mods.push(mc.spec.clone());
     });
 
-    resolve_unordered_and_integrate_with_provider_init(
-        game_pak_path,
-        &mut state,
-        &mods,
-        action.update,
-        init_provider,
-    )
-    .await
-    .map_err(|e| anyhow!("{}", e))
+    loop {
+        let options = IntegrateOptions {
+            dry_run: action.dry_run,
+            ignore_conflicts: action.ignore_conflicts,
+            lints: state.config.lint_config(),
+            meta: (&*state.config).into(),
+            ..Default::default()
+        };
+        match resolve_unordered_and_integrate_with_provider_init(
+            &game_pak_path,
+            &mut state,
+            &mods,
+            action.update,
+            options,
+            init_provider,
+        )
+        .await
+        {
+            Err(e) if remove_declared_conflict(&e, &mut mods)? => continue,
+            result => return result.map_err(integration_failure),
+        }
+    }
+}
+
+fn action_profile_save(dirs: Dirs, action: ActionProfileSave) -> Result<()> {
+    let state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+    let mut profile = installed_profile(&installation, &action.name)?
+        .context("no mods have been installed by mint, integrate them first")?;
+    profile.description = action.description;
+    let path = state.dirs.profile_path(&action.name);
+    profile.save(&path)?;
+    println!("saved {} mods to {}", profile.mods.len(), path.display());
+    Ok(())
+}
+
+async fn action_profile_switch(dirs: Dirs, action: ActionProfileSwitch) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    let installation = DBSZInstallation::from_game_path(&game_pak_path)?;
+    let to = Profile::load(&state.dirs.profile_path(&action.name))?;
+    let from = installed_profile(&installation, "installed")?;
+
+    // initializes the providers of all mods involved before changing the installation
+    let specs = from
+        .iter()
+        .flat_map(|p| &p.mods)
+        .chain(&to.mods)
+        .cloned()
+        .collect::<Vec<_>>();
+    resolve_with_provider_init(&mut state, &specs, init_provider).await?;
+
+    let options = IntegrateOptions {
+        lints: state.config.lint_config(),
+        meta: (&*state.config).into(),
+        ..Default::default()
+    };
+    match &from {
+        Some(from) => {
+            let report = switch_profile(&installation, Some(from), &to, &state.store, &options)
+                .await
+                .map_err(|source| integration_failure(MintError::IntegrationError { source }))?;
+            println!(
+                "switched to {:?}: {} added, {} removed, {} kept",
+                to.name,
+                report.added.len(),
+                report.removed.len(),
+                report.reused.len()
+            );
+        }
+        None => {
+            apply_profile(&installation, &to, &state.store, &options)
+                .await
+                .map_err(|source| integration_failure(MintError::IntegrationError { source }))?;
+            println!("installed {} mods of {:?}", to.mods.len(), to.name);
+        }
+    }
+    Ok(())
+}
+
+/// Error to show when integration failed, suggesting to close the game if files could not be
+/// written.
+fn integration_failure(error: MintError) -> anyhow::Error {
+    match &error {
+        MintError::IntegrationError { source } if source.is_io() => {
+            anyhow!("{error}\nif the game is running, close it and try again")
+        }
+        _ => anyhow!("{}", error),
+    }
+}
+
+/// Asks which of two mods declared incompatible to leave out, returning whether integration
+/// should be retried without it.
+fn remove_declared_conflict(error: &MintError, mods: &mut Vec<ModSpecification>) -> Result<bool> {
+    let MintError::IntegrationError {
+        source:
+            IntegrationError::LintError {
+                source: LintError::DeclaredConflict { mod_a, mod_b },
+            },
+    } = error
+    else {
+        return Ok(false);
+    };
+    let selection = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(format!(
+            "{:?} and {:?} are incompatible, select one to remove",
+            mod_a.name, mod_b.name
+        ))
+        .items(&[mod_a.name.as_str(), mod_b.name.as_str(), "Cancel"])
+        .default(0)
+        .interact()?;
+    let removed = match selection {
+        0 => mod_a,
+        1 => mod_b,
+        _ => return Ok(false),
+    };
+    let count = mods.len();
+    mods.retain(|m| *m != removed.spec && m.without_version_constraint() != removed.spec);
+    Ok(mods.len() != count)
 }
 
 async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
@@ -244,6 +1014,22 @@ async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
 
     let mod_paths = resolve_ordered_with_provider_init(&mut state, &mods, init_provider).await?;
 
+    let mod_dirs = mods
+        .iter()
+        .zip(&mod_paths)
+        .filter(|(_, path)| path.is_dir())
+        .filter_map(|(spec, path)| Some((state.store.get_mod_info(spec)?, path.clone())))
+        .collect::<Vec<_>>();
+    let metas = mods
+        .iter()
+        .filter_map(|spec| {
+            let info = state.store.get_mod_info(spec)?;
+            let meta = mint::meta_mod(&info, state.store.get_version_name(spec));
+            Some((spec.clone(), meta))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let lint_config = state.config.lint_config();
     let report = tokio::task::spawn_blocking(move || {
         run_lints(
             &BTreeSet::from([
@@ -256,12 +1042,1243 @@ async fn action_lint(dirs: Dirs, action: ActionLint) -> Result<()> {
                 LintId::ARCHIVE_WITH_MULTIPLE_PAKS,
                 LintId::NON_ASSET_FILES,
                 LintId::SPLIT_ASSET_PAIRS,
+                LintId::OVERSIZED_FILES,
+                LintId::META_MOD,
             ]),
             mods.into_iter().zip(mod_paths).collect(),
+            &metas,
             Some(game_pak_path),
+            &lint_config,
         )
     })
     .await??;
     println!("{:#?}", report);
+
+    for (mod_info, mod_dir) in mod_dirs {
+        let mut fixes = vec![];
+        for (lint, fix) in lint_mod_dir(&mod_info, &mod_dir)? {
+            println!("{}: {lint}", mod_info.name);
+            if let Some(fix) = fix.filter(|f| !fixes.contains(f)) {
+                fixes.push(fix);
+            }
+        }
+        for fix in fixes {
+            let apply = dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+                .with_prompt(format!("{}: {fix}?", mod_info.name))
+                .default(false)
+                .interact()?;
+            if apply {
+                apply_lint_fix(&fix, &mod_dir)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn action_import(dirs: Dirs, action: ActionImport) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    debug!(?game_pak_path);
+
+    let profile = match (&action.input, &action.url) {
+        (Some(path), _) => {
+            let buf = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            if path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+            {
+                Profile::from_json(&buf)?
+            } else {
+                Profile::from_toml(&buf)?
+            }
+        }
+        (None, Some(url)) => Profile::from_url(url).await?,
+        (None, None) => unreachable!("clap requires a profile"),
+    };
+    info!(
+        "importing profile {:?} with {} mods",
+        profile.name,
+        profile.mods.len()
+    );
+
+    let installed = DBSZInstallation::from_game_path(&game_pak_path)
+        .ok()
+        .and_then(|installation| Manifest::load(&installation).ok().flatten())
+        .map(|manifest| manifest.mods)
+        .unwrap_or_default();
+    let (current, to_install): (Vec<_>, Vec<_>) = profile
+        .mods
+        .iter()
+        .cloned()
+        .partition(|spec| !action.force && is_installed(&installed, &profile, spec));
+    println!(
+        "will install {} mods, {} already current",
+        to_install.len(),
+        current.len()
+    );
+    if to_install.is_empty() {
+        return Ok(());
+    }
+    let confirmed = dialoguer::Confirm::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Continue?")
+        .default(true)
+        .interact()?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    // integration records the installed mods in the install manifest
+    let options = IntegrateOptions {
+        lints: state.config.lint_config(),
+        meta: (&*state.config).into(),
+        ..Default::default()
+    };
+    resolve_unordered_and_integrate_with_provider_init(
+        game_pak_path,
+        &mut state,
+        &to_install,
+        false,
+        options,
+        init_provider,
+    )
+    .await
+    .map_err(integration_failure)
+}
+
+/// SHA-256 of the paks installed by the mod
+fn pak_hashes(installed: &ManifestMod) -> Vec<String> {
+    installed
+        .files
+        .iter()
+        .filter(|f| {
+            f.path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("pak"))
+        })
+        .map(|f| f.sha256.clone())
+        .collect()
+}
+
+/// Whether the mod was installed from `spec`, and if the profile records the hashes of its paks,
+/// whether the installed paks are unchanged.
+fn is_installed(installed: &[ManifestMod], profile: &Profile, spec: &ModSpecification) -> bool {
+    installed.iter().any(|m| {
+        let from_spec = [&m.resolution, &m.source]
+            .into_iter()
+            .any(|url| url.as_ref() == Some(&spec.url));
+        from_spec
+            && profile
+                .pak_hashes
+                .get(&spec.url)
+                .is_none_or(|hashes| *hashes == pak_hashes(m))
+    })
+}
+
+async fn action_deps(dirs: Dirs, action: ActionDeps) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let mut mods = Vec::new();
+    state.mod_data.for_each_mod(&action.profile, |mc| {
+        mods.push(mc.spec.clone());
+    });
+
+    let mut mods = resolve_with_provider_init(&mut state, &mods, init_provider).await?;
+    mods.sort_by(|a, b| a.spec.cmp(&b.spec));
+
+    if action.json {
+        println!("{:#}", deps_to_node_link(&mods));
+        return Ok(());
+    }
+    for m in &mods {
+        println!("{} ({})", m.name, m.spec.url);
+        for dep in &m.suggested_dependencies {
+            println!("  -> {}", dep.url);
+        }
+    }
+    Ok(())
+}
+
+/// Resolves the mods, initializing providers that have not been configured yet
+async fn resolve_batch_with_provider_init(
+    state: &mut State,
+    mods: &[ModSpecification],
+    concurrency: usize,
+    update: bool,
+) -> Result<Vec<Result<ModInfo, ProviderError>>, MintError> {
+    loop {
+        let results = resolve_batch(mods, &state.store, concurrency, update).await;
+        let uninitialized = results.iter().find_map(|r| match r {
+            Err(ProviderError::NoProvider { url, factory }) => Some((url.clone(), *factory)),
+            _ => None,
+        });
+        match uninitialized {
+            Some((url, factory)) => init_provider(state, url, factory)?,
+            None => return Ok(results),
+        }
+    }
+}
+
+/// Source of the provider of `url` preferring cached resolutions over contacting the provider
+fn offline_source(state: &mut State, url: &str) -> Result<OfflineModeProvider, MintError> {
+    loop {
+        match state.store.source(url) {
+            Err(ProviderError::NoProvider { url, factory }) => init_provider(state, url, factory)?,
+            source => return Ok(OfflineModeProvider::new(source?, &state.dirs.cache_dir)),
+        }
+    }
+}
+
+async fn action_resolve(dirs: Dirs, action: ActionResolve) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let mut mods = Vec::new();
+    state.mod_data.for_each_mod(&action.profile, |mc| {
+        mods.push(mc.spec.clone());
+    });
+
+    let results = if action.offline {
+        let mut results = vec![];
+        for spec in &mods {
+            results.push(offline_source(&mut state, &spec.url)?.resolve(spec).await);
+        }
+        results
+    } else {
+        resolve_batch_with_provider_init(&mut state, &mods, action.concurrency, false).await?
+    };
+
+    let failed = mods
+        .iter()
+        .zip(&results)
+        .filter_map(|(spec, r)| r.as_ref().err().map(|e| format!("{}: {e}", spec.url)))
+        .collect::<Vec<_>>();
+    let resolved = results.len() - failed.len();
+    if failed.is_empty() {
+        println!("{resolved} resolved");
+    } else {
+        println!(
+            "{resolved} resolved, {} failed: {}",
+            failed.len(),
+            failed.join(", ")
+        );
+    }
     Ok(())
 }
+
+async fn action_search(dirs: Dirs, action: ActionSearch) -> Result<()> {
+    let state = State::init(dirs)?;
+
+    let mut mods = match &action.author {
+        Some(author) => {
+            let mut mods = vec![];
+            for source in state.store.sources() {
+                match search_by_author(author, source.as_ref(), &state.dirs.cache_dir).await {
+                    Ok(found) => mods.extend(found),
+                    Err(e) => warn!("searching {} failed: {e}", source.id()),
+                }
+            }
+            mods
+        }
+        None => state
+            .store
+            .resolutions()
+            .lock()
+            .unwrap()
+            .mods()
+            .cloned()
+            .collect::<Vec<_>>(),
+    };
+    mods.sort_by(|a, b| a.spec.cmp(&b.spec));
+    mods.dedup_by(|a, b| a.spec == b.spec);
+
+    if let Some(query) = &action.query {
+        let lowercase = query.to_lowercase();
+        let (matching, others): (Vec<_>, Vec<_>) = mods
+            .into_iter()
+            .partition(|m| m.name.to_lowercase().contains(&lowercase));
+        if matching.is_empty() {
+            match fuzzy_search(query, &others).first() {
+                Some((suggestion, _)) => println!("Did you mean: {}?", suggestion.name),
+                None => println!("no mods found"),
+            }
+            return Ok(());
+        }
+        mods = matching;
+    }
+
+    let mut found = match &action.tag {
+        Some(tag) => filter_by_tag(&mods, tag),
+        None => mods.iter().collect(),
+    };
+    if let Some(license) = &action.license {
+        found.retain(|m| {
+            m.license
+                .as_deref()
+                .is_some_and(|l| l.eq_ignore_ascii_case(license))
+        });
+    }
+    for m in found {
+        let name = match &m.homepage {
+            Some(homepage) => hyperlink(homepage, &m.name),
+            None => m.name.clone(),
+        };
+        if m.tags.is_empty() {
+            println!("{name} ({})", m.spec.url);
+        } else {
+            println!("{name} ({}) [{}]", m.spec.url, m.tags.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Changelogs longer than this are cut off so they don't flood the terminal
+const MAX_CHANGELOG_CHARS: usize = 2000;
+
+/// Trimmed `changelog` cut off after `MAX_CHANGELOG_CHARS` characters
+fn truncate_changelog(changelog: &str) -> String {
+    let changelog = changelog.trim();
+    match changelog.char_indices().nth(MAX_CHANGELOG_CHARS) {
+        Some((end, _)) => format!("{}\n[... truncated]", &changelog[..end]),
+        None => changelog.to_string(),
+    }
+}
+
+async fn action_changelog(dirs: Dirs, action: ActionChangelog) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let spec = ModSpecification::new(action.mod_url);
+    let mod_info = if action.offline {
+        offline_source(&mut state, &spec.url)?
+            .resolve(&spec)
+            .await?
+    } else {
+        let mods = resolve_with_provider_init(&mut state, &[spec], init_provider).await?;
+        let mut mod_info = mods.into_iter().next().context("mod was not resolved")?;
+        mod_info.changelog = state.store.changelog(&mod_info).await?;
+        mod_info
+    };
+
+    println!("{}", mod_info.name);
+    match mod_info.changelog.as_deref().map(str::trim) {
+        Some(changelog) if !changelog.is_empty() => println!("{}", truncate_changelog(changelog)),
+        _ => println!("no changelog available"),
+    }
+    Ok(())
+}
+
+async fn action_info(dirs: Dirs, action: ActionInfo) -> Result<()> {
+    let mut state = State::init(dirs)?;
+
+    let spec = ModSpecification::new(action.mod_url);
+    let mods =
+        resolve_with_provider_init(&mut state, std::slice::from_ref(&spec), init_provider).await?;
+    let mod_info = mods
+        .iter()
+        .find(|m| m.spec == spec.without_version_constraint())
+        .or(mods.first())
+        .context("mod was not resolved")?;
+    let changelog = state.store.changelog(mod_info).await?;
+    let version = state.store.get_version_name(&mod_info.spec);
+    // the installed version is only shown if the game can be found without asking
+    let installed = state
+        .config
+        .dbsz_path
+        .as_ref()
+        .and_then(|path| DBSZInstallation::from_game_path(path).ok())
+        .or_else(DBSZInstallation::find)
+        .and_then(|installation| Manifest::load(&installation).ok().flatten())
+        .and_then(|manifest| {
+            manifest
+                .mods
+                .into_iter()
+                .find(|m| m.source.as_ref() == Some(&mod_info.spec.url) || m.name == mod_info.name)
+        });
+
+    let mut out = StandardStream::stdout(ColorChoice::Auto);
+    out.set_color(ColorSpec::new().set_bold(true))?;
+    writeln!(out, "{}", mod_info.name)?;
+    out.reset()?;
+
+    let version = version.as_deref().unwrap_or("unknown");
+    match &installed {
+        Some(installed) => {
+            let installed = installed.version.as_deref().unwrap_or("unknown");
+            info_field(
+                &mut out,
+                "version",
+                &format!("{version} (installed: {installed})"),
+            )?
+        }
+        None => info_field(&mut out, "version", version)?,
+    }
+    info_field(&mut out, "provider", mod_info.provider)?;
+    info_field(&mut out, "url", &mod_info.resolution.url.0)?;
+    if !mod_info.authors.is_empty() {
+        let authors = mod_info
+            .authors
+            .iter()
+            .map(|a| match &a.url {
+                Some(url) => format!("{} ({})", a.name, hyperlink(url, url)),
+                None => a.name.clone(),
+            })
+            .collect::<Vec<_>>();
+        info_field(&mut out, "authors", &authors.join(", "))?;
+    }
+    if let Some(license) = &mod_info.license {
+        info_field(&mut out, "license", license)?;
+    }
+    if let Some(category) = &mod_info.category {
+        info_field(&mut out, "category", &category.to_string())?;
+    }
+    if !mod_info.tags.is_empty() {
+        info_field(&mut out, "tags", &mod_info.tags.join(", "))?;
+    }
+    if let Some(homepage) = &mod_info.homepage {
+        info_field(&mut out, "homepage", &hyperlink(homepage, homepage))?;
+    }
+
+    let dependencies = mod_info
+        .suggested_dependencies
+        .iter()
+        .map(|d| d.url.clone())
+        .chain(
+            mod_info
+                .optional_dependencies
+                .iter()
+                .map(|d| format!("{} (optional)", d.url)),
+        )
+        .collect::<Vec<_>>();
+    if !dependencies.is_empty() {
+        info_section(&mut out, "dependencies")?;
+        for dependency in dependencies {
+            writeln!(out, "  {dependency}")?;
+        }
+    }
+    if let Some(description) = mod_info
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+    {
+        info_section(&mut out, "description")?;
+        let width = console::Term::stdout()
+            .size_checked()
+            .map_or(INFO_WIDTH, |(_, columns)| columns as usize);
+        for line in word_wrap(description, width.saturating_sub(2).max(20)) {
+            writeln!(out, "  {line}")?;
+        }
+    }
+    if let Some(changelog) = changelog.as_deref().filter(|c| !c.trim().is_empty()) {
+        info_section(&mut out, "changelog")?;
+        for line in truncate_changelog(changelog).lines() {
+            writeln!(out, "  {line}")?;
+        }
+    }
+    if action.verbose
+        && let serde_json::Value::Object(extra) = &mod_info.extra_metadata
+    {
+        for (key, value) in extra {
+            info_field(&mut out, key, &value.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Terminal width assumed by `mint info` if it cannot be determined
+const INFO_WIDTH: usize = 80;
+
+fn info_field(out: &mut StandardStream, label: &str, value: &str) -> std::io::Result<()> {
+    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+    write!(out, "{label}: ")?;
+    out.reset()?;
+    writeln!(out, "{value}")
+}
+
+fn info_section(out: &mut StandardStream, label: &str) -> std::io::Result<()> {
+    out.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
+    writeln!(out, "{label}:")?;
+    out.reset()
+}
+
+/// Breaks the lines of `text` at spaces so they fit in `width` characters. Words longer than
+/// `width` are kept on their own line.
+fn word_wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+async fn action_link(dirs: Dirs, action: ActionLink) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+
+    let path = std::fs::canonicalize(&action.path)
+        .with_context(|| format!("mod directory {} not found", action.path.display()))?;
+    let spec = ModSpecification::new(format!("file://{}", path.display()));
+    let mods = resolve_with_provider_init(&mut state, &[spec], init_provider).await?;
+    let mod_info = mods.first().context("mod was not resolved")?;
+
+    link_mod(&path, &installation, mod_info)?;
+    println!("linked {} ({})", mod_info.name, path.display());
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Results of the checks run by `mint doctor`, printed as they are added.
+#[derive(Debug, Default)]
+struct DoctorReport {
+    worst: Option<CheckStatus>,
+}
+
+impl DoctorReport {
+    fn pass(&mut self, check: &str) {
+        self.add(CheckStatus::Pass, check, "");
+    }
+
+    /// `suggestion` is printed below the check
+    fn add(&mut self, status: CheckStatus, check: &str, suggestion: &str) {
+        let label = match status {
+            CheckStatus::Pass => "[PASS]",
+            CheckStatus::Warn => "[WARN]",
+            CheckStatus::Fail => "[FAIL]",
+        };
+        println!("{label} {check}");
+        if status != CheckStatus::Pass {
+            println!("       {suggestion}");
+        }
+        self.worst = self.worst.max(Some(status));
+    }
+}
+
+/// How long `mint doctor` waits for the proxy to accept a connection
+const PROXY_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Exits with 0 if every check passed, 1 if any warned and 2 if any failed.
+async fn action_doctor(dirs: Dirs, _action: ActionDoctor) -> Result<()> {
+    let state = State::init(dirs)?;
+    let mut report = DoctorReport::default();
+
+    let installation = state
+        .config
+        .dbsz_path
+        .as_ref()
+        .and_then(|path| DBSZInstallation::from_game_path(path).ok())
+        .or_else(DBSZInstallation::find);
+    match &installation {
+        Some(installation) => report.pass(&format!(
+            "game installation found at {}",
+            installation.root.display()
+        )),
+        None => report.add(
+            CheckStatus::Fail,
+            "game installation found",
+            "set the path of the game in the GUI or pass --fsd-pak to the commands using it",
+        ),
+    }
+
+    if let Some(installation) = &installation {
+        match installation.game_version() {
+            Ok(version) => report.pass(&format!("game version {version} readable")),
+            Err(e) => report.add(
+                CheckStatus::Warn,
+                &format!("game version readable: {e:#}"),
+                "verify the game files, minimum game versions of mods cannot be checked",
+            ),
+        }
+
+        let paks = installation.paks_path();
+        match tempfile::tempfile_in(&paks) {
+            Ok(_) => report.pass("Content/Paks writable"),
+            Err(e) => report.add(
+                CheckStatus::Fail,
+                &format!("Content/Paks writable: {e}"),
+                &format!("make sure {} can be written by this user", paks.display()),
+            ),
+        }
+    }
+
+    if let Some(proxy) = active_proxy() {
+        match check_proxy(&proxy, PROXY_CHECK_TIMEOUT).await {
+            Ok(latency) => report.pass(&format!(
+                "proxy {} reachable ({}ms)",
+                proxy.url,
+                latency.as_millis()
+            )),
+            Err(e) => report.add(
+                CheckStatus::Fail,
+                &format!("proxy {} reachable: {e}", proxy.url),
+                "check the proxy url in the config or the HTTPS_PROXY environment variable",
+            ),
+        }
+    }
+
+    let mut sources = state.store.sources();
+    sources.sort_by_key(|s| s.id());
+    for source in sources {
+        let id = source.id();
+        let suggestion = format!("check your connection and the credentials of {id}");
+        match source.health_check().await {
+            Ok(health) => {
+                let quota = health
+                    .api_quota_remaining
+                    .map_or(String::new(), |q| format!(", {q} requests left"));
+                let check = format!("provider {id} reachable ({}ms{quota})", health.latency_ms);
+                match health.status {
+                    ProviderStatus::Healthy => report.pass(&check),
+                    ProviderStatus::Degraded(reason) => report.add(
+                        CheckStatus::Warn,
+                        &format!("{check}: {reason}"),
+                        &suggestion,
+                    ),
+                    ProviderStatus::Down => report.add(CheckStatus::Fail, &check, &suggestion),
+                }
+            }
+            Err(e) => report.add(
+                CheckStatus::Fail,
+                &format!("provider {id} reachable: {e}"),
+                &suggestion,
+            ),
+        }
+    }
+
+    if let Some(installation) = &installation {
+        match stale_install_lock(installation) {
+            None => report.pass("no stale mint.lock"),
+            Some(lock) => report.add(
+                CheckStatus::Warn,
+                "no stale mint.lock",
+                &format!("delete {} if no integration is running", lock.display()),
+            ),
+        }
+    }
+
+    let cache_size = DownloadCache::load(&state.dirs.cache_dir)
+        .files()
+        .map(|(_, size)| size)
+        .sum();
+    let check = format!(
+        "download cache size {} under {}",
+        format_size(cache_size),
+        format_size(state.config.max_cache_bytes)
+    );
+    if cache_size <= state.config.max_cache_bytes {
+        report.pass(&check);
+    } else {
+        report.add(CheckStatus::Warn, &check, "run `mint cache clean`");
+    }
+
+    if let Some(installation) = &installation {
+        let orphaned = orphaned_temp_files(installation);
+        if orphaned.is_empty() {
+            report.pass("no orphaned temporary files in ~mods");
+        } else {
+            report.add(
+                CheckStatus::Warn,
+                &format!("{} orphaned temporary files in ~mods", orphaned.len()),
+                "delete the .tmp. files and .mint_tmp_ directories if no integration is running",
+            );
+        }
+    }
+
+    match report.worst {
+        Some(CheckStatus::Fail) => std::process::exit(2),
+        Some(CheckStatus::Warn) => std::process::exit(1),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct InstalledMod {
+    name: String,
+    version: Option<String>,
+    #[serde(rename = "type")]
+    mod_type: &'static str,
+    /// ISO-8601 time of the installation, unknown for mods found without a manifest
+    installed_at: Option<String>,
+    /// Total size of the installed files in bytes
+    size: u64,
+    /// Priority of pak mods, paks are loaded one after another in the order of `load_order`
+    priority: Option<i32>,
+    #[serde(skip)]
+    load_order: Option<usize>,
+    disabled: bool,
+}
+
+fn action_list(dirs: Dirs, action: ActionList) -> Result<()> {
+    let state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+    let manifest = match Manifest::load(&installation)? {
+        Some(manifest) => manifest,
+        // installed before manifests were written, only the mod directories can be listed
+        None => Manifest::rebuild(&installation)?,
+    };
+    let load_order = installation
+        .installed_load_order()?
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, priority))| (name, (i, priority)))
+        .collect::<HashMap<_, _>>();
+
+    let mut mods = manifest
+        .mods
+        .into_iter()
+        .filter(|m| match action.filter_type {
+            Some(ListFilterType::Pak) => m.mod_type == ModType::Pak,
+            Some(ListFilterType::Plugin) => m.mod_type == ModType::ModPlugin,
+            None => true,
+        })
+        .map(|m| InstalledMod {
+            // only known if the provider of the mod is configured and has it cached
+            version: m.version.or_else(|| {
+                state
+                    .store
+                    .get_version_name(&ModSpecification::new(m.source?))
+            }),
+            mod_type: match m.mod_type {
+                ModType::ModPlugin => "plugin",
+                ModType::Pak => "pak",
+                ModType::Blueprint => "blueprint",
+                ModType::Config => "config",
+            },
+            installed_at: m.installed_at,
+            size: m.files.iter().map(|f| f.size).sum(),
+            priority: load_order.get(&m.name).map(|(_, priority)| *priority),
+            load_order: load_order.get(&m.name).map(|(i, _)| *i),
+            disabled: m.disabled,
+            name: m.name,
+        })
+        .collect::<Vec<_>>();
+    match action.sort {
+        ListSort::Name => mods.sort_by_key(|m| m.name.to_lowercase()),
+        ListSort::Size => mods.sort_by_key(|m| std::cmp::Reverse(m.size)),
+        // RFC 3339 times in UTC sort chronologically
+        ListSort::Date => mods.sort_by(|a, b| b.installed_at.cmp(&a.installed_at)),
+        // mods which are not loaded as paks last
+        ListSort::LoadOrder => mods.sort_by_key(|m| m.load_order.unwrap_or(usize::MAX)),
+    }
+
+    if action.json {
+        println!("{}", serde_json::to_string_pretty(&mods)?);
+        return Ok(());
+    }
+    let mut table = comfy_table::Table::new();
+    table.set_header([
+        "Name",
+        "Version",
+        "Type",
+        "Priority",
+        "Installed Date",
+        "Size",
+    ]);
+    for m in mods {
+        let installed_at = m
+            .installed_at
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            });
+        let name = if m.disabled {
+            format!("{} (disabled)", m.name)
+        } else {
+            m.name
+        };
+        table.add_row([
+            name,
+            m.version.unwrap_or_else(|| "-".to_string()),
+            m.mod_type.to_string(),
+            m.priority
+                .map_or_else(|| "-".to_string(), |p| p.to_string()),
+            installed_at.unwrap_or_else(|| "-".to_string()),
+            format_size(m.size),
+        ]);
+    }
+    println!("{table}");
+    Ok(())
+}
+
+fn action_enable(dirs: Dirs, action: ActionEnable) -> Result<()> {
+    let state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+    installation.enable_mod(&action.name)?;
+    println!("enabled {}", action.name);
+    Ok(())
+}
+
+fn action_disable(dirs: Dirs, action: ActionDisable) -> Result<()> {
+    let state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+    installation.disable_mod(&action.name)?;
+    println!("disabled {}", action.name);
+    Ok(())
+}
+
+fn action_export(dirs: Dirs, action: ActionExport) -> Result<()> {
+    let state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+    let manifest = Manifest::load(&installation)?
+        .context("no mods have been installed by mint, integrate them first")?;
+
+    let mut profile = Profile {
+        name: "installed".to_string(),
+        description: Some(format!("Mods installed in {}", installation.root.display())),
+        mods: vec![],
+        pak_hashes: Default::default(),
+        generated_by: format!("mint {}", env!("CARGO_PKG_VERSION")),
+    };
+    for m in manifest.mods {
+        let hashes = pak_hashes(&m);
+        // the resolution is pinned to the installed version
+        let Some(url) = m.resolution.or(m.source) else {
+            warn!("skipping {}, it was installed without a source", m.name);
+            continue;
+        };
+        if !hashes.is_empty() {
+            profile.pak_hashes.insert(url.clone(), hashes);
+        }
+        profile.mods.push(ModSpecification::new(url));
+    }
+
+    let serialized = match action.format {
+        ProfileFormat::Toml => profile.to_toml()?,
+        ProfileFormat::Json => profile.to_json()?,
+    };
+    match action.output {
+        Some(path) => std::fs::write(&path, serialized)
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => println!("{serialized}"),
+    }
+    Ok(())
+}
+
+/// Exits with 0 if every mod is intact and 1 if any is not. Installations without a manifest are
+/// scanned to rebuild it.
+fn action_verify(dirs: Dirs, action: ActionVerify) -> Result<()> {
+    let state = State::init(dirs)?;
+    let installation = DBSZInstallation::from_game_path(get_pak_path(&state, &action.fsd_pak)?)?;
+
+    let mut hash_cache = HashCache::load(state.dirs.cache_dir.join("file_hashes.json"));
+    let (mods, failures) = verify_installation(
+        &installation,
+        |m| action.mod_name.as_ref().is_none_or(|name| m.name == *name),
+        action.check_hashes,
+        &mut hash_cache,
+    )?;
+    if let Err(e) = hash_cache.save() {
+        warn!("failed to save file hashes: {e:#}");
+    }
+    if let Some(name) = &action.mod_name
+        && mods.is_empty()
+    {
+        bail!("mod {name:?} is not installed");
+    }
+
+    let mut table = comfy_table::Table::new();
+    table.set_header(["Mod", "Status"]);
+    for m in &mods {
+        let failures = failures
+            .iter()
+            .filter(|f| f.mod_name() == m.name)
+            .collect::<Vec<_>>();
+        let status = if failures.is_empty() {
+            "✓ OK"
+        } else if failures
+            .iter()
+            .any(|f| matches!(f, VerificationFailure::HashMismatch { .. }))
+        {
+            "✗ Hash mismatch"
+        } else if failures
+            .iter()
+            .any(|f| matches!(f, VerificationFailure::SizeMismatch { .. }))
+        {
+            "✗ Size mismatch"
+        } else {
+            "⚠ Missing files"
+        };
+        table.add_row([m.name.as_str(), status]);
+    }
+    println!("{table}");
+    for failure in &failures {
+        match failure {
+            VerificationFailure::MissingFile { mod_name, path } => {
+                println!("{mod_name}: missing {}", path.display())
+            }
+            VerificationFailure::SizeMismatch {
+                mod_name,
+                path,
+                expected,
+                actual,
+            } => println!(
+                "{mod_name}: {} is {actual} bytes, expected {expected}",
+                path.display()
+            ),
+            VerificationFailure::HashMismatch { mod_name, path } => {
+                println!("{mod_name}: {} was modified", path.display())
+            }
+        }
+    }
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Whether `latest` is newer than the installed mod. Versions are compared if both can be
+/// parsed, otherwise any other resolution counts as newer.
+fn is_update(installed: &ManifestMod, latest: &ModInfo, latest_version: Option<&str>) -> bool {
+    match (
+        installed
+            .version
+            .as_deref()
+            .and_then(|v| SemverVersion::parse(v).ok()),
+        latest_version.and_then(|v| SemverVersion::parse(v).ok()),
+    ) {
+        (Some(installed), Some(latest)) => latest > installed,
+        _ => installed.resolution.as_ref() != Some(&latest.resolution.url.0),
+    }
+}
+
+async fn action_update(dirs: Dirs, action: ActionUpdate) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    state.store.set_offline(action.offline);
+    let game_pak_path = get_pak_path(&state, &action.fsd_pak)?;
+    let installation = DBSZInstallation::from_game_path(&game_pak_path)?;
+    let manifest = Manifest::load(&installation)?
+        .context("no mods have been installed by mint, integrate them first")?;
+
+    let mut installed = manifest.mods;
+    if let Some(name) = &action.mod_name {
+        installed.retain(|m| m.name == *name);
+        if installed.is_empty() {
+            bail!("mod {name:?} is not installed");
+        }
+    }
+    let (installed, unknown): (Vec<_>, Vec<_>) =
+        installed.into_iter().partition(|m| m.source.is_some());
+    for m in unknown {
+        warn!("skipping {}, it was installed without a source", m.name);
+    }
+
+    let specs = installed
+        .iter()
+        .map(|m| ModSpecification::new(m.source.clone().unwrap()))
+        .collect::<Vec<_>>();
+    if action.check {
+        return check_updates(&mut state, &installed, &specs).await;
+    }
+    let results = resolve_batch_with_provider_init(&mut state, &specs, 5, true).await?;
+
+    let mut updates = vec![];
+    for (m, result) in installed.iter().zip(results) {
+        let latest = match result {
+            Ok(latest) => latest,
+            Err(e) => {
+                warn!("failed to check {} for updates: {e}", m.name);
+                continue;
+            }
+        };
+        let latest_version = state.store.get_version_name(&latest.spec);
+        if is_update(m, &latest, latest_version.as_deref()) {
+            println!(
+                "{}: {} → {}",
+                m.name,
+                m.version.as_deref().unwrap_or("unknown"),
+                latest_version.as_deref().unwrap_or("unknown")
+            );
+            updates.push(latest.spec);
+        }
+    }
+
+    if updates.is_empty() {
+        println!("all mods are up to date");
+        return Ok(());
+    }
+    if action.dry_run {
+        return Ok(());
+    }
+    let options = IntegrateOptions {
+        lints: state.config.lint_config(),
+        meta: (&*state.config).into(),
+        ..Default::default()
+    };
+    resolve_unordered_and_integrate_with_provider_init(
+        &game_pak_path,
+        &mut state,
+        &updates,
+        true,
+        options,
+        init_provider,
+    )
+    .await
+    .map_err(integration_failure)?;
+    if action.mod_name.is_none() {
+        // updates found by the last background check are installed now
+        PendingUpdates::save(&state.dirs.cache_dir, vec![])?;
+    }
+    Ok(())
+}
+
+/// `mint update --check`, compares the installed versions with the latest versions of each
+/// provider and stores the result in the cache directory.
+async fn check_updates(
+    state: &mut State,
+    installed: &[ManifestMod],
+    specs: &[ModSpecification],
+) -> Result<()> {
+    // resolved from the cache for the mod info of the installed version
+    let current = resolve_batch_with_provider_init(state, specs, 5, false).await?;
+    let mut by_source: BTreeMap<&'static str, (Arc<dyn ModSource>, Vec<_>)> = BTreeMap::new();
+    for (m, info) in installed.iter().zip(current) {
+        let Some(version) = m.version.as_deref().and_then(InstalledVersion::from_name) else {
+            warn!("skipping {}, its installed version is unknown", m.name);
+            continue;
+        };
+        let info = match info {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("failed to check {} for updates: {e}", m.name);
+                continue;
+            }
+        };
+        let source = state.store.source(&info.spec.url)?;
+        by_source
+            .entry(source.id())
+            .or_insert_with(|| (source, vec![]))
+            .1
+            .push((info, version));
+    }
+
+    let mut updates = vec![];
+    for (source, installed) in by_source.into_values() {
+        updates.extend(check_for_mod_updates(&installed, source.as_ref()).await);
+    }
+    let pending = PendingUpdates::save(&state.dirs.cache_dir, updates)?;
+
+    if pending.updates.is_empty() {
+        println!("all mods are up to date");
+        return Ok(());
+    }
+    let mut table = comfy_table::Table::new();
+    table.set_header(["Mod", "Installed", "Latest", "Provider"]);
+    for update in &pending.updates {
+        table.add_row([
+            update.current.name.clone(),
+            update.current_version.to_string(),
+            update.latest_version.to_string(),
+            update.latest.provider.to_string(),
+        ]);
+    }
+    println!("{table}");
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Number of mods shown by `mint recent`
+const RECENT_SHOWN: usize = 10;
+
+fn action_recent(dirs: Dirs, action: ActionRecent) -> Result<()> {
+    let mut recent = RecentlyInstalled::load(&dirs.data_dir);
+    if action.clear {
+        recent.clear()?;
+        return Ok(());
+    }
+    for (info, time) in recent.list().iter().take(RECENT_SHOWN) {
+        println!("{} ({}) {}", info.name, info.spec.url, time_ago(*time));
+    }
+    Ok(())
+}
+
+/// Formats the time relative to now, e.g. `3 hours ago`, or as a date if it is more than a week
+/// ago.
+fn time_ago(time: std::time::SystemTime) -> String {
+    let secs = time.elapsed().map(|d| d.as_secs()).unwrap_or_default();
+    let plural = |n: u64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => plural(secs / 60, "minute"),
+        3600..86400 => plural(secs / 3600, "hour"),
+        86400..604800 => plural(secs / 86400, "day"),
+        _ => chrono::DateTime::<chrono::Local>::from(time)
+            .format("%Y-%m-%d")
+            .to_string(),
+    }
+}
+
+fn action_cache(dirs: Dirs, action: ActionCache) -> Result<()> {
+    let mut downloads = DownloadCache::load(&dirs.cache_dir);
+    let mut resolutions: ResolutionCache = ResolutionCache::load(
+        dirs.cache_dir.join("resolutions.json"),
+        DEFAULT_RESOLUTION_TTL,
+    );
+    let mut author_searches: ResolutionCache<String, Vec<ModInfo>> = ResolutionCache::load(
+        dirs.cache_dir.join("author_searches.json"),
+        AUTHOR_SEARCH_TTL,
+    );
+
+    match action.action {
+        CacheAction::Stats(_) => {
+            println!("location: {}", dirs.cache_dir.display());
+            println!("total size: {}", format_size(dir_size(&dirs.cache_dir)));
+            let download_size = downloads.files().map(|(_, size)| size).sum();
+            println!(
+                "downloads: {} ({})",
+                downloads.files().count(),
+                format_size(download_size)
+            );
+            println!("resolutions: {}", resolutions.len() + author_searches.len());
+        }
+        CacheAction::Clean(clean) => {
+            let older_than = Duration::from_secs(clean.older_than * 24 * 60 * 60);
+            if clean.dry_run {
+                let unused = downloads.unused_for(older_than);
+                for (path, size) in &unused {
+                    println!("{} ({})", path.display(), format_size(*size));
+                }
+                let size = unused.iter().map(|(_, size)| size).sum();
+                println!(
+                    "would remove {} downloads ({}) and {} resolutions",
+                    unused.len(),
+                    format_size(size),
+                    resolutions.count_older_than(older_than)
+                        + author_searches.count_older_than(older_than)
+                );
+                return Ok(());
+            }
+            let index_size = |cache_dir: &Path| {
+                ["resolutions.json", "author_searches.json"]
+                    .iter()
+                    .filter_map(|f| std::fs::metadata(cache_dir.join(f)).ok())
+                    .map(|m| m.len())
+                    .sum::<u64>()
+            };
+            let before = index_size(&dirs.cache_dir);
+            let mut freed = downloads.evict_older_than(older_than)?;
+            let evicted = resolutions.evict_older_than(older_than)?
+                + author_searches.evict_older_than(older_than)?;
+            freed += before.saturating_sub(index_size(&dirs.cache_dir));
+            println!(
+                "removed {evicted} resolutions, freed {}",
+                format_size(freed)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Total size of the files in `path` and its subdirectories
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.metadata() {
+            Ok(m) if m.is_dir() => dir_size(&e.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+async fn action_self_update(dirs: Dirs, action: ActionSelfUpdate) -> Result<()> {
+    let mut state = State::init(dirs)?;
+    if let Some(channel) = action.channel {
+        state.config.update_channel = channel;
+        state.config.save()?;
+        println!("switched to the {channel} channel");
+    }
+
+    let current = SemverVersion::parse(env!("CARGO_PKG_VERSION"))?;
+    let version = self_update(
+        &current,
+        GITHUB_REPO,
+        state.config.update_channel,
+        http_client_builder(),
+    )
+    .await?;
+    if version == current {
+        println!("mint {current} is up to date");
+    } else {
+        println!("updated mint from {current} to {version}, restart mint to use it");
+    }
+    Ok(())
+}
+
+fn action_pak(action: ActionPak) -> Result<()> {
+    match action.action {
+        PakAction::List(action) if action.names_only => {
+            for name in list_pak_entry_names(&action.file)? {
+                println!("{name}");
+            }
+        }
+        PakAction::List(action) => {
+            for entry in list_pak_contents(&action.file)? {
+                match entry.compression {
+                    Some(compression) => println!(
+                        "{} ({} bytes, {} compressed with {compression})",
+                        entry.path, entry.uncompressed_size, entry.compressed_size
+                    ),
+                    None => println!("{} ({} bytes)", entry.path, entry.uncompressed_size),
+                }
+            }
+        }
+        PakAction::Extract(action) => {
+            let extracted = extract_pak(&action.file, &action.output, action.filter.as_deref())?;
+            println!(
+                "extracted {} files to {}",
+                extracted.len(),
+                action.output.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether stdout is a terminal that is likely to render OSC 8 hyperlinks.
+fn supports_hyperlinks() -> bool {
+    use std::io::IsTerminal;
+
+    std::io::stdout().is_terminal()
+        && std::env::var("TERM").is_ok_and(|term| term != "dumb")
+        && std::env::var_os("COLORTERM").is_some()
+}
+
+/// `text` linked to `url` if the terminal supports hyperlinks, otherwise just `text`.
+fn hyperlink(url: &str, text: &str) -> String {
+    if supports_hyperlinks() {
+        format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+    } else {
+        text.to_string()
+    }
+}
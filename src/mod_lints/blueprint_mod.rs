@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use mint_lib::collect_files;
+
+use crate::providers::ModInfo;
+
+use super::{LintError, LintFix};
+
+/// Path prefix every file of a blueprint mod is expected to be under
+const CONTENT_PREFIX: &str = "SparkingZERO/Content/";
+
+/// Extensions of files that have no business being in a blueprint mod
+const EXECUTABLE_EXTENSIONS: &[&str] =
+    &["exe", "dll", "so", "bat", "cmd", "ps1", "sh", "vbs", "py"];
+
+/// Checks that all files of a blueprint mod belong in `Content` and that no executables or
+/// scripts are shipped alongside the assets.
+pub fn lint_blueprint_mod(mod_info: &ModInfo, dir: &Path) -> Vec<(LintError, Option<LintFix>)> {
+    let mut files = vec![];
+    if let Err(source) = collect_files(dir, &mut files) {
+        return vec![(LintError::IoError { source }, None)];
+    }
+
+    let mut errors = vec![];
+    for file in files {
+        let Ok(relative) = file.strip_prefix(dir) else {
+            continue;
+        };
+        let path = relative.to_string_lossy().replace('\\', "/");
+        if !path.starts_with(CONTENT_PREFIX) {
+            // assets packaged relative to the project root only lack the project directory
+            let fix = path
+                .starts_with("Content/")
+                .then(|| LintFix::RenameInvalidPrefix {
+                    from: "Content".to_string(),
+                    to: "SparkingZERO/Content".to_string(),
+                });
+            errors.push((
+                LintError::BlueprintInvalidPrefix {
+                    mod_name: mod_info.name.clone(),
+                    path: path.clone(),
+                },
+                fix,
+            ));
+        }
+        if file
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| EXECUTABLE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        {
+            errors.push((
+                LintError::BlueprintExecutableFile {
+                    mod_name: mod_info.name.clone(),
+                    path,
+                },
+                None,
+            ));
+        }
+    }
+    errors
+}
@@ -0,0 +1,25 @@
+use crate::providers::ModInfo;
+
+use super::LintError;
+
+fn declares_conflict(a: &ModInfo, b: &ModInfo) -> bool {
+    a.conflicts_with
+        .iter()
+        .any(|c| b.spec.satisfies_dependency(c) || b.resolution.url.0 == c.url)
+}
+
+/// Finds pairs of mods where either declares the other as incompatible.
+pub fn lint_declared_conflicts(mods: &[ModInfo]) -> Vec<LintError> {
+    let mut errors = vec![];
+    for (i, mod_a) in mods.iter().enumerate() {
+        for mod_b in &mods[i + 1..] {
+            if declares_conflict(mod_a, mod_b) || declares_conflict(mod_b, mod_a) {
+                errors.push(LintError::DeclaredConflict {
+                    mod_a: Box::new(mod_a.clone()),
+                    mod_b: Box::new(mod_b.clone()),
+                });
+            }
+        }
+    }
+    errors
+}
@@ -0,0 +1,70 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use fs_err as fs;
+use mint_lib::collect_files;
+use mint_lib::mod_info::ModType;
+
+use crate::providers::ModInfo;
+
+use super::LintError;
+
+/// Normalized paths of all files a mod installs. Pak mods are compared by the entries of their
+/// paks, plugin mods by the files in their directory.
+pub(crate) fn mod_file_paths(
+    mod_type: ModType,
+    path: &Path,
+) -> Result<BTreeSet<String>, LintError> {
+    let mut files = vec![];
+    collect_files(path, &mut files)?;
+
+    let mut paths = BTreeSet::new();
+    match mod_type {
+        ModType::ModPlugin | ModType::Blueprint => {
+            for file in files {
+                let relative = file.strip_prefix(path).unwrap_or(&file);
+                paths.insert(
+                    relative
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                        .to_ascii_lowercase(),
+                );
+            }
+        }
+        ModType::Pak => {
+            for file in files
+                .iter()
+                .filter(|f| f.extension().is_some_and(|e| e == "pak"))
+            {
+                let mut reader = BufReader::new(fs::File::open(file)?);
+                let pak = repak::PakBuilder::new().reader(&mut reader)?;
+                let mount = PathBuf::from(pak.mount_point());
+                for entry in pak.files() {
+                    let entry = mount.join(entry).to_string_lossy().replace('\\', "/");
+                    paths.insert(entry.trim_start_matches("../").to_ascii_lowercase());
+                }
+            }
+        }
+        // config mods are merged key by key so they never replace each other's files
+        ModType::Config => {}
+    }
+    Ok(paths)
+}
+
+/// Finds file paths contained in more than one mod. Config mods are skipped as they are merged
+/// key by key instead of replacing each other's files.
+pub fn lint_duplicate_files(mods: &[(ModInfo, PathBuf)]) -> Result<Vec<LintError>, LintError> {
+    let mut owners: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (mod_info, path) in mods {
+        for file in mod_file_paths(mod_info.mod_type, path)? {
+            owners.entry(file).or_default().push(mod_info.name.clone());
+        }
+    }
+
+    Ok(owners
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(path, mods)| LintError::DuplicateFile { path, mods })
+        .collect())
+}
@@ -0,0 +1,157 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use fs_err as fs;
+
+use crate::providers::ModInfo;
+
+use super::{lint_blueprint_mod, LintError};
+use mint_lib::collect_files;
+use mint_lib::mod_info::ModType;
+
+/// Automatic correction of a lint that is safe to apply to a mod directory and can be undone by
+/// reverting the rename or edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintFix {
+    /// Turns file names containing `\` into nested directories
+    NormalizePathSeparators,
+    /// Trims whitespace around string fields of `.uplugin` descriptors
+    TrimMetadataWhitespace,
+    /// Moves the directory `from` to `to`, both relative to the mod directory
+    RenameInvalidPrefix { from: String, to: String },
+}
+
+impl Display for LintFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintFix::NormalizePathSeparators => write!(f, "replace `\\` in file names with `/`"),
+            LintFix::TrimMetadataWhitespace => write!(f, "trim whitespace in plugin descriptors"),
+            LintFix::RenameInvalidPrefix { from, to } => write!(f, "move {from:?} to {to:?}"),
+        }
+    }
+}
+
+/// `.uplugin` descriptors at the root of the mod directory.
+fn plugin_descriptors(mod_dir: &Path) -> Result<Vec<PathBuf>, LintError> {
+    let mut descriptors = vec![];
+    for entry in fs::read_dir(mod_dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("uplugin"))
+        {
+            descriptors.push(path);
+        }
+    }
+    Ok(descriptors)
+}
+
+fn lint_path_separators(mod_dir: &Path) -> Result<Vec<(LintError, Option<LintFix>)>, LintError> {
+    let mut files = vec![];
+    collect_files(mod_dir, &mut files)?;
+    Ok(files
+        .into_iter()
+        .filter_map(|file| {
+            let path = file
+                .strip_prefix(mod_dir)
+                .ok()?
+                .to_string_lossy()
+                .to_string();
+            path.contains('\\').then_some((
+                LintError::NonNormalizedPath { path },
+                Some(LintFix::NormalizePathSeparators),
+            ))
+        })
+        .collect())
+}
+
+fn lint_plugin_descriptors(mod_dir: &Path) -> Result<Vec<(LintError, Option<LintFix>)>, LintError> {
+    let mut errors = vec![];
+    for descriptor in plugin_descriptors(mod_dir)? {
+        let Ok(serde_json::Value::Object(fields)) =
+            serde_json::from_slice::<serde_json::Value>(&fs::read(&descriptor)?)
+        else {
+            continue;
+        };
+        for (field, value) in fields {
+            if let serde_json::Value::String(value) = value
+                && value.trim() != value
+            {
+                errors.push((
+                    LintError::UntrimmedMetadata {
+                        path: descriptor.to_string_lossy().to_string(),
+                        field,
+                    },
+                    Some(LintFix::TrimMetadataWhitespace),
+                ));
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// Runs every lint that inspects an extracted mod directory, pairing each finding with a fix if
+/// one can be applied automatically.
+pub fn lint_mod_dir(
+    mod_info: &ModInfo,
+    mod_dir: &Path,
+) -> Result<Vec<(LintError, Option<LintFix>)>, LintError> {
+    let mut errors = lint_path_separators(mod_dir)?;
+    errors.extend(lint_plugin_descriptors(mod_dir)?);
+    if mod_info.mod_type == ModType::Blueprint {
+        errors.extend(lint_blueprint_mod(mod_info, mod_dir));
+    }
+    Ok(errors)
+}
+
+fn create_parent_dir(path: &Path) -> Result<(), LintError> {
+    let parent = path.parent().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} has no parent directory", path.display()),
+        )
+    })?;
+    fs::create_dir_all(parent)?;
+    Ok(())
+}
+
+/// Applies the fix to the mod directory in place.
+pub fn apply_lint_fix(fix: &LintFix, mod_dir: &Path) -> Result<(), LintError> {
+    match fix {
+        LintFix::NormalizePathSeparators => {
+            let mut files = vec![];
+            collect_files(mod_dir, &mut files)?;
+            for file in files {
+                let path = file.strip_prefix(mod_dir)?.to_string_lossy().to_string();
+                if path.contains('\\') {
+                    let normalized = mod_dir.join(path.replace('\\', "/"));
+                    create_parent_dir(&normalized)?;
+                    fs::rename(&file, normalized)?;
+                }
+            }
+        }
+        LintFix::TrimMetadataWhitespace => {
+            for descriptor in plugin_descriptors(mod_dir)? {
+                let Ok(serde_json::Value::Object(mut fields)) =
+                    serde_json::from_slice::<serde_json::Value>(&fs::read(&descriptor)?)
+                else {
+                    continue;
+                };
+                for value in fields.values_mut() {
+                    if let serde_json::Value::String(s) = value {
+                        *s = s.trim().to_string();
+                    }
+                }
+                let buf = serde_json::to_vec_pretty(&fields).map_err(std::io::Error::from)?;
+                fs::write(&descriptor, buf)?;
+            }
+        }
+        LintFix::RenameInvalidPrefix { from, to } => {
+            let to = mod_dir.join(to);
+            create_parent_dir(&to)?;
+            fs::rename(mod_dir.join(from), to)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use mint_lib::mod_info::MetaMod;
+
+use crate::providers::{ModInfo, ModSpecification};
+
+use super::{Lint, LintCtxt, LintError};
+
+/// Checks that a freshly resolved mod carries the metadata needed to install and display it.
+pub fn lint_metadata(info: &ModInfo) -> Vec<LintError> {
+    let mut errors = vec![];
+    if info.name.trim().is_empty() {
+        errors.push(LintError::MissingMetadata { field: "name" });
+    }
+    if info.resolution.url.0.trim().is_empty() {
+        errors.push(LintError::MissingMetadata {
+            field: "resolution.url",
+        });
+    }
+    errors
+}
+
+/// Reports mods that do not declare under which license they can be redistributed.
+pub fn lint_license(info: &ModInfo) -> Vec<LintError> {
+    if info.license.is_none() {
+        vec![LintError::MissingLicense]
+    } else {
+        vec![]
+    }
+}
+
+/// Checks the fields of a `MetaMod` entry that are shown to other players.
+pub fn lint_meta_mod(meta: &MetaMod) -> Vec<LintError> {
+    let mut errors = vec![];
+    if meta.version.trim().is_empty() {
+        errors.push(LintError::MissingMetadata { field: "version" });
+    }
+    if meta.authors.iter().all(|a| a.name.trim().is_empty()) {
+        errors.push(LintError::MissingMetadata { field: "authors" });
+    }
+    errors
+}
+
+/// Runs `lint_meta_mod` on the `MetaMod` entry of every linted mod that has one.
+pub struct MetaModLint<'a> {
+    pub metas: &'a BTreeMap<ModSpecification, MetaMod>,
+}
+
+impl Lint for MetaModLint<'_> {
+    type Output = BTreeMap<ModSpecification, Vec<LintError>>;
+
+    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError> {
+        Ok(lcx
+            .mods
+            .iter()
+            .filter_map(|(spec, _)| {
+                let errors = lint_meta_mod(self.metas.get(spec)?);
+                (!errors.is_empty()).then(|| (spec.clone(), errors))
+            })
+            .collect())
+    }
+}
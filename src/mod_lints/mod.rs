@@ -1,10 +1,18 @@
 mod archive_multiple_paks;
 mod archive_only_non_pak_files;
 mod asset_register_bin;
+mod blueprint_mod;
 mod conflicting_mods;
+mod declared_conflicts;
+mod duplicate_files;
 mod empty_archive;
+mod fix;
+mod metadata;
 mod non_asset_files;
 mod outdated_pak_version;
+mod oversized_files;
+mod pak_paths;
+mod pak_signatures;
 mod shader_files;
 mod split_asset_pairs;
 mod unmodified_game_assets;
@@ -15,22 +23,34 @@ use std::path::{Path, PathBuf};
 
 use fs_err as fs;
 use indexmap::IndexSet;
+use mint_lib::mod_info::MetaMod;
 use repak::PakReader;
 use snafu::prelude::*;
-use tracing::trace;
+use tracing::{debug, error, trace, warn};
 
 use self::archive_multiple_paks::ArchiveMultiplePaksLint;
 use self::archive_only_non_pak_files::ArchiveOnlyNonPakFilesLint;
 use self::asset_register_bin::AssetRegisterBinLint;
+pub use self::blueprint_mod::lint_blueprint_mod;
+pub use self::declared_conflicts::lint_declared_conflicts;
+pub use self::duplicate_files::lint_duplicate_files;
+pub(crate) use self::duplicate_files::mod_file_paths;
 use self::empty_archive::EmptyArchiveLint;
+pub use self::fix::{apply_lint_fix, lint_mod_dir, LintFix};
+use self::metadata::MetaModLint;
+pub use self::metadata::{lint_license, lint_meta_mod, lint_metadata};
 use self::non_asset_files::NonAssetFilesLint;
 use self::outdated_pak_version::OutdatedPakVersionLint;
+use self::oversized_files::OversizedFilesLint;
+pub use self::oversized_files::DEFAULT_MAX_FILE_BYTES;
+pub use self::pak_paths::lint_pak_paths;
+pub use self::pak_signatures::lint_pak_signatures;
 use self::shader_files::ShaderFilesLint;
 pub use self::split_asset_pairs::SplitAssetPair;
 use self::split_asset_pairs::SplitAssetPairsLint;
 use self::unmodified_game_assets::UnmodifiedGameAssetsLint;
 use crate::mod_lints::conflicting_mods::ConflictingModsLint;
-use crate::providers::{ModSpecification, ReadSeek};
+use crate::providers::{ModInfo, ModSpecification, ReadSeek};
 
 #[derive(Debug, Snafu)]
 pub enum LintError {
@@ -48,6 +68,81 @@ pub enum LintError {
     OnlyNonPakFiles,
     #[snafu(display("some lints require specifying a valid game pak path"))]
     InvalidGamePath,
+    #[snafu(display("blueprint mod {mod_name:?} contains {path:?} outside of Content"))]
+    BlueprintInvalidPrefix { mod_name: String, path: String },
+    #[snafu(display("blueprint mod {mod_name:?} contains executable or script file {path:?}"))]
+    BlueprintExecutableFile { mod_name: String, path: String },
+    #[snafu(display("{path:?} is {size} bytes which exceeds the limit of {limit} bytes"))]
+    OversizedFile { path: String, size: u64, limit: u64 },
+    #[snafu(display("{path:?} is contained in multiple mods: {}", mods.join(", ")))]
+    DuplicateFile { path: String, mods: Vec<String> },
+    #[snafu(display("pak {pak:?} contains {entry:?} outside of the game's content directories"))]
+    InvalidPakPath { pak: String, entry: String },
+    #[snafu(display("missing metadata field `{field}`"))]
+    MissingMetadata { field: &'static str },
+    #[snafu(display("file name {path:?} contains `\\` instead of a path separator"))]
+    NonNormalizedPath { path: String },
+    #[snafu(display("field `{field}` of {path:?} has leading or trailing whitespace"))]
+    UntrimmedMetadata { path: String, field: String },
+    #[snafu(display("mods {:?} and {:?} are declared incompatible", mod_a.name, mod_b.name))]
+    DeclaredConflict {
+        mod_a: Box<ModInfo>,
+        mod_b: Box<ModInfo>,
+    },
+    #[snafu(display("no license declared"))]
+    MissingLicense,
+    #[snafu(transparent)]
+    PakSignatureError {
+        source: crate::pak::PakSignatureError,
+    },
+    #[snafu(display("pak {pak:?} is not signed by a trusted key"))]
+    UntrustedPak { pak: String },
+}
+
+/// How a lint affects integration: errors abort it, warnings and infos are only logged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintError {
+    /// Name of the lint rule that produced the error, used to suppress it.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            LintError::BlueprintInvalidPrefix { .. } => "blueprint_invalid_prefix",
+            LintError::BlueprintExecutableFile { .. } => "blueprint_executable_file",
+            LintError::OversizedFile { .. } => "oversized_files",
+            LintError::DuplicateFile { .. } => "duplicate_files",
+            LintError::InvalidPakPath { .. } => "invalid_pak_path",
+            LintError::MissingMetadata { .. } => "missing_metadata",
+            LintError::NonNormalizedPath { .. } => "non_normalized_path",
+            LintError::UntrimmedMetadata { .. } => "untrimmed_metadata",
+            LintError::DeclaredConflict { .. } => "declared_conflict",
+            LintError::MissingLicense => "missing_license",
+            LintError::UntrustedPak { .. } => "untrusted_pak",
+            // failures to run a lint rather than findings of one
+            _ => "lint_failure",
+        }
+    }
+
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            LintError::BlueprintInvalidPrefix { .. }
+            | LintError::BlueprintExecutableFile { .. }
+            | LintError::OversizedFile { .. }
+            | LintError::DuplicateFile { .. }
+            | LintError::NonNormalizedPath { .. }
+            | LintError::MissingLicense
+            | LintError::UntrustedPak { .. } => LintSeverity::Warning,
+            LintError::MissingMetadata { .. } | LintError::UntrimmedMetadata { .. } => {
+                LintSeverity::Info
+            }
+            LintError::DeclaredConflict { .. } => LintSeverity::Error,
+            _ => LintSeverity::Error,
+        }
+    }
 }
 
 pub struct LintCtxt {
@@ -253,6 +348,79 @@ impl LintId {
     pub const UNMODIFIED_GAME_ASSETS: Self = LintId {
         name: "unmodified_game_assets",
     };
+    pub const OVERSIZED_FILES: Self = LintId {
+        name: "oversized_files",
+    };
+    pub const META_MOD: Self = LintId { name: "meta_mod" };
+}
+
+/// User configurable parameters of lints.
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    pub max_file_bytes: u64,
+    /// Treat every warning as an error
+    pub warn_as_error: bool,
+    /// Rule names of lints that are not reported
+    pub suppressed_lints: Vec<String>,
+    /// Treat mods without a license as an error
+    pub require_license: bool,
+    /// Treat paks without a valid signature as an error
+    pub require_pak_signatures: bool,
+    /// PEM file of the ed25519 key paks are signed with, without it signatures are not checked
+    pub pak_signing_key: Option<PathBuf>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            warn_as_error: false,
+            suppressed_lints: vec![],
+            require_license: false,
+            require_pak_signatures: false,
+            pak_signing_key: None,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Severity of the lint after applying the config, `None` if it is suppressed.
+    pub fn severity(&self, lint: &LintError) -> Option<LintSeverity> {
+        if self.suppressed_lints.iter().any(|r| r == lint.rule()) {
+            return None;
+        }
+        match lint.severity() {
+            LintSeverity::Warning if self.warn_as_error => Some(LintSeverity::Error),
+            _ if self.require_license && matches!(lint, LintError::MissingLicense) => {
+                Some(LintSeverity::Error)
+            }
+            _ if self.require_pak_signatures && matches!(lint, LintError::UntrustedPak { .. }) => {
+                Some(LintSeverity::Error)
+            }
+            severity => Some(severity),
+        }
+    }
+
+    /// Logs warnings and infos, returning the first error if any lint is an error. `context` is
+    /// prefixed to every message.
+    pub fn report(&self, context: &str, lints: Vec<LintError>) -> Result<(), LintError> {
+        let mut first_error = None;
+        for lint in lints {
+            match self.severity(&lint) {
+                None => {}
+                Some(LintSeverity::Info) => debug!("{context}{lint}"),
+                Some(LintSeverity::Warning) => warn!("{context}{lint}"),
+                Some(LintSeverity::Error) => {
+                    if first_error.is_none() {
+                        first_error = Some(lint);
+                    } else {
+                        error!("{context}{lint}");
+                    }
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
 }
 
 #[derive(Default, Debug)]
@@ -268,12 +436,18 @@ pub struct LintReport {
     pub split_asset_pairs_mods:
         Option<BTreeMap<ModSpecification, BTreeMap<String, SplitAssetPair>>>,
     pub unmodified_game_assets_mods: Option<BTreeMap<ModSpecification, BTreeSet<String>>>,
+    pub oversized_file_mods: Option<BTreeMap<ModSpecification, Vec<LintError>>>,
+    pub meta_mod_errors: Option<BTreeMap<ModSpecification, Vec<LintError>>>,
 }
 
+/// `metas` are the `MetaMod` entries the mods would be installed with, checked by
+/// `LintId::META_MOD`.
 pub fn run_lints(
     enabled_lints: &BTreeSet<LintId>,
     mods: IndexSet<(ModSpecification, PathBuf)>,
+    metas: &BTreeMap<ModSpecification, MetaMod>,
     fsd_pak_path: Option<PathBuf>,
+    config: &LintConfig,
 ) -> Result<LintReport, LintError> {
     let lint_ctxt = LintCtxt::init(mods, fsd_pak_path)?;
     let mut lint_report = LintReport::default();
@@ -320,6 +494,17 @@ pub fn run_lints(
                 let res = UnmodifiedGameAssetsLint.check_mods(&lint_ctxt)?;
                 lint_report.unmodified_game_assets_mods = Some(res);
             }
+            LintId::OVERSIZED_FILES => {
+                let res = OversizedFilesLint {
+                    max_bytes: config.max_file_bytes,
+                }
+                .check_mods(&lint_ctxt)?;
+                lint_report.oversized_file_mods = Some(res);
+            }
+            LintId::META_MOD => {
+                let res = MetaModLint { metas }.check_mods(&lint_ctxt)?;
+                lint_report.meta_mod_errors = Some(res);
+            }
             _ => unimplemented!(),
         }
     }
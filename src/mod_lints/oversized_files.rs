@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::io::{BufReader, Cursor, Read, Write};
+use std::path::Path;
+
+use fs_err as fs;
+
+use crate::providers::{ModSpecification, ReadSeek};
+
+use super::{Lint, LintCtxt, LintError};
+
+/// Files above this size are almost always uncompressed source assets included by mistake
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 256 * 1024 * 1024;
+
+pub struct OversizedFilesLint {
+    pub max_bytes: u64,
+}
+
+impl Default for OversizedFilesLint {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_FILE_BYTES,
+        }
+    }
+}
+
+/// Counts bytes written so pak entries can be measured without buffering them
+#[derive(Default)]
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl OversizedFilesLint {
+    fn check_size(&self, path: String, size: u64, errors: &mut Vec<LintError>) {
+        if size > self.max_bytes {
+            errors.push(LintError::OversizedFile {
+                path,
+                size,
+                limit: self.max_bytes,
+            });
+        }
+    }
+
+    fn check_pak(
+        &self,
+        name: &str,
+        mut reader: Box<dyn ReadSeek>,
+        errors: &mut Vec<LintError>,
+    ) -> Result<(), LintError> {
+        let pak = repak::PakBuilder::new().reader(&mut reader)?;
+        for file in pak.files() {
+            let mut counter = ByteCounter::default();
+            pak.read_file(&file, &mut reader, &mut counter)?;
+            self.check_size(format!("{name}/{file}"), counter.0, errors);
+        }
+        Ok(())
+    }
+
+    fn check_dir(
+        &self,
+        root: &Path,
+        dir: &Path,
+        errors: &mut Vec<LintError>,
+    ) -> Result<(), LintError> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.check_dir(root, &path, errors)?;
+                continue;
+            }
+            let name = path
+                .strip_prefix(root)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if path.extension().is_some_and(|e| e == "pak") {
+                let reader = Box::new(BufReader::new(fs::File::open(&path)?));
+                self.check_pak(&name, reader, errors)?;
+            } else {
+                self.check_size(name, fs::metadata(&path)?.len(), errors);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_mod(&self, path: &Path) -> Result<Vec<LintError>, LintError> {
+        let mut errors = vec![];
+        if path.is_dir() {
+            self.check_dir(path, path, &mut errors)?;
+            return Ok(errors);
+        }
+
+        let mut reader = BufReader::new(fs::File::open(path)?);
+        if let Ok(mut archive) = zip::ZipArchive::new(&mut reader) {
+            for i in 0..archive.len() {
+                let mut file = archive
+                    .by_index(i)
+                    .map_err(|_| LintError::ZipArchiveError)?;
+                let Some(name) = file
+                    .enclosed_name()
+                    .map(|p| p.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                if !file.is_file() {
+                    continue;
+                }
+                if name.ends_with(".pak") {
+                    let mut buf = vec![];
+                    file.read_to_end(&mut buf)?;
+                    self.check_pak(&name, Box::new(Cursor::new(buf)), &mut errors)?;
+                } else {
+                    self.check_size(name, file.size(), &mut errors);
+                }
+            }
+        } else {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.check_pak(
+                &name,
+                Box::new(BufReader::new(fs::File::open(path)?)),
+                &mut errors,
+            )?;
+        }
+        Ok(errors)
+    }
+}
+
+impl Lint for OversizedFilesLint {
+    type Output = BTreeMap<ModSpecification, Vec<LintError>>;
+
+    fn check_mods(&mut self, lcx: &LintCtxt) -> Result<Self::Output, LintError> {
+        let mut oversized_files = BTreeMap::new();
+        for (mod_spec, path) in &lcx.mods {
+            let errors = self.check_mod(path)?;
+            if !errors.is_empty() {
+                oversized_files.insert(mod_spec.clone(), errors);
+            }
+        }
+        Ok(oversized_files)
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::BufReader;
+use std::path::Path;
+
+use fs_err as fs;
+use mint_lib::collect_files;
+
+use super::LintError;
+
+/// Directories pak entries are allowed to extract to
+const ALLOWED_PREFIXES: &[&str] = &["SparkingZERO/Content/", "SparkingZERO/Plugins/"];
+
+fn is_allowed(entry: &str) -> bool {
+    ALLOWED_PREFIXES
+        .iter()
+        .any(|prefix| entry.starts_with(prefix))
+        && !entry.split('/').any(|c| c == "..")
+}
+
+/// Checks that every entry of every pak in the mod extracts into the game's content or plugin
+/// directories.
+pub fn lint_pak_paths(mod_dir: &Path) -> Result<Vec<LintError>, LintError> {
+    let mut paks = vec![];
+    collect_files(mod_dir, &mut paks)?;
+    paks.retain(|p| p.extension().is_some_and(|e| e == "pak"));
+
+    let mut errors = vec![];
+    for pak_path in paks {
+        let mut reader = BufReader::new(fs::File::open(&pak_path)?);
+        let pak = repak::PakBuilder::new().reader(&mut reader)?;
+        let mount = pak.mount_point().replace('\\', "/");
+        // mount points are relative to the engine binaries, e.g. `../../../SparkingZERO/Content/`
+        let mount = mount.trim_start_matches("../");
+        for file in pak.files() {
+            let entry = format!("{}/{file}", mount.trim_end_matches('/'));
+            if !is_allowed(entry.trim_start_matches('/')) {
+                errors.push(LintError::InvalidPakPath {
+                    pak: pak_path
+                        .strip_prefix(mod_dir)
+                        .unwrap_or(&pak_path)
+                        .to_string_lossy()
+                        .to_string(),
+                    entry,
+                });
+            }
+        }
+    }
+    Ok(errors)
+}
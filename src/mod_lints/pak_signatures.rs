@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use mint_lib::collect_files;
+use tracing::debug;
+
+use crate::pak::{verify_pak_signature, SignatureResult};
+
+use super::LintError;
+
+/// Reports every pak of the mod that is not signed by the key `public_key_pem`.
+pub fn lint_pak_signatures(
+    mod_dir: &Path,
+    public_key_pem: &[u8],
+) -> Result<Vec<LintError>, LintError> {
+    let mut paks = vec![];
+    collect_files(mod_dir, &mut paks)?;
+    paks.retain(|p| p.extension().is_some_and(|e| e == "pak"));
+
+    let mut errors = vec![];
+    for pak_path in paks {
+        let result = verify_pak_signature(&pak_path, public_key_pem)?;
+        debug!("signature of {}: {result:?}", pak_path.display());
+        if result != SignatureResult::Valid {
+            errors.push(LintError::UntrustedPak {
+                pak: pak_path
+                    .strip_prefix(mod_dir)
+                    .unwrap_or(&pak_path)
+                    .to_string_lossy()
+                    .to_string(),
+            });
+        }
+    }
+    Ok(errors)
+}
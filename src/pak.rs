@@ -0,0 +1,720 @@
+//! Inspection of pak files outside of integration.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+
+use ed25519_dalek::pkcs8::DecodePublicKey;
+use ed25519_dalek::{Signature, VerifyingKey};
+use fs_err as fs;
+use sha2::{Digest, Sha512};
+use snafu::prelude::*;
+
+/// File stored in a pak.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PakEntry {
+    /// Path relative to the mount point
+    pub path: String,
+    pub uncompressed_size: u64,
+    /// Size stored in the pak, equal to `uncompressed_size` for uncompressed entries
+    pub compressed_size: u64,
+    /// Name of the compression method, `None` if the entry is stored uncompressed
+    pub compression: Option<String>,
+}
+
+/// Lists every entry of a pak sorted by path. Sizes and compression methods are read from the
+/// index without decompressing any entry. repak does not expose them, so the footer and index are
+/// parsed here.
+pub fn list_pak_contents(pak_path: &Path) -> io::Result<Vec<PakEntry>> {
+    let mut file = BufReader::new(fs::File::open(pak_path)?);
+    let footer = PakFooter::read(&mut file)?;
+    if footer.encrypted_index {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "listing paks with an encrypted index is not supported",
+        ));
+    }
+    let mut entries = footer.read_index(&mut file)?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+const PAK_MAGIC: u32 = 0x5A6F12E1;
+
+/// Version of the first paks with a path hash index and full directory index
+const PAK_VERSION_PATH_HASH_INDEX: u32 = 10;
+
+/// Version of the first paks referring to compression methods by their name in the footer
+const PAK_VERSION_FNAME_COMPRESSION: u32 = 8;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_bytes(reader: &mut impl Read, len: u64) -> io::Result<Vec<u8>> {
+    let mut buf = vec![];
+    reader.take(len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(buf)
+}
+
+fn skip(reader: &mut impl Read, len: u64) -> io::Result<()> {
+    io::copy(&mut reader.take(len), &mut io::sink()).map(|_| ())
+}
+
+/// Unreal `FString`: a length including the nul terminator, negative for UTF-16
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(reader)? as i32;
+    let string = if len < 0 {
+        let buf = read_bytes(reader, 2 * len.unsigned_abs() as u64)?;
+        let chars = buf
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|c| u16::from_le_bytes(*c))
+            .collect::<Vec<_>>();
+        String::from_utf16(&chars).map_err(|_| invalid_data("invalid UTF-16 string in pak"))?
+    } else {
+        let buf = read_bytes(reader, len as u64)?;
+        String::from_utf8(buf).map_err(|_| invalid_data("invalid string in pak"))?
+    };
+    Ok(string.trim_end_matches('\0').to_string())
+}
+
+/// Distance of the magic from the end, versions it is valid for, compression slots
+type FooterLayout = (u64, fn(u32) -> bool, usize);
+
+struct PakFooter {
+    version: u32,
+    /// Version 8 was released in two revisions, the first with 4 compression method names
+    compression_slots: usize,
+    encrypted_index: bool,
+    index_offset: u64,
+    index_size: u64,
+    /// Names entries of version 8 and later refer to by their 1-based slot
+    compression_methods: Vec<String>,
+}
+
+impl PakFooter {
+    /// Finds the footer by trying the distance of the magic from the end of the pak of every
+    /// footer layout, from the newest to the oldest.
+    fn read<R: Read + Seek>(reader: &mut R) -> io::Result<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        let layouts: [FooterLayout; 4] = [
+            (205, |v| v == 9, 5),
+            (204, |v| v >= 8, 5),
+            (172, |v| v == 8, 4),
+            (44, |v| v < 8, 0),
+        ];
+        for (distance, valid, compression_slots) in layouts {
+            let Some(magic_pos) = len.checked_sub(distance) else {
+                continue;
+            };
+            reader.seek(SeekFrom::Start(magic_pos))?;
+            if read_u32(reader)? != PAK_MAGIC {
+                continue;
+            }
+            let version = read_u32(reader)?;
+            if !valid(version) {
+                continue;
+            }
+            let index_offset = read_u64(reader)?;
+            let index_size = read_u64(reader)?;
+            skip(reader, 20 + u64::from(version == 9))?;
+            let compression_methods = (0..compression_slots)
+                .map(|_| {
+                    let name = read_bytes(reader, 32)?;
+                    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                    Ok(String::from_utf8_lossy(&name[..end]).to_string())
+                })
+                .collect::<io::Result<_>>()?;
+
+            let encrypted_index = if version >= 4 {
+                reader.seek(SeekFrom::Start(magic_pos - 1))?;
+                read_u8(reader)? != 0
+            } else {
+                false
+            };
+            return Ok(Self {
+                version,
+                compression_slots,
+                encrypted_index,
+                index_offset,
+                index_size,
+                compression_methods,
+            });
+        }
+        Err(invalid_data("pak footer not found"))
+    }
+
+    /// Name of the compression method an entry refers to, `None` if it is uncompressed
+    fn compression_name(&self, compression: u32) -> io::Result<Option<String>> {
+        if compression == 0 {
+            return Ok(None);
+        }
+        if self.version < PAK_VERSION_FNAME_COMPRESSION {
+            // flags of the compression method rather than a slot
+            return Ok(Some(
+                match compression {
+                    0x01 => "Zlib",
+                    0x02 => "Gzip",
+                    _ => "Custom",
+                }
+                .to_string(),
+            ));
+        }
+        self.compression_methods
+            .get(compression as usize - 1)
+            .filter(|name| !name.is_empty())
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| invalid_data("pak entry refers to an unknown compression method"))
+    }
+
+    /// Entry record stored in full, as in the index of versions before 10 and for entries which
+    /// cannot be encoded in later versions
+    fn read_entry(&self, reader: &mut impl Read, path: String) -> io::Result<PakEntry> {
+        let _offset = read_u64(reader)?;
+        let compressed_size = read_u64(reader)?;
+        let uncompressed_size = read_u64(reader)?;
+        let compression = if self.version == 8 && self.compression_slots == 4 {
+            u32::from(read_u8(reader)?)
+        } else {
+            read_u32(reader)?
+        };
+        if self.version == 1 {
+            skip(reader, 8)?; // timestamp
+        }
+        skip(reader, 20)?; // hash
+        if self.version >= 3 {
+            if compression != 0 {
+                let blocks = read_u32(reader)?;
+                skip(reader, 16 * u64::from(blocks))?;
+            }
+            skip(reader, 1 + 4)?; // flags, compression block size
+        }
+        Ok(PakEntry {
+            path,
+            uncompressed_size,
+            compressed_size,
+            compression: self.compression_name(compression)?,
+        })
+    }
+
+    /// Entry record bit-packed into the encoded entries of versions 10 and later
+    fn read_encoded_entry(&self, reader: &mut impl Read, path: String) -> io::Result<PakEntry> {
+        let bits = read_u32(reader)?;
+        let compression = (bits >> 23) & 0x3f;
+        let mut read_var = |is_u32: bool| {
+            if is_u32 {
+                read_u32(reader).map(u64::from)
+            } else {
+                read_u64(reader)
+            }
+        };
+        if bits & 0x3f == 0x3f {
+            read_var(true)?; // compression block size which does not fit into the bits
+        }
+        let _offset = read_var(bits & (1 << 31) != 0)?;
+        let uncompressed_size = read_var(bits & (1 << 30) != 0)?;
+        let compressed_size = if compression == 0 {
+            uncompressed_size
+        } else {
+            read_var(bits & (1 << 29) != 0)?
+        };
+        Ok(PakEntry {
+            path,
+            uncompressed_size,
+            compressed_size,
+            compression: self.compression_name(compression)?,
+        })
+    }
+
+    fn read_index<R: Read + Seek>(&self, reader: &mut R) -> io::Result<Vec<PakEntry>> {
+        reader.seek(SeekFrom::Start(self.index_offset))?;
+        let index = read_bytes(reader, self.index_size)?;
+        let mut index = index.as_slice();
+
+        let _mount_point = read_string(&mut index)?;
+        let count = read_u32(&mut index)?;
+        if self.version < PAK_VERSION_PATH_HASH_INDEX {
+            return (0..count)
+                .map(|_| {
+                    let path = read_string(&mut index)?;
+                    self.read_entry(&mut index, path)
+                })
+                .collect();
+        }
+
+        skip(&mut index, 8)?; // path hash seed
+        if read_u32(&mut index)? != 0 {
+            skip(&mut index, 8 + 8 + 20)?; // path hash index
+        }
+        if read_u32(&mut index)? == 0 {
+            return Err(invalid_data("pak has no full directory index"));
+        }
+        let directory_offset = read_u64(&mut index)?;
+        let directory_size = read_u64(&mut index)?;
+        skip(&mut index, 20)?;
+        let encoded_size = read_u32(&mut index)?;
+        let encoded = read_bytes(&mut index, u64::from(encoded_size))?;
+        let unencoded_count = read_u32(&mut index)?;
+        let unencoded = (0..unencoded_count)
+            .map(|_| self.read_entry(&mut index, String::new()))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        reader.seek(SeekFrom::Start(directory_offset))?;
+        let directories = read_bytes(reader, directory_size)?;
+        let mut directories = directories.as_slice();
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..read_u32(&mut directories)? {
+            let directory = read_string(&mut directories)?;
+            for _ in 0..read_u32(&mut directories)? {
+                let path = format!("{directory}{}", read_string(&mut directories)?)
+                    .trim_start_matches('/')
+                    .to_string();
+                let location = read_u32(&mut directories)? as i32;
+                let entry = if location < 0 {
+                    // unencoded entries are referred to by their index, negated and minus one
+                    let mut entry = unencoded
+                        .get((-(location + 1)) as usize)
+                        .ok_or_else(|| invalid_data("pak entry location out of range"))?
+                        .clone();
+                    entry.path = path;
+                    entry
+                } else {
+                    let encoded = encoded
+                        .get(location as usize..)
+                        .ok_or_else(|| invalid_data("pak entry location out of range"))?;
+                    self.read_encoded_entry(&mut &encoded[..], path)?
+                };
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Paths of every entry of a pak, sorted. Only the index is read.
+pub fn list_pak_entry_names(pak_path: &Path) -> Result<Vec<String>, repak::Error> {
+    let mut reader = BufReader::new(fs::File::open(pak_path)?);
+    let mut names = repak::PakBuilder::new().reader(&mut reader)?.files();
+    names.sort();
+    Ok(names)
+}
+
+/// Extracts the entries of a pak into `output_dir`, keeping their directory structure. If
+/// `glob_filter` is set only entries whose path matches it are extracted. Returns the paths of the
+/// extracted files.
+pub fn extract_pak(
+    pak_path: &Path,
+    output_dir: &Path,
+    glob_filter: Option<&str>,
+) -> Result<Vec<PathBuf>, repak::Error> {
+    let matcher = glob_filter
+        .map(|glob| {
+            globset::Glob::new(glob)
+                .map(|g| g.compile_matcher())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+        })
+        .transpose()?;
+
+    let mut reader = BufReader::new(fs::File::open(pak_path)?);
+    let pak = repak::PakBuilder::new().reader(&mut reader)?;
+
+    let mut extracted = vec![];
+    for entry in pak.files() {
+        if matcher.as_ref().is_some_and(|m| !m.is_match(&entry)) {
+            continue;
+        }
+        let relative = Path::new(&entry);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("pak entry {entry:?} escapes the output directory"),
+            )
+            .into());
+        }
+        let path = output_dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap())?;
+        let mut writer = BufWriter::new(fs::File::create(&path)?);
+        pak.read_file(&entry, &mut reader, &mut writer)?;
+        writer.flush()?;
+        extracted.push(path);
+    }
+    Ok(extracted)
+}
+
+/// Which file is kept when several paks passed to `merge_paks` contain the same path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Keep the file of the pak passed last. This matches the game, which lets paks loaded later
+    /// override the files of earlier ones.
+    #[default]
+    LastWins,
+    /// Keep the file of the pak passed first.
+    FirstWins,
+}
+
+/// Path contained in more than one of the merged paks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PakConflict {
+    pub path: String,
+    /// Every pak containing the path, in the order they were passed
+    pub paks: Vec<PathBuf>,
+    /// Pak whose file was written to the merged pak
+    pub kept: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Number of files written to the merged pak
+    pub merged_count: usize,
+    pub conflicts: Vec<PakConflict>,
+}
+
+/// Compression of paks written by mint. Level 0 stores files uncompressed, 1 to 9 compress them
+/// with zlib. repak currently compresses with its own zlib level and ignores the chosen one, the
+/// level takes effect once repak exposes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PakCompression {
+    pub level: u8,
+}
+
+impl PakCompression {
+    /// Fastest to write, e.g. for CI builds
+    pub const NONE: Self = Self { level: 0 };
+    pub const DEFAULT: Self = Self { level: 6 };
+    /// Smallest download size
+    pub const MAX: Self = Self { level: 9 };
+
+    /// Compression method to configure the pak writer with
+    fn method(self) -> Option<repak::Compression> {
+        (self.level > 0).then_some(repak::Compression::Zlib)
+    }
+}
+
+impl Default for PakCompression {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Mount point of merged paks, which contain paths relative to the game root
+const MERGED_MOUNT_POINT: &str = "../../../";
+
+/// Path of a pak entry relative to `MERGED_MOUNT_POINT`.
+fn merged_path(mount_point: &str, entry: &str) -> String {
+    let path = Path::new(mount_point)
+        .join(entry)
+        .to_string_lossy()
+        .replace('\\', "/");
+    path.trim_start_matches("../").to_string()
+}
+
+/// Combines the files of `input_paks` into a single pak at `output_pak` so the game has fewer
+/// paks to load. Paths contained in several inputs are resolved according to
+/// `conflict_strategy`. Files are recompressed according to `compression`.
+pub fn merge_paks(
+    input_paks: &[PathBuf],
+    output_pak: &Path,
+    conflict_strategy: ConflictStrategy,
+    compression: PakCompression,
+) -> Result<MergeReport, repak::Error> {
+    let mut inputs = vec![];
+    let mut owners: BTreeMap<String, Vec<(usize, String)>> = BTreeMap::new();
+    for (i, path) in input_paks.iter().enumerate() {
+        let mut reader = BufReader::new(fs::File::open(path)?);
+        let pak = repak::PakBuilder::new().reader(&mut reader)?;
+        for entry in pak.files() {
+            owners
+                .entry(merged_path(pak.mount_point(), &entry))
+                .or_default()
+                .push((i, entry));
+        }
+        inputs.push((pak, reader));
+    }
+
+    let mut writer = repak::PakBuilder::new()
+        .compression(compression.method())
+        .writer(
+            BufWriter::new(fs::File::create(output_pak)?),
+            repak::Version::V11,
+            MERGED_MOUNT_POINT.to_string(),
+            None,
+        );
+    let mut report = MergeReport::default();
+    for (path, owners) in owners {
+        let (kept, entry) = match conflict_strategy {
+            ConflictStrategy::LastWins => owners.last(),
+            ConflictStrategy::FirstWins => owners.first(),
+        }
+        .expect("every path has an owner");
+        let (pak, reader) = &mut inputs[*kept];
+        writer.write_file(&path, pak.get(entry, reader)?)?;
+        report.merged_count += 1;
+
+        if owners.len() > 1 {
+            report.conflicts.push(PakConflict {
+                paks: owners.iter().map(|(i, _)| input_paks[*i].clone()).collect(),
+                kept: input_paks[*kept].clone(),
+                path,
+            });
+        }
+    }
+    writer.write_index()?;
+    Ok(report)
+}
+
+/// Outcome of `verify_pak_signature`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureResult {
+    Valid,
+    /// The signature does not match the pak or the key
+    Invalid,
+    /// No signature file exists next to the pak
+    Unsigned,
+}
+
+#[derive(Debug, Snafu)]
+pub enum PakSignatureError {
+    #[snafu(display("invalid ed25519 public key: {source}"))]
+    InvalidPublicKey {
+        source: ed25519_dalek::pkcs8::spki::Error,
+    },
+    #[snafu(transparent)]
+    SignatureIoError { source: io::Error },
+}
+
+/// Signature file of a pak, `Mod.pak.sig` for `Mod.pak`
+pub fn signature_path(pak_path: &Path) -> PathBuf {
+    let mut path = pak_path.as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Verifies the ed25519 signature of a pak against a PEM encoded public key. repak does not
+/// support pak signatures, so like the game's own `.sig` files the raw 64 byte signature of the
+/// whole pak is stored next to it, see `signature_path`. Paks are signed with Ed25519ph over
+/// their SHA-512 so they are hashed while being read instead of being loaded into memory.
+pub fn verify_pak_signature(
+    pak_path: &Path,
+    public_key_pem: &[u8],
+) -> Result<SignatureResult, PakSignatureError> {
+    let key = VerifyingKey::from_public_key_pem(&String::from_utf8_lossy(public_key_pem))
+        .context(InvalidPublicKeySnafu)?;
+
+    let signature = match fs::read(signature_path(pak_path)) {
+        Ok(signature) => signature,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(SignatureResult::Unsigned),
+        Err(e) => return Err(e.into()),
+    };
+    let Ok(signature) = <[u8; 64]>::try_from(signature.as_slice()) else {
+        return Ok(SignatureResult::Invalid);
+    };
+
+    let mut hasher = Sha512::new();
+    io::copy(&mut fs::File::open(pak_path)?, &mut hasher)?;
+    Ok(
+        if key
+            .verify_prehashed(hasher, None, &Signature::from_bytes(&signature))
+            .is_ok()
+        {
+            SignatureResult::Valid
+        } else {
+            SignatureResult::Invalid
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use ed25519_dalek::pkcs8::EncodePublicKey;
+    use ed25519_dalek::SigningKey;
+
+    use super::*;
+
+    fn lint_pak(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_assets/lints")
+            .join(name)
+    }
+
+    #[test]
+    fn test_list_pak_contents_reads_sizes_from_index() {
+        let entries = list_pak_contents(&lint_pak("A.pak")).unwrap();
+        let listed = entries
+            .iter()
+            .map(|e| (e.path.as_str(), e.uncompressed_size, e.compressed_size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            listed,
+            [
+                ("FSD/Content/A.uexp", 2, 2),
+                ("FSD/Content/AssetRegistry.bin", 0, 0),
+                ("FSD/Content/B.uexp", 0, 0),
+                ("FSD/Content/C.ushaderbytecode", 0, 0),
+            ]
+        );
+        assert!(entries.iter().all(|e| e.compression.is_none()));
+    }
+
+    #[test]
+    fn test_list_pak_contents_matches_extracted_files() {
+        for name in ["B", "non_asset_files", "outdated_pak_version", "reference"] {
+            let entries = list_pak_contents(&lint_pak(&format!("{name}.pak"))).unwrap();
+            assert!(!entries.is_empty(), "{name}.pak has no entries");
+            for entry in entries {
+                let file = lint_pak(name).join(&entry.path);
+                assert_eq!(
+                    fs::metadata(&file).unwrap().len(),
+                    entry.uncompressed_size,
+                    "size of {}",
+                    file.display()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_list_pak_contents_rejects_non_pak() {
+        let err = list_pak_contents(&lint_pak("multiple_paks.zip")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// Contents of `A.uexp` in the merged pak, which both `A.pak` and `B.pak` contain
+    fn merge(conflict_strategy: ConflictStrategy) -> (MergeReport, Vec<u8>) {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("merged.pak");
+        let report = merge_paks(
+            &[lint_pak("A.pak"), lint_pak("B.pak")],
+            &output,
+            conflict_strategy,
+            PakCompression::NONE,
+        )
+        .unwrap();
+
+        let mut reader = BufReader::new(fs::File::open(&output).unwrap());
+        let pak = repak::PakBuilder::new().reader(&mut reader).unwrap();
+        let contents = pak.get("FSD/Content/A.uexp", &mut reader).unwrap();
+        (report, contents)
+    }
+
+    #[test]
+    fn test_merge_paks() {
+        let (report, contents) = merge(ConflictStrategy::LastWins);
+        assert_eq!(report.merged_count, 5);
+        assert_eq!(
+            report.conflicts,
+            [PakConflict {
+                path: "FSD/Content/A.uexp".to_string(),
+                paks: vec![lint_pak("A.pak"), lint_pak("B.pak")],
+                kept: lint_pak("B.pak"),
+            }]
+        );
+        assert_eq!(contents, b"b\n");
+
+        let (report, contents) = merge(ConflictStrategy::FirstWins);
+        assert_eq!(report.conflicts[0].kept, lint_pak("A.pak"));
+        assert_eq!(contents, b"a\n");
+    }
+
+    #[test]
+    fn test_pak_compression_method() {
+        assert!(PakCompression::NONE.method().is_none());
+        for level in [1, PakCompression::DEFAULT.level, PakCompression::MAX.level] {
+            assert!(matches!(
+                PakCompression { level }.method(),
+                Some(repak::Compression::Zlib)
+            ));
+        }
+    }
+
+    /// PEM public key and the private key signing with it, the same on every run
+    fn signing_key() -> (SigningKey, Vec<u8>) {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let pem = key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        (key, pem.into_bytes())
+    }
+
+    fn sign(key: &SigningKey, pak: &Path) {
+        let signature = key
+            .sign_prehashed(Sha512::new_with_prefix(fs::read(pak).unwrap()), None)
+            .unwrap();
+        fs::write(signature_path(pak), signature.to_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_verify_pak_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak = dir.path().join("A.pak");
+        fs::copy(lint_pak("A.pak"), &pak).unwrap();
+        let (key, pem) = signing_key();
+
+        assert_eq!(
+            verify_pak_signature(&pak, &pem).unwrap(),
+            SignatureResult::Unsigned
+        );
+
+        sign(&key, &pak);
+        assert_eq!(
+            verify_pak_signature(&pak, &pem).unwrap(),
+            SignatureResult::Valid
+        );
+        let other_pem = SigningKey::from_bytes(&[8; 32])
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        assert_eq!(
+            verify_pak_signature(&pak, other_pem.as_bytes()).unwrap(),
+            SignatureResult::Invalid
+        );
+
+        // tampered pak
+        let mut tampered = fs::read(&pak).unwrap();
+        tampered[0] ^= 1;
+        fs::write(&pak, tampered).unwrap();
+        assert_eq!(
+            verify_pak_signature(&pak, &pem).unwrap(),
+            SignatureResult::Invalid
+        );
+
+        // malformed signatures
+        for signature in [&b""[..], b"garbage", &[0; 64]] {
+            fs::write(signature_path(&pak), signature).unwrap();
+            assert_eq!(
+                verify_pak_signature(&pak, &pem).unwrap(),
+                SignatureResult::Invalid
+            );
+        }
+
+        assert!(verify_pak_signature(&pak, b"not a key").is_err());
+    }
+}
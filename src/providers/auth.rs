@@ -0,0 +1,92 @@
+//! Credentials sent by providers and their storage in the OS keychain.
+
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::providers::*;
+
+/// Service of the keychain entries holding provider credentials, the entry user is the provider id
+const KEYRING_SERVICE: &str = "mint";
+
+/// How a provider authenticates its requests.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthConfig {
+    /// Sent in the `apikey` header, as expected by Nexus Mods
+    ApiKey(String),
+    Bearer(String),
+    BasicAuth {
+        username: String,
+        password: String,
+    },
+    #[default]
+    None,
+}
+
+/// Credentials are redacted so they do not end up in logs
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthConfig::ApiKey(_) => write!(f, "ApiKey(..)"),
+            AuthConfig::Bearer(_) => write!(f, "Bearer(..)"),
+            AuthConfig::BasicAuth { username, .. } => f
+                .debug_struct("BasicAuth")
+                .field("username", username)
+                .finish_non_exhaustive(),
+            AuthConfig::None => write!(f, "None"),
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn authenticate(&self, request_builder: RequestBuilder) -> RequestBuilder {
+        match self {
+            AuthConfig::ApiKey(key) => request_builder.header("apikey", key),
+            AuthConfig::Bearer(token) => request_builder.bearer_auth(token),
+            AuthConfig::BasicAuth { username, password } => {
+                request_builder.basic_auth(username, Some(password))
+            }
+            AuthConfig::None => request_builder,
+        }
+    }
+}
+
+fn keyring_entry(provider: &str) -> Result<keyring::Entry, ProviderError> {
+    keyring::Entry::new(KEYRING_SERVICE, provider).context(KeyringSnafu { provider })
+}
+
+/// Provider parameters stored in the OS keychain by `store_credentials`. Empty if none are stored
+/// or the keychain cannot be accessed.
+pub fn load_credentials(provider: &str) -> HashMap<String, String> {
+    let password = keyring_entry(provider).and_then(|entry| match entry.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(source) => Err(ProviderError::KeyringError {
+            source,
+            provider: provider.to_string(),
+        }),
+    });
+    match password {
+        Ok(Some(password)) => serde_json::from_str(&password).unwrap_or_else(|e| {
+            warn!("ignoring malformed credentials of {provider} in the keychain: {e}");
+            Default::default()
+        }),
+        Ok(None) => Default::default(),
+        Err(e) => {
+            warn!("{e}");
+            Default::default()
+        }
+    }
+}
+
+/// Stores provider parameters in the OS keychain instead of the config file, which is readable by
+/// every user of the machine.
+pub fn store_credentials(
+    provider: &str,
+    parameters: &HashMap<String, String>,
+) -> Result<(), ProviderError> {
+    let password = serde_json::to_string(parameters).expect("string map is serializable");
+    keyring_entry(provider)?
+        .set_password(&password)
+        .context(KeyringSnafu { provider })
+}
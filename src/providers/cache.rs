@@ -1,12 +1,15 @@
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
 
 use fs_err as fs;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
 
+use super::{ModInfo, ModSpecification};
 use crate::state::config::ConfigWrapper;
 
 pub type ProviderCache = Arc<RwLock<ConfigWrapper<VersionAnnotatedCache>>>;
@@ -193,16 +196,122 @@ pub struct BlobCacheError {
     kind: &'static str,
 }
 
+/// Downloaded files indexed by the SHA-256 of their contents so a file whose hash is known in
+/// advance is never downloaded twice. The index is persisted to `index.jsonl` in `dir`, one
+/// `[hash, path]` entry per line. New entries are appended and later lines replace earlier ones,
+/// the file is only rewritten when entries are removed.
+#[derive(Debug)]
+pub struct DownloadCache {
+    dir: PathBuf,
+    index: HashMap<[u8; 32], PathBuf>,
+}
+
+impl DownloadCache {
+    pub fn load<P: AsRef<Path>>(dir: P) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+        let index = fs::read_to_string(Self::index_path(&dir))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<(String, PathBuf)>(line).ok())
+            .filter_map(|(hash, path)| Some((hex::decode(hash).ok()?.try_into().ok()?, path)))
+            .collect();
+        Self { dir, index }
+    }
+
+    fn index_path(dir: &Path) -> PathBuf {
+        dir.join("index.jsonl")
+    }
+
+    fn entry_line(hash: &[u8; 32], path: &Path) -> String {
+        let mut line = serde_json::json!([hex::encode(hash), path]).to_string();
+        line.push('\n');
+        line
+    }
+
+    /// Rewrites the index with the current entries
+    fn save(&self) -> Result<(), BlobCacheError> {
+        let buf = self
+            .index
+            .iter()
+            .map(|(hash, path)| Self::entry_line(hash, path))
+            .collect::<String>();
+        fs::write(Self::index_path(&self.dir), buf).context(BlobCacheSnafu { kind: "save" })
+    }
+
+    pub fn get(&self, hash: [u8; 32]) -> Option<&Path> {
+        self.index
+            .get(&hash)
+            .map(PathBuf::as_path)
+            .filter(|p| p.exists())
+    }
+
+    pub fn insert(&mut self, hash: [u8; 32], file: PathBuf) -> Result<(), BlobCacheError> {
+        use std::io::Write;
+
+        let line = Self::entry_line(&hash, &file);
+        self.index.insert(hash, file);
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::index_path(&self.dir))
+            .and_then(|mut index| index.write_all(line.as_bytes()))
+            .context(BlobCacheSnafu { kind: "save" })
+    }
+
+    /// Cached files with their size in bytes
+    pub fn files(&self) -> impl Iterator<Item = (&Path, u64)> {
+        self.index
+            .values()
+            .filter_map(|path| Some((path.as_path(), fs::metadata(path).ok()?.len())))
+    }
+
+    /// Cached files not used for longer than `duration`, with their size in bytes
+    pub fn unused_for(&self, duration: Duration) -> Vec<(PathBuf, u64)> {
+        let now = SystemTime::now();
+        self.files()
+            .filter(|(path, _)| {
+                fs::metadata(path)
+                    .ok()
+                    .as_ref()
+                    .and_then(last_used)
+                    .and_then(|t| now.duration_since(t).ok())
+                    .is_some_and(|age| age > duration)
+            })
+            .map(|(path, size)| (path.to_path_buf(), size))
+            .collect()
+    }
+
+    /// Removes files not used for longer than `duration` and entries of files which no longer
+    /// exist, returning the number of bytes freed.
+    pub fn evict_older_than(&mut self, duration: Duration) -> Result<u64, BlobCacheError> {
+        let mut freed = 0;
+        for (path, size) in self.unused_for(duration) {
+            fs::remove_file(&path).context(BlobCacheSnafu { kind: "evict" })?;
+            freed += size;
+        }
+        self.index.retain(|_, path| path.exists());
+        self.save()?;
+        Ok(freed)
+    }
+}
+
+/// Last time the file was read, or written if the file system does not record access times
+fn last_used(metadata: &std::fs::Metadata) -> Option<SystemTime> {
+    metadata.accessed().or_else(|_| metadata.modified()).ok()
+}
+
 #[derive(Debug, Clone)]
 pub struct BlobCache {
     path: PathBuf,
+    downloads: Arc<Mutex<DownloadCache>>,
 }
 
 impl BlobCache {
-    pub(super) fn new<P: AsRef<Path>>(path: P) -> Self {
+    pub(super) fn new<P: AsRef<Path>>(path: P, downloads: DownloadCache) -> Self {
         fs::create_dir(&path).ok();
         Self {
             path: path.as_ref().to_path_buf(),
+            downloads: Arc::new(Mutex::new(downloads)),
         }
     }
 
@@ -211,11 +320,14 @@ impl BlobCache {
 
         let mut hasher = Sha256::new();
         hasher.update(blob);
-        let hash = hex::encode(hasher.finalize());
+        let digest: [u8; 32] = hasher.finalize().into();
+        let hash = hex::encode(digest);
 
         let tmp = self.path.join(format!(".{hash}"));
         fs::write(&tmp, blob).context(BlobCacheSnafu { kind: "write" })?;
-        fs::rename(tmp, self.path.join(&hash)).context(BlobCacheSnafu { kind: "rename" })?;
+        let path = self.path.join(&hash);
+        fs::rename(tmp, &path).context(BlobCacheSnafu { kind: "rename" })?;
+        self.downloads.lock().unwrap().insert(digest, path)?;
 
         Ok(BlobRef(hash))
     }
@@ -224,4 +336,136 @@ impl BlobCache {
         let path = self.path.join(&blob.0);
         path.exists().then_some(path)
     }
+
+    /// Blob with the given SHA-256 if it has already been downloaded.
+    pub(super) fn get_by_hash(&self, hash: [u8; 32]) -> Option<BlobRef> {
+        self.downloads
+            .lock()
+            .unwrap()
+            .get(hash)
+            .map(|_| BlobRef(hex::encode(hash)))
+    }
+
+    pub fn downloads(&self) -> &Mutex<DownloadCache> {
+        &self.downloads
+    }
+}
+
+/// How long a resolved mod is reused before its provider is queried again
+pub const DEFAULT_RESOLUTION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Resolved mods reused for `ttl` instead of querying the provider again. Entries are timestamped
+/// with `SystemTime` rather than `Instant` so they stay valid across runs. Other provider
+/// responses such as search results can be cached by choosing another key and value.
+#[derive(Debug)]
+pub struct ResolutionCache<K = ModSpecification, V = ModInfo> {
+    path: PathBuf,
+    entries: HashMap<K, (V, SystemTime)>,
+    ttl: Duration,
+}
+
+impl<K, V> ResolutionCache<K, V>
+where
+    K: Eq + std::hash::Hash + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    pub fn load<P: AsRef<Path>>(path: P, ttl: Duration) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|buf| serde_json::from_slice::<Vec<(K, V, SystemTime)>>(&buf).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(spec, info, time)| (spec, (info, time)))
+            .collect();
+        Self { path, entries, ttl }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        // specifications are not strings so the map is stored as a list of entries
+        let entries = self
+            .entries
+            .iter()
+            .map(|(spec, (info, time))| (spec, info, time))
+            .collect::<Vec<_>>();
+        fs::write(&self.path, serde_json::to_vec(&entries)?)
+    }
+
+    /// Resolved mod if it was cached less than `ttl` ago.
+    pub fn get(&self, spec: &K) -> Option<&V> {
+        let (info, time) = self.entries.get(spec)?;
+        let age = SystemTime::now().duration_since(*time).ok()?;
+        (age < self.ttl).then_some(info)
+    }
+
+    /// Resolved mod regardless of how long ago it was cached.
+    pub fn get_ignoring_ttl(&self, spec: &K) -> Option<&V> {
+        self.entries.get(spec).map(|(info, _)| info)
+    }
+
+    /// When the mod was cached, regardless of the `ttl`
+    pub fn cached_at(&self, spec: &K) -> Option<SystemTime> {
+        self.entries.get(spec).map(|(_, time)| *time)
+    }
+
+    /// When the most recent entry matching `f` was cached
+    pub fn last_cached_where(&self, f: impl Fn(&V) -> bool) -> Option<SystemTime> {
+        self.entries
+            .values()
+            .filter(|(info, _)| f(info))
+            .map(|(_, time)| *time)
+            .max()
+    }
+
+    /// Every cached mod including expired ones, usable for searching offline.
+    pub fn mods(&self) -> impl Iterator<Item = &V> {
+        self.entries.values().map(|(info, _)| info)
+    }
+
+    pub fn insert(&mut self, spec: K, info: V) -> std::io::Result<()> {
+        self.entries.insert(spec, (info, SystemTime::now()));
+        self.save()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of entries cached longer than `duration` ago
+    pub fn count_older_than(&self, duration: Duration) -> usize {
+        let now = SystemTime::now();
+        self.entries
+            .values()
+            .filter(|(_, time)| now.duration_since(*time).is_ok_and(|age| age > duration))
+            .count()
+    }
+
+    /// Removes entries cached longer than `duration` ago, returning how many were removed
+    pub fn evict_older_than(&mut self, duration: Duration) -> std::io::Result<usize> {
+        let now = SystemTime::now();
+        let len = self.entries.len();
+        self.entries
+            .retain(|_, (_, time)| !now.duration_since(*time).is_ok_and(|age| age > duration));
+        let evicted = len - self.entries.len();
+        if evicted > 0 {
+            self.save()?;
+        }
+        Ok(evicted)
+    }
+
+    pub fn invalidate(&mut self, spec: &K) -> std::io::Result<()> {
+        if self.entries.remove(spec).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn invalidate_all(&mut self) -> std::io::Result<()> {
+        self.entries.clear();
+        self.save()
+    }
 }
@@ -0,0 +1,111 @@
+//! Downloading mod updates as binary patches of the previously downloaded version.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use fs_err as fs;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info};
+
+use crate::providers::*;
+
+static DELTA_DOWNLOADS: AtomicBool = AtomicBool::new(true);
+
+/// Enables or disables delta downloads for every provider, see `Config::enable_delta_downloads`
+pub fn configure_delta_downloads(enabled: bool) {
+    DELTA_DOWNLOADS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn delta_downloads_enabled() -> bool {
+    DELTA_DOWNLOADS.load(Ordering::Relaxed)
+}
+
+/// Fetches bsdiff patches servers publish next to a mod at `<base_url>.delta/<old_sha256>`,
+/// where `old_sha256` is the hex SHA-256 of the version being updated from. Patched files must
+/// match the SHA-256 published at `<base_url>.sha256`, servers without one are not asked for
+/// patches.
+#[derive(Debug, Clone)]
+pub struct DeltaDownloader {
+    client: reqwest::Client,
+}
+
+impl DeltaDownloader {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    pub fn delta_url(base_url: &str, old_sha256: [u8; 32]) -> String {
+        format!("{base_url}.delta/{}", hex::encode(old_sha256))
+    }
+
+    pub fn checksum_url(base_url: &str) -> String {
+        format!("{base_url}.sha256")
+    }
+
+    /// GETs `url`, `None` if the server responds with `404`
+    async fn get(&self, url: &str, auth: &AuthConfig) -> Result<Option<Vec<u8>>, ProviderError> {
+        let response = auth
+            .authenticate(self.client.get(url))
+            .send()
+            .await
+            .context(RequestFailedSnafu { url })?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(
+            response
+                .error_for_status()
+                .context(ResponseSnafu { url })?
+                .bytes()
+                .await
+                .context(FetchSnafu { url })?
+                .to_vec(),
+        ))
+    }
+
+    /// The file at `base_url` built by patching `old`, `None` if the server has no patch from
+    /// `old` and it has to be downloaded in full.
+    pub async fn fetch(
+        &self,
+        base_url: &str,
+        old: &Path,
+        auth: &AuthConfig,
+    ) -> Result<Option<Vec<u8>>, ProviderError> {
+        let checksum_url = Self::checksum_url(base_url);
+        let Some(checksum) = self.get(&checksum_url, auth).await? else {
+            debug!("no checksum at <{checksum_url}>, not looking for a delta patch");
+            return Ok(None);
+        };
+        let expected = std::str::from_utf8(&checksum)
+            .ok()
+            .and_then(mint_lib::update::parse_checksum)
+            .context(InvalidChecksumSnafu { url: &checksum_url })?;
+
+        let old = fs::read(old).context(BufferIoSnafu { url: base_url })?;
+        let url = Self::delta_url(base_url, Sha256::digest(&old).into());
+        let Some(patch) = self.get(&url, auth).await? else {
+            debug!("no delta patch at <{url}>");
+            return Ok(None);
+        };
+
+        let mut new = vec![];
+        bsdiff::patch(&old, &mut patch.as_slice(), &mut new)
+            .context(BufferIoSnafu { url: &url })?;
+        let found = hex::encode(Sha256::digest(&new));
+        ensure!(
+            found == expected,
+            ChecksumMismatchSnafu {
+                url: &url,
+                expected,
+                found
+            }
+        );
+        let saved = new.len().saturating_sub(patch.len());
+        info!(
+            "delta patch saved {:.1} MB ({:.0}% of full download)",
+            saved as f64 / 1_000_000.0,
+            saved as f64 * 100.0 / new.len().max(1) as f64
+        );
+        Ok(Some(new))
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+
+use reqwest::header::{ETAG, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: DIRECT_URL_PROVIDER_ID,
+        new: DirectUrlProvider::new_provider,
+        can_provide: |url| url.starts_with("https://"),
+        schemes: &["https"],
+        parameters: &[],
+    }
+}
+
+/// Checked after all other providers since it accepts any `https://` URL
+pub(super) const DIRECT_URL_PROVIDER_ID: &str = "direct";
+
+/// Magic found in the footer of every pak
+const PAK_MAGIC: [u8; 4] = 0x5A6F12E1u32.to_le_bytes();
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirectUrlProviderCache {
+    downloads: HashMap<String, CachedDownload>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for DirectUrlProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedDownload {
+    /// `ETag` or `Last-Modified` of the download, never reused if neither was present
+    validator: Option<String>,
+    blob: BlobRef,
+    mod_type: ModType,
+}
+
+fn validator(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get(ETAG)
+        .or_else(|| headers.get(LAST_MODIFIED))
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// Infers the mod type from the pak footer magic or the contents of a zip
+fn sniff_mod_type(path: &Path) -> Option<ModType> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut head = [0; 4];
+    file.read_exact(&mut head).ok()?;
+    if &head[..2] == b"PK" {
+        let archive = zip::ZipArchive::new(&mut file).ok()?;
+        let has = |ext: &str| {
+            archive
+                .file_names()
+                .any(|n| n.to_lowercase().ends_with(ext))
+        };
+        return Some(if !has(".uplugin") && has(".pak") {
+            ModType::Pak
+        } else {
+            ModType::ModPlugin
+        });
+    }
+
+    let len = file.seek(SeekFrom::End(0)).ok()?;
+    let footer_len = len.min(256);
+    file.seek(SeekFrom::Start(len - footer_len)).ok()?;
+    let mut footer = vec![0; footer_len as usize];
+    file.read_exact(&mut footer).ok()?;
+    footer
+        .windows(PAK_MAGIC.len())
+        .any(|w| w == PAK_MAGIC)
+        .then_some(ModType::Pak)
+}
+
+fn url_name(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.path_segments()
+                .and_then(|mut s| s.next_back())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+#[derive(Debug)]
+pub struct DirectUrlProvider {
+    client: reqwest::Client,
+    auth: AuthConfig,
+    limiter: Arc<RateLimiter>,
+    /// URLs downloaded by this provider, reused when the mod is fetched after being resolved
+    downloaded: Mutex<HashSet<String>>,
+}
+
+impl Default for DirectUrlProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirectUrlProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {
+            client: http_client(),
+            auth: AuthConfig::None,
+            limiter: rate_limiter(DIRECT_URL_PROVIDER_ID),
+            downloaded: Default::default(),
+        }
+    }
+
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// The cached download if it was downloaded by this provider or is still valid according to
+    /// `validator`
+    fn cached_download(
+        &self,
+        url: &str,
+        validator: Option<&str>,
+        cache: &ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<(PathBuf, ModType)> {
+        let lock = cache.read().unwrap();
+        let cached = lock
+            .get::<DirectUrlProviderCache>(DIRECT_URL_PROVIDER_ID)?
+            .downloads
+            .get(url)?;
+        let valid = self.downloaded.lock().unwrap().contains(url)
+            || cached.validator.is_some() && cached.validator.as_deref() == validator;
+        valid
+            .then(|| blob_cache.get_path(&cached.blob))
+            .flatten()
+            .map(|path| (path, cached.mod_type.clone()))
+    }
+
+    fn insert_download(
+        &self,
+        url: &str,
+        validator: Option<String>,
+        blob: BlobRef,
+        mod_type: ModType,
+        cache: &ProviderCache,
+    ) {
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<DirectUrlProviderCache>(DIRECT_URL_PROVIDER_ID)
+            .downloads
+            .insert(
+                url.to_owned(),
+                CachedDownload {
+                    validator,
+                    blob,
+                    mod_type,
+                },
+            );
+        self.downloaded.lock().unwrap().insert(url.to_owned());
+    }
+
+    /// Adopts the blob `HttpProvider` downloaded the URL to before it handled only `http://` URLs,
+    /// trusting it to be current like `HttpProvider` did.
+    fn migrate_http_download(
+        &self,
+        url: &str,
+        validator: Option<String>,
+        cache: &ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<(PathBuf, ModType)> {
+        let blob = super::http::take_url_blob(cache, url)?;
+        let path = blob_cache.get_path(&blob)?;
+        let mod_type = sniff_mod_type(&path)?;
+        self.insert_download(url, validator, blob, mod_type.clone(), cache);
+        Some((path, mod_type))
+    }
+
+    /// Downloads the URL into the blob cache unless the cached download is still valid
+    async fn download(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: &ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<&Sender<FetchProgress>>,
+    ) -> Result<(PathBuf, ModType), ProviderError> {
+        use futures::stream::TryStreamExt;
+
+        let url = &res.url.0;
+        // already downloaded while resolving the mod
+        if let Some(cached) = self.cached_download(url, None, cache, blob_cache) {
+            return Ok(cached);
+        }
+        if !update {
+            self.limiter.acquire().await;
+            let head = self
+                .auth
+                .authenticate(self.client.head(url))
+                .send()
+                .await
+                .context(RequestFailedSnafu { url })?;
+            let validator = validator(head.headers());
+            if let Some(cached) = self.cached_download(url, validator.as_deref(), cache, blob_cache)
+            {
+                return Ok(cached);
+            }
+            if let Some(migrated) = self.migrate_http_download(url, validator, cache, blob_cache) {
+                return Ok(migrated);
+            }
+        }
+
+        info!("downloading mod {url:?}...");
+        self.limiter.acquire().await;
+        let response = self
+            .auth
+            .authenticate(self.client.get(url))
+            .send()
+            .await
+            .context(RequestFailedSnafu { url })?
+            .error_for_status()
+            .context(ResponseSnafu { url })?;
+        let validator = validator(response.headers());
+        let size = response.content_length();
+
+        let mut data = vec![];
+        let mut stream = response.bytes_stream();
+        while let Some(bytes) = stream
+            .try_next()
+            .await
+            .with_context(|_| FetchSnafu { url })?
+        {
+            data.extend(&bytes);
+            if let Some(size) = size
+                && let Some(tx) = tx
+            {
+                tx.send(FetchProgress::Progress {
+                    resolution: res.clone(),
+                    progress: data.len() as u64,
+                    size,
+                })
+                .await
+                .unwrap();
+            }
+        }
+
+        let blob = blob_cache.write(&data)?;
+        let path = blob_cache.get_path(&blob).unwrap();
+        let mod_type = sniff_mod_type(&path).context(UnrecognizedFormatSnafu { url })?;
+        self.insert_download(url, validator, blob, mod_type.clone(), cache);
+        Ok((path, mod_type))
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for DirectUrlProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Result<ModResponse, ProviderError> {
+        let resolution = ModResolution::resolvable(spec.url.as_str().into());
+        let (_, mod_type) = self
+            .download(&resolution, update, &cache, blob_cache, None)
+            .await?;
+        Ok(ModResponse::Resolve(Box::new(
+            ModInfo::builder()
+                .provider(DIRECT_URL_PROVIDER_ID)
+                .name(url_name(&spec.url))
+                .spec(spec.clone())
+                .resolution(resolution)
+                .mod_type(mod_type)
+                .build()
+                .expect("required fields are set"),
+        )))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let (path, _) = self
+            .download(res, update, &cache, blob_cache, tx.as_ref())
+            .await?;
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        let mod_type = cache
+            .read()
+            .unwrap()
+            .get::<DirectUrlProviderCache>(DIRECT_URL_PROVIDER_ID)?
+            .downloads
+            .get(&spec.url)?
+            .mod_type
+            .clone();
+        Some(
+            ModInfo::builder()
+                .provider(DIRECT_URL_PROVIDER_ID)
+                .name(url_name(&spec.url))
+                .spec(spec.clone())
+                .resolution(ModResolution::resolvable(spec.url.as_str().into()))
+                .mod_type(mod_type)
+                .build()
+                .expect("required fields are set"),
+        )
+    }
+
+    fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        true
+    }
+
+    fn get_version_name(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
+        Some("latest".to_string())
+    }
+}
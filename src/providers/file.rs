@@ -14,6 +14,7 @@ inventory::submit! {
         id: FILE_PROVIDER_ID,
         new: FileProvider::new_provider,
         can_provide: |url| Path::new(url).exists(),
+        schemes: &[],
         parameters: &[],
     }
 }
@@ -42,35 +43,41 @@ impl ModProvider for FileProvider {
         spec: &ModSpecification,
         _update: bool,
         _cache: ProviderCache,
+        _blob_cache: &BlobCache,
     ) -> Result<ModResponse, ProviderError> {
         let path = Path::new(&spec.url);
         let name = path
             .file_name()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_else(|| spec.url.to_string());
-        Ok(ModResponse::Resolve(ModInfo {
-            provider: FILE_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::unresolvable(
-                spec.url.clone().into(),
-                path.file_name()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-            ),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            mod_type: match path.join(path
-                .file_name()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_else(|| spec.url.to_string())
-                + ".uplugin").exists()
-            {
-                true => ModPlugin,
-                false => Pak
-            }
-        }))
+        Ok(ModResponse::Resolve(Box::new(
+            ModInfo::builder()
+                .provider(FILE_PROVIDER_ID)
+                .name(name)
+                .spec(spec.clone())
+                .resolution(ModResolution::unresolvable(
+                    spec.url.clone().into(),
+                    path.file_name()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ))
+                .mod_type(
+                    match path
+                        .join(
+                            path.file_name()
+                                .map(|s| s.to_string_lossy().to_string())
+                                .unwrap_or_else(|| spec.url.to_string())
+                                + ".uplugin",
+                        )
+                        .exists()
+                    {
+                        true => ModPlugin,
+                        false => Pak,
+                    },
+                )
+                .build()
+                .expect("required fields are set"),
+        )))
     }
 
     async fn fetch_mod(
@@ -105,29 +112,34 @@ impl ModProvider for FileProvider {
             .file_name()
             .map(|s| s.to_string_lossy().to_string())
             .unwrap_or_else(|| spec.url.to_string());
-        Some(ModInfo {
-            provider: FILE_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::unresolvable(
-                spec.url.clone().into(),
-                path.file_name()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-            ),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            mod_type: match path.join(path
-                .file_name()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_else(|| spec.url.to_string())
-                + ".uplugin").exists()
-            {
-                true => ModPlugin,
-                false => Pak
-            }
-        })
+        Some(
+            ModInfo::builder()
+                .provider(FILE_PROVIDER_ID)
+                .name(name)
+                .spec(spec.clone())
+                .resolution(ModResolution::unresolvable(
+                    spec.url.clone().into(),
+                    path.file_name()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ))
+                .mod_type(
+                    match path
+                        .join(
+                            path.file_name()
+                                .map(|s| s.to_string_lossy().to_string())
+                                .unwrap_or_else(|| spec.url.to_string())
+                                + ".uplugin",
+                        )
+                        .exists()
+                    {
+                        true => ModPlugin,
+                        false => Pak,
+                    },
+                )
+                .build()
+                .expect("required fields are set"),
+        )
     }
 
     fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
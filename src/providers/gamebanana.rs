@@ -0,0 +1,452 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: GAMEBANANA_PROVIDER_ID,
+        new: GameBananaProvider::new_provider,
+        can_provide: |url| re_mod().is_match(url),
+        schemes: &["gamebanana"],
+        parameters: &[],
+    }
+}
+
+const GAMEBANANA_PROVIDER_ID: &str = "gamebanana";
+const GAMEBANANA_API_URL: &str = "https://api.gamebanana.com/Core/Item/Data";
+const GAMEBANANA_SEARCH_URL: &str = "https://gamebanana.com/apiv11/Util/Search/Results";
+
+/// GameBanana ID of Dragon Ball: Sparking! ZERO, searches are limited to its mods
+const GAMEBANANA_GAME_ID: u32 = 20357;
+
+/// Items resolved per search, each requires fetching its files
+const SEARCH_LIMIT: usize = 10;
+
+/// Requested item fields, the API responds with an array in the same order
+const ITEM_FIELDS: &str = "name,Owner().name,description,catid,Files().aFiles()";
+
+/// Mod category IDs of the Dragon Ball: Sparking! ZERO section, other categories are `Other`
+static CATEGORIES: &[(u32, ModCategory)] = &[
+    (38915, ModCategory::Cosmetic), // Skins
+    (38916, ModCategory::Cosmetic), // Characters
+    (38917, ModCategory::Cosmetic), // Stages
+    (38918, ModCategory::Audio),    // Sounds
+    (38919, ModCategory::Audio),    // Music
+    (38920, ModCategory::UI),       // UI
+    (38921, ModCategory::Gameplay), // Gameplay
+    (38922, ModCategory::Gameplay), // Movesets
+    (38923, ModCategory::Utility),  // Tools
+    (38924, ModCategory::Debug),    // Debug
+];
+
+fn lookup_category(id: u32) -> ModCategory {
+    CATEGORIES
+        .iter()
+        .find_map(|(i, c)| (*i == id).then_some(*c))
+        .unwrap_or(ModCategory::Other)
+}
+
+static RE_MOD: OnceLock<regex::Regex> = OnceLock::new();
+fn re_mod() -> &'static regex::Regex {
+    RE_MOD.get_or_init(|| {
+        regex::Regex::new(r"^gamebanana:(?P<item_id>\d+)(/(?P<file_id>\d+))?$").unwrap()
+    })
+}
+
+fn parse_url(url: &str) -> Result<(u32, Option<u32>), ProviderError> {
+    let invalid = || ProviderError::InvalidUrl {
+        url: url.to_string(),
+    };
+    let captures = re_mod().captures(url).ok_or_else(invalid)?;
+    let item_id = captures["item_id"].parse().map_err(|_| invalid())?;
+    let file_id = captures
+        .name("file_id")
+        .map(|f| f.as_str().parse().map_err(|_| invalid()))
+        .transpose()?;
+    Ok((item_id, file_id))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GameBananaProviderCache {
+    items: HashMap<u32, GameBananaItem>,
+    url_blobs: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for GameBananaProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameBananaItem {
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    /// `None` in items cached before the category was stored
+    #[serde(default)]
+    pub category_id: Option<u32>,
+    /// Newest file first
+    pub files: Vec<GameBananaFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameBananaFile {
+    #[serde(rename = "_idRow")]
+    pub id: u32,
+    #[serde(rename = "_sFile")]
+    pub file_name: String,
+    #[serde(rename = "_sDownloadUrl")]
+    pub download_url: String,
+    #[serde(rename = "_tsDateAdded", default)]
+    pub date_added: u64,
+    #[serde(rename = "_nDownloadCount", default)]
+    pub download_count: u64,
+    /// Hex encoded SHA-256 of the file, missing for files uploaded before GameBanana published
+    /// them
+    #[serde(rename = "_sSha256Checksum", default)]
+    pub sha256: Option<String>,
+}
+
+impl GameBananaFile {
+    fn mod_type(&self) -> ModType {
+        let is_pak = |s: &str| s.to_lowercase().ends_with(".pak");
+        if is_pak(&self.download_url) || is_pak(&self.file_name) {
+            ModType::Pak
+        } else {
+            ModType::ModPlugin
+        }
+    }
+}
+
+impl GameBananaItem {
+    fn file(&self, file_id: Option<u32>) -> Option<&GameBananaFile> {
+        match file_id {
+            Some(id) => self.files.iter().find(|f| f.id == id),
+            None => self.files.first(),
+        }
+    }
+
+    fn to_mod_info(&self, spec: &ModSpecification, item_id: u32, file: &GameBananaFile) -> ModInfo {
+        ModInfo::builder()
+            .provider(GAMEBANANA_PROVIDER_ID)
+            .name(self.name.clone())
+            .spec(spec.clone())
+            .versions(
+                self.files
+                    .iter()
+                    .map(|f| ModSpecification::new(format!("gamebanana:{item_id}/{}", f.id)))
+                    .collect(),
+            )
+            .resolution(ModResolution::resolvable(
+                format!("gamebanana:{item_id}/{}", file.id).into(),
+            ))
+            .mod_type(file.mod_type())
+            .category(self.category_id.map(lookup_category))
+            .authors(vec![Author::new(self.author.clone())])
+            .homepage(format!("https://gamebanana.com/mods/{item_id}"))
+            .description(Some(self.description.clone()).filter(|d| !d.trim().is_empty()))
+            .extra_metadata(serde_json::json!({
+                "download_count": self.files.iter().map(|f| f.download_count).sum::<u64>(),
+            }))
+            .build()
+            .expect("required fields are set")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(rename = "_aRecords")]
+    records: Vec<SearchRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchRecord {
+    #[serde(rename = "_idRow")]
+    id: u32,
+    #[serde(rename = "_sModelName")]
+    model_name: String,
+}
+
+#[derive(Debug)]
+pub struct GameBananaProvider {
+    client: reqwest::Client,
+    /// Does not follow redirects so the click-tracking URL can be resolved to the CDN URL
+    no_redirect_client: reqwest::Client,
+    auth: AuthConfig,
+    limiter: Arc<RateLimiter>,
+}
+
+impl Default for GameBananaProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameBananaProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {
+            client: http_client(),
+            no_redirect_client: http_client_builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .unwrap(),
+            auth: AuthConfig::None,
+            limiter: rate_limiter(GAMEBANANA_PROVIDER_ID),
+        }
+    }
+
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    async fn fetch_item(&self, item_id: u32) -> Result<GameBananaItem, ProviderError> {
+        type ItemResponse = (String, String, String, u32, HashMap<String, GameBananaFile>);
+
+        let url =
+            format!("{GAMEBANANA_API_URL}?itemtype=Mod&itemid={item_id}&fields={ITEM_FIELDS}");
+        self.limiter.acquire().await;
+        let (name, author, description, category_id, files): ItemResponse = self
+            .auth
+            .authenticate(self.client.get(&url))
+            .send()
+            .await
+            .context(RequestFailedSnafu { url: &url })?
+            .error_for_status()
+            .context(ResponseSnafu { url: &url })?
+            .json()
+            .await
+            .context(ResponseSnafu { url: &url })?;
+
+        let mut files = files.into_values().collect::<Vec<_>>();
+        files.sort_by_key(|f| std::cmp::Reverse(f.date_added));
+        Ok(GameBananaItem {
+            name,
+            author,
+            description,
+            category_id: Some(category_id),
+            files,
+        })
+    }
+
+    async fn item(
+        &self,
+        item_id: u32,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<GameBananaItem, ProviderError> {
+        let cached = (!update)
+            .then(|| {
+                cache
+                    .read()
+                    .unwrap()
+                    .get::<GameBananaProviderCache>(GAMEBANANA_PROVIDER_ID)
+                    .and_then(|c| c.items.get(&item_id).cloned())
+            })
+            .flatten();
+        if let Some(item) = cached {
+            return Ok(item);
+        }
+        let item = self.fetch_item(item_id).await?;
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<GameBananaProviderCache>(GAMEBANANA_PROVIDER_ID)
+            .items
+            .insert(item_id, item.clone());
+        Ok(item)
+    }
+
+    /// Follows the click-tracking redirect of a download URL to the CDN URL
+    async fn cdn_url(&self, download_url: &str) -> Result<String, ProviderError> {
+        self.limiter.acquire().await;
+        let response = self
+            .no_redirect_client
+            .head(download_url)
+            .send()
+            .await
+            .context(RequestFailedSnafu { url: download_url })?;
+        Ok(response
+            .status()
+            .is_redirection()
+            .then(|| response.headers().get(reqwest::header::LOCATION))
+            .flatten()
+            .and_then(|l| l.to_str().ok())
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| download_url.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for GameBananaProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Result<ModResponse, ProviderError> {
+        let (item_id, file_id) = parse_url(&spec.url)?;
+        let item = self.item(item_id, update, &cache).await?;
+        let file = item
+            .file(file_id)
+            .context(NoAssociatedModfileSnafu { url: &spec.url })?;
+        Ok(ModResponse::Resolve(Box::new(
+            item.to_mod_info(spec, item_id, file),
+        )))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let url = &res.url;
+        if let Some(path) = if update {
+            None
+        } else {
+            cache
+                .read()
+                .unwrap()
+                .get::<GameBananaProviderCache>(GAMEBANANA_PROVIDER_ID)
+                .and_then(|c| c.url_blobs.get(&url.0))
+                .and_then(|r| blob_cache.get_path(r))
+        } {
+            if let Some(tx) = tx {
+                tx.send(FetchProgress::Complete {
+                    resolution: res.clone(),
+                })
+                .await
+                .unwrap();
+            }
+            return Ok(path);
+        }
+
+        let (item_id, file_id) = parse_url(&url.0)?;
+        let item = self.item(item_id, false, &cache).await?;
+        let file = item
+            .file(file_id)
+            .context(NoAssociatedModfileSnafu { url: &url.0 })?;
+        let cdn_url = self.cdn_url(&file.download_url).await?;
+
+        info!("downloading mod {url:?}...");
+        self.limiter.acquire().await;
+        let blob = download_blob(
+            self.client.get(&cdn_url),
+            &cdn_url,
+            res,
+            blob_cache,
+            file.sha256.as_deref().and_then(parse_sha256),
+            tx.as_ref(),
+        )
+        .await?;
+        let path = blob_cache.get_path(&blob).unwrap();
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<GameBananaProviderCache>(GAMEBANANA_PROVIDER_ID)
+            .url_blobs
+            .insert(url.0.to_owned(), blob);
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        cache: ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        let url = GAMEBANANA_SEARCH_URL;
+        self.limiter.acquire().await;
+        let response: SearchResponse = self
+            .auth
+            .authenticate(self.client.get(url))
+            .query(&[
+                ("_sModelName", "Mod".to_string()),
+                ("_sOrder", "best_match".to_string()),
+                ("_idGameRow", GAMEBANANA_GAME_ID.to_string()),
+                ("_sSearchString", query.to_string()),
+                ("_nPerpage", SEARCH_LIMIT.to_string()),
+            ])
+            .send()
+            .await
+            .context(RequestFailedSnafu { url })?
+            .error_for_status()
+            .context(ResponseSnafu { url })?
+            .json()
+            .await
+            .context(ResponseSnafu { url })?;
+
+        let mut mods = vec![];
+        for record in response.records.iter().filter(|r| r.model_name == "Mod") {
+            let spec = ModSpecification::new(format!("gamebanana:{}", record.id));
+            let item = self.item(record.id, false, &cache).await?;
+            // items without files cannot be installed
+            if let Some(file) = item.file(None) {
+                mods.push(item.to_mod_info(&spec, record.id, file));
+            }
+        }
+        Ok(mods)
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        let (item_id, file_id) = parse_url(&spec.url).ok()?;
+        let lock = cache.read().unwrap();
+        let item = lock
+            .get::<GameBananaProviderCache>(GAMEBANANA_PROVIDER_ID)?
+            .items
+            .get(&item_id)?;
+        Some(item.to_mod_info(spec, item_id, item.file(file_id)?))
+    }
+
+    fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        parse_url(&spec.url).is_ok_and(|(_, file_id)| file_id.is_some())
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        let (item_id, file_id) = parse_url(&spec.url).ok()?;
+        let lock = cache.read().unwrap();
+        let item = lock
+            .get::<GameBananaProviderCache>(GAMEBANANA_PROVIDER_ID)?
+            .items
+            .get(&item_id)?;
+        Some(item.file(file_id)?.file_name.clone())
+    }
+}
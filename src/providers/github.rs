@@ -0,0 +1,644 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: GITHUB_PROVIDER_ID,
+        new: GitHubReleasesProvider::new_provider,
+        can_provide: |url| re_mod().is_match(url),
+        schemes: &["github"],
+        parameters: &[
+            ProviderParameter {
+                id: "token",
+                name: "Token",
+                description: "GitHub personal access token, raises the API rate limit",
+                link: Some("https://github.com/settings/tokens"),
+                optional: true,
+            },
+        ]
+    }
+}
+
+const GITHUB_PROVIDER_ID: &str = "github";
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Topic of repositories searched for mods
+const GITHUB_MOD_TOPIC: &str = "dbsz-mod";
+
+/// Repositories resolved per search, each requires fetching its release
+const SEARCH_LIMIT: usize = 10;
+
+/// Repository topics naming a category, repositories without any of them have no category
+static TOPIC_CATEGORIES: &[(&str, ModCategory)] = &[
+    ("gameplay", ModCategory::Gameplay),
+    ("moveset", ModCategory::Gameplay),
+    ("cosmetic", ModCategory::Cosmetic),
+    ("skin", ModCategory::Cosmetic),
+    ("audio", ModCategory::Audio),
+    ("music", ModCategory::Audio),
+    ("ui", ModCategory::UI),
+    ("utility", ModCategory::Utility),
+    ("tool", ModCategory::Utility),
+    ("debug", ModCategory::Debug),
+];
+
+fn lookup_category(topics: &[String]) -> Option<ModCategory> {
+    topics.iter().find_map(|topic| {
+        TOPIC_CATEGORIES
+            .iter()
+            .find_map(|(t, c)| (t == topic).then_some(*c))
+    })
+}
+
+static RE_MOD: OnceLock<regex::Regex> = OnceLock::new();
+fn re_mod() -> &'static regex::Regex {
+    RE_MOD.get_or_init(|| {
+        regex::Regex::new(r"^github:(?P<owner>[^/@]+)/(?P<repo>[^/@]+)(@(?P<tag>.+))?$").unwrap()
+    })
+}
+
+struct GitHubUrl<'a> {
+    owner: &'a str,
+    repo: &'a str,
+    tag: Option<&'a str>,
+}
+
+impl GitHubUrl<'_> {
+    fn repository_url(&self) -> String {
+        format!("{GITHUB_API_URL}/repos/{}/{}", self.owner, self.repo)
+    }
+
+    fn release_url(&self) -> String {
+        let GitHubUrl { owner, repo, tag } = self;
+        match tag {
+            Some(tag) => format!("{GITHUB_API_URL}/repos/{owner}/{repo}/releases/tags/{tag}"),
+            None => format!("{GITHUB_API_URL}/repos/{owner}/{repo}/releases/latest"),
+        }
+    }
+}
+
+fn parse_url(url: &str) -> Result<GitHubUrl<'_>, ProviderError> {
+    let captures = re_mod().captures(url).context(InvalidUrlSnafu { url })?;
+    Ok(GitHubUrl {
+        owner: captures.name("owner").unwrap().as_str(),
+        repo: captures.name("repo").unwrap().as_str(),
+        tag: captures.name("tag").map(|t| t.as_str()),
+    })
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitHubProviderCache {
+    /// Release metadata keyed by `cache_key` of the API URL
+    releases: HashMap<String, CachedRelease>,
+    url_blobs: HashMap<String, BlobRef>,
+    /// Repository metadata keyed by `cache_key` of the API URL
+    #[serde(default)]
+    repositories: HashMap<String, Repository>,
+    /// Etags of `repositories` to revalidate them when updating
+    #[serde(default)]
+    repository_etags: HashMap<String, String>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for GitHubProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedRelease {
+    etag: Option<String>,
+    release: Release,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Release {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    /// User who published the release
+    #[serde(default)]
+    author: Option<ReleaseAuthor>,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReleaseAuthor {
+    login: String,
+    html_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Repository {
+    #[serde(default)]
+    html_url: Option<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+    #[serde(default)]
+    license: Option<RepositoryLicense>,
+    #[serde(default)]
+    stargazers_count: u64,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    items: Vec<SearchItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchItem {
+    /// `owner/repo`
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    resources: RateLimitResources,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResources {
+    /// Limit of the REST API, which excludes search
+    core: RateLimit,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimit {
+    remaining: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RepositoryLicense {
+    /// `NOASSERTION` if GitHub could not identify the license
+    spdx_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+    /// `sha256:<hex>`, only present for assets uploaded after GitHub started hashing them
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+impl Asset {
+    fn sha256(&self) -> Option<[u8; 32]> {
+        parse_sha256(self.digest.as_deref()?.strip_prefix("sha256:")?)
+    }
+}
+
+impl Release {
+    /// First `.pak` or `.zip` asset of the release
+    fn mod_asset(&self) -> Option<&Asset> {
+        self.assets.iter().find(|a| {
+            let name = a.name.to_lowercase();
+            name.ends_with(".pak") || name.ends_with(".zip")
+        })
+    }
+
+    fn to_mod_info(
+        &self,
+        spec: &ModSpecification,
+        url: &GitHubUrl,
+        repository: Option<&Repository>,
+    ) -> ModInfo {
+        let pinned = format!("github:{}/{}@{}", url.owner, url.repo, self.tag_name);
+        ModInfo::builder()
+            .provider(GITHUB_PROVIDER_ID)
+            .name(
+                self.name
+                    .clone()
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or_else(|| format!("{}/{}", url.owner, url.repo)),
+            )
+            .spec(spec.clone())
+            .versions(vec![ModSpecification::new(pinned.clone())])
+            .resolution(ModResolution::resolvable(pinned.into()))
+            .mod_type(match self.mod_asset() {
+                Some(asset) if asset.name.to_lowercase().ends_with(".pak") => ModType::Pak,
+                _ => ModType::ModPlugin,
+            })
+            .changelog(self.body.clone())
+            .min_game_version(self.body.as_deref().and_then(game_version_from_notes))
+            .tags(repository.map(|r| r.topics.clone()).unwrap_or_default())
+            .category(repository.and_then(|r| lookup_category(&r.topics)))
+            .description(repository.and_then(|r| r.description.clone()))
+            .license(
+                repository
+                    .and_then(|r| r.license.as_ref()?.spdx_id.clone())
+                    .filter(|id| id != "NOASSERTION"),
+            )
+            .homepage(
+                repository
+                    .and_then(|r| r.html_url.clone())
+                    .unwrap_or_else(|| format!("https://github.com/{}/{}", url.owner, url.repo)),
+            )
+            .authors(
+                self.author
+                    .iter()
+                    .map(|a| Author {
+                        name: a.login.clone(),
+                        url: Some(a.html_url.clone()),
+                    })
+                    .collect(),
+            )
+            .extra_metadata(repository.map_or(
+                serde_json::Value::Null,
+                |r| serde_json::json!({ "star_count": r.stargazers_count }),
+            ))
+            .build()
+            .expect("required fields are set")
+    }
+}
+
+#[derive(Debug)]
+pub struct GitHubReleasesProvider {
+    client: reqwest::Client,
+    auth: AuthConfig,
+    limiter: Arc<RateLimiter>,
+}
+
+impl GitHubReleasesProvider {
+    pub fn new_provider(
+        parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        let token = parameters
+            .get("token")
+            .cloned()
+            .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+            .filter(|t| !t.is_empty());
+        Ok(Arc::new(Self::new(token)))
+    }
+
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            client: http_client_builder()
+                .user_agent(concat!("mint/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .unwrap(),
+            auth: token.map_or(AuthConfig::None, AuthConfig::Bearer),
+            limiter: rate_limiter(GITHUB_PROVIDER_ID),
+        }
+    }
+
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    fn request(&self, url: &str) -> reqwest::RequestBuilder {
+        self.auth.authenticate(self.client.get(url))
+    }
+
+    async fn resolve_release(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<ModInfo, ProviderError> {
+        let url = parse_url(&spec.url)?;
+        let release = self.release(&url, update, cache).await?;
+        ensure!(
+            release.mod_asset().is_some(),
+            NoAssociatedModfileSnafu { url: &spec.url }
+        );
+        let repository = self.repository(&url, update, cache).await?;
+        Ok(release.to_mod_info(spec, &url, Some(&repository)))
+    }
+
+    /// Resolves the latest release of every repository matching the search qualifiers `q`
+    async fn search_repositories(
+        &self,
+        q: &str,
+        cache: &ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        let url = format!("{GITHUB_API_URL}/search/repositories");
+        self.limiter.acquire().await;
+        let response: SearchResponse = self
+            .request(&url)
+            .query(&[("q", q.to_string()), ("per_page", SEARCH_LIMIT.to_string())])
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .context(RequestFailedSnafu { url: &url })?
+            .error_for_status()
+            .context(ResponseSnafu { url: &url })?
+            .json()
+            .await
+            .context(ResponseSnafu { url: &url })?;
+
+        let mut mods = vec![];
+        for item in response.items {
+            let spec = ModSpecification::new(format!("github:{}", item.full_name));
+            // repositories without a release cannot be installed
+            if let Ok(info) = self.resolve_release(&spec, false, cache).await {
+                mods.push(info);
+            }
+        }
+        Ok(mods)
+    }
+
+    /// Fetches the release, revalidating cached metadata with its etag
+    async fn release(
+        &self,
+        url: &GitHubUrl<'_>,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<Release, ProviderError> {
+        let release_url = url.release_url();
+        let key = cache_key(&release_url);
+        let cached = cache
+            .read()
+            .unwrap()
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .and_then(|c| c.releases.get(&key).cloned());
+        if !update
+            && url.tag.is_some()
+            && let Some(cached) = &cached
+        {
+            return Ok(cached.release.clone());
+        }
+
+        let mut request = self
+            .request(&release_url)
+            .header("Accept", "application/vnd.github+json");
+        if let Some(etag) = cached.as_ref().and_then(|c| c.etag.as_deref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        self.limiter.acquire().await;
+        let response = request
+            .send()
+            .await
+            .context(RequestFailedSnafu { url: &release_url })?;
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            return Ok(cached.release);
+        }
+        let response = response
+            .error_for_status()
+            .context(ResponseSnafu { url: &release_url })?;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|e| e.to_str().ok())
+            .map(|e| e.to_string());
+        let release: Release = response
+            .json()
+            .await
+            .context(ResponseSnafu { url: &release_url })?;
+
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .releases
+            .insert(
+                key,
+                CachedRelease {
+                    etag,
+                    release: release.clone(),
+                },
+            );
+        Ok(release)
+    }
+
+    /// Fetches the repository metadata unless it is cached, revalidating it with its etag when
+    /// updating
+    async fn repository(
+        &self,
+        url: &GitHubUrl<'_>,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<Repository, ProviderError> {
+        let repository_url = url.repository_url();
+        let key = cache_key(&repository_url);
+        let (cached, etag) = cache
+            .read()
+            .unwrap()
+            .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .map(|c| {
+                (
+                    c.repositories.get(&key).cloned(),
+                    c.repository_etags.get(&key).cloned(),
+                )
+            })
+            .unwrap_or_default();
+        if !update && let Some(repository) = cached {
+            return Ok(repository);
+        }
+
+        let mut request = self
+            .request(&repository_url)
+            .header("Accept", "application/vnd.github+json");
+        if cached.is_some()
+            && let Some(etag) = &etag
+        {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        self.limiter.acquire().await;
+        let response = request.send().await.context(RequestFailedSnafu {
+            url: &repository_url,
+        })?;
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(repository) = cached
+        {
+            return Ok(repository);
+        }
+        let response = response.error_for_status().context(ResponseSnafu {
+            url: &repository_url,
+        })?;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|e| e.to_str().ok())
+            .map(|e| e.to_string());
+        let repository: Repository = response.json().await.context(ResponseSnafu {
+            url: &repository_url,
+        })?;
+
+        let mut lock = cache.write().unwrap();
+        let github_cache = lock.get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID);
+        match etag {
+            Some(etag) => github_cache.repository_etags.insert(key.clone(), etag),
+            None => github_cache.repository_etags.remove(&key),
+        };
+        github_cache.repositories.insert(key, repository.clone());
+        Ok(repository)
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for GitHubReleasesProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Result<ModResponse, ProviderError> {
+        Ok(ModResponse::Resolve(Box::new(
+            self.resolve_release(spec, update, &cache).await?,
+        )))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let url = &res.url;
+        if let Some(path) = if update {
+            None
+        } else {
+            cache
+                .read()
+                .unwrap()
+                .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                .and_then(|c| c.url_blobs.get(&url.0))
+                .and_then(|r| blob_cache.get_path(r))
+        } {
+            if let Some(tx) = tx {
+                tx.send(FetchProgress::Complete {
+                    resolution: res.clone(),
+                })
+                .await
+                .unwrap();
+            }
+            return Ok(path);
+        }
+
+        let release = self.release(&parse_url(&url.0)?, update, &cache).await?;
+        let asset = release
+            .mod_asset()
+            .context(NoAssociatedModfileSnafu { url: &url.0 })?;
+
+        info!("downloading mod {url:?}...");
+        self.limiter.acquire().await;
+        let blob = download_blob(
+            self.request(&asset.browser_download_url),
+            &asset.browser_download_url,
+            res,
+            blob_cache,
+            asset.sha256(),
+            tx.as_ref(),
+        )
+        .await?;
+        let path = blob_cache.get_path(&blob).unwrap();
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+            .url_blobs
+            .insert(url.0.to_owned(), blob);
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        let url = parse_url(&spec.url).ok()?;
+        let lock = cache.read().unwrap();
+        let cache = lock.get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)?;
+        let release = &cache.releases.get(&cache_key(&url.release_url()))?.release;
+        Some(release.to_mod_info(
+            spec,
+            &url,
+            cache.repositories.get(&cache_key(&url.repository_url())),
+        ))
+    }
+
+    fn is_pinned(&self, spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        parse_url(&spec.url).is_ok_and(|url| url.tag.is_some())
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        let url = parse_url(&spec.url).ok()?;
+        Some(match url.tag {
+            Some(tag) => tag.to_string(),
+            None => cache
+                .read()
+                .unwrap()
+                .get::<GitHubProviderCache>(GITHUB_PROVIDER_ID)
+                .and_then(|c| c.releases.get(&cache_key(&url.release_url())))
+                .map(|c| c.release.tag_name.clone())
+                .unwrap_or_else(|| "latest".to_string()),
+        })
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        cache: ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        self.search_repositories(&format!("{query} topic:{GITHUB_MOD_TOPIC}"), &cache)
+            .await
+    }
+
+    async fn search_by_author(
+        &self,
+        author: &str,
+        cache: ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        self.search_repositories(&format!("topic:{GITHUB_MOD_TOPIC} user:{author}"), &cache)
+            .await
+    }
+
+    /// Queries the rate limit endpoint, which does not count against the limit
+    async fn health_check(&self) -> Result<ProviderHealth, ProviderError> {
+        let url = format!("{GITHUB_API_URL}/rate_limit");
+        self.limiter.acquire().await;
+        let start = Instant::now();
+        let result: Result<RateLimitResponse, ProviderError> = async {
+            self.request(&url)
+                .header("Accept", "application/vnd.github+json")
+                .send()
+                .await
+                .context(RequestFailedSnafu { url: &url })?
+                .error_for_status()
+                .context(ResponseSnafu { url: &url })?
+                .json()
+                .await
+                .context(ResponseSnafu { url: &url })
+        }
+        .await;
+        let remaining = result.as_ref().ok().map(|r| r.resources.core.remaining);
+        Ok(ProviderHealth::from_result(start, remaining, result))
+    }
+}
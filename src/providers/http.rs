@@ -1,7 +1,7 @@
 use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 use mint_lib::mod_info::ModType::ModPlugin;
 use crate::providers::*;
 
@@ -17,6 +17,7 @@ inventory::submit! {
                     !["mod.io", "drg.mod.io", "drg.old.mod.io"].contains(&h.as_str())
                 })
         },
+        schemes: &["http", "https"],
         parameters: &[],
     }
 }
@@ -26,6 +27,16 @@ pub struct HttpProviderCache {
     url_blobs: HashMap<String, BlobRef>,
 }
 
+/// Removes the blob downloaded from `url` so another provider can take it over
+pub(super) fn take_url_blob(cache: &ProviderCache, url: &str) -> Option<BlobRef> {
+    cache
+        .write()
+        .unwrap()
+        .get_mut::<HttpProviderCache>(HTTP_PROVIDER_ID)
+        .url_blobs
+        .remove(url)
+}
+
 #[typetag::serde]
 impl ModProviderCache for HttpProviderCache {
     fn new() -> Self {
@@ -44,6 +55,9 @@ impl ModProviderCache for HttpProviderCache {
 #[derive(Debug)]
 pub struct HttpProvider {
     client: reqwest::Client,
+    auth: AuthConfig,
+    limiter: Arc<RateLimiter>,
+    delta: DeltaDownloader,
 }
 
 impl HttpProvider {
@@ -54,15 +68,51 @@ impl HttpProvider {
     }
 
     pub fn new() -> Self {
+        let client = http_client();
         Self {
-            client: reqwest::Client::new(),
+            delta: DeltaDownloader::new(client.clone()),
+            client,
+            auth: AuthConfig::None,
+            limiter: rate_limiter(HTTP_PROVIDER_ID),
         }
     }
+
+    /// Updates the previously downloaded file of the url with a delta patch if the server has one.
+    /// Mods at the same url change as they are updated, so their last download is patched.
+    async fn fetch_delta(
+        &self,
+        url: &str,
+        cache: &ProviderCache,
+        blob_cache: &BlobCache,
+    ) -> Option<Vec<u8>> {
+        if !delta_downloads_enabled() {
+            return None;
+        }
+        let old = cache
+            .read()
+            .unwrap()
+            .get::<HttpProviderCache>(HTTP_PROVIDER_ID)
+            .and_then(|c| c.url_blobs.get(url))
+            .and_then(|r| blob_cache.get_path(r))?;
+        self.limiter.acquire().await;
+        match self.delta.fetch(url, &old, &self.auth).await {
+            Ok(patched) => patched,
+            Err(e) => {
+                warn!("delta download of {url:?} failed, downloading it in full: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
 }
 
 static RE_MOD: OnceLock<regex::Regex> = OnceLock::new();
 fn re_mod() -> &'static regex::Regex {
-    RE_MOD.get_or_init(|| regex::Regex::new(r"^https?://(?P<hostname>[^/]+)(/|$)").unwrap())
+    RE_MOD.get_or_init(|| regex::Regex::new(r"^http://(?P<hostname>[^/]+)(/|$)").unwrap())
 }
 
 const HTTP_PROVIDER_ID: &str = "http";
@@ -74,6 +124,7 @@ impl ModProvider for HttpProvider {
         spec: &ModSpecification,
         _update: bool,
         _cache: ProviderCache,
+        _blob_cache: &BlobCache,
     ) -> Result<ModResponse, ProviderError> {
         let Ok(url) = url::Url::parse(&spec.url) else {
             return Err(ProviderError::InvalidUrl {
@@ -87,16 +138,16 @@ impl ModProvider for HttpProvider {
             .map(|s| s.to_string())
             .unwrap_or_else(|| url.to_string());
 
-        Ok(ModResponse::Resolve(ModInfo {
-            provider: HTTP_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::resolvable(spec.url.as_str().into()),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            mod_type: ModPlugin
-        }))
+        Ok(ModResponse::Resolve(Box::new(
+            ModInfo::builder()
+                .provider(HTTP_PROVIDER_ID)
+                .name(name)
+                .spec(spec.clone())
+                .resolution(ModResolution::resolvable(spec.url.as_str().into()))
+                .mod_type(ModPlugin)
+                .build()
+                .expect("required fields are set"),
+        )))
     }
 
     async fn fetch_mod(
@@ -119,6 +170,24 @@ impl ModProvider for HttpProvider {
                     .and_then(|c| c.url_blobs.get(&url.0))
                     .and_then(|r| blob_cache.get_path(r))
             } {
+                if let Some(tx) = tx {
+                    tx.send(FetchProgress::Complete {
+                        resolution: res.clone(),
+                    })
+                    .await
+                    .unwrap();
+                }
+                path
+            } else if let Some(patched) = self.fetch_delta(&url.0, &cache, blob_cache).await {
+                let blob = blob_cache.write(&patched)?;
+                let path = blob_cache.get_path(&blob).unwrap();
+                cache
+                    .write()
+                    .unwrap()
+                    .get_mut::<HttpProviderCache>(HTTP_PROVIDER_ID)
+                    .url_blobs
+                    .insert(url.0.to_owned(), blob);
+
                 if let Some(tx) = tx {
                     tx.send(FetchProgress::Complete {
                         resolution: res.clone(),
@@ -129,9 +198,10 @@ impl ModProvider for HttpProvider {
                 path
             } else {
                 info!("downloading mod {url:?}...");
+                self.limiter.acquire().await;
                 let response = self
-                    .client
-                    .get(&url.0)
+                    .auth
+                    .authenticate(self.client.get(&url.0))
                     .send()
                     .await
                     .context(RequestFailedSnafu {
@@ -221,16 +291,16 @@ impl ModProvider for HttpProvider {
             .and_then(|s| s.last())
             .map(|s| s.to_string())
             .unwrap_or_else(|| url.to_string());
-        Some(ModInfo {
-            provider: HTTP_PROVIDER_ID,
-            name,
-            spec: spec.clone(),
-            versions: vec![],
-            resolution: ModResolution::resolvable(spec.url.as_str().into()),
-            suggested_require: false,
-            suggested_dependencies: vec![],
-            mod_type: ModPlugin
-        })
+        Some(
+            ModInfo::builder()
+                .provider(HTTP_PROVIDER_ID)
+                .name(name)
+                .spec(spec.clone())
+                .resolution(ModResolution::resolvable(spec.url.as_str().into()))
+                .mod_type(ModPlugin)
+                .build()
+                .expect("required fields are set"),
+        )
     }
 
     fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
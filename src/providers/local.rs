@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: LOCAL_PROVIDER_ID,
+        new: LocalFsProvider::new_provider,
+        can_provide: |url| local_path(url).is_some(),
+        schemes: &[],
+        parameters: &[],
+    }
+}
+
+const LOCAL_PROVIDER_ID: &str = "local";
+
+/// Path of a `file://<absolute-path>` or `local:<relative-path-from-cwd>` URL
+fn local_path(url: &str) -> Option<PathBuf> {
+    if let Some(path) = url.strip_prefix("file://") {
+        let path = PathBuf::from(path);
+        path.is_absolute().then_some(path)
+    } else {
+        url.strip_prefix("local:")
+            .map(|path| std::env::current_dir().unwrap_or_default().join(path))
+    }
+}
+
+fn find_with_extension(dir: &Path, extension: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// Display name from the `FriendlyName` of the plugin descriptor if present
+fn plugin_name(uplugin: &Path) -> Option<String> {
+    let descriptor: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(uplugin).ok()?).ok()?;
+    descriptor
+        .get("FriendlyName")?
+        .as_str()
+        .filter(|n| !n.is_empty())
+        .map(|n| n.to_string())
+}
+
+fn inspect(spec: &ModSpecification) -> Result<ModInfo, ProviderError> {
+    let path = local_path(&spec.url).context(InvalidUrlSnafu { url: &spec.url })?;
+    let dir_name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| spec.url.to_string());
+
+    let (mod_type, name) = if let Some(uplugin) = find_with_extension(&path, "uplugin") {
+        (
+            ModType::ModPlugin,
+            plugin_name(&uplugin).unwrap_or(dir_name),
+        )
+    } else if find_with_extension(&path, "pak").is_some() {
+        (ModType::Pak, dir_name)
+    } else {
+        return UnrecognizedFormatSnafu { url: &spec.url }.fail();
+    };
+
+    Ok(ModInfo::builder()
+        .provider(LOCAL_PROVIDER_ID)
+        .name(name)
+        .spec(spec.clone())
+        .resolution(ModResolution::unresolvable(
+            spec.url.clone().into(),
+            path.file_name()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+        ))
+        .mod_type(mod_type)
+        .build()
+        .expect("required fields are set"))
+}
+
+#[derive(Debug, Default)]
+pub struct LocalFsProvider {}
+
+impl LocalFsProvider {
+    pub fn new_provider(
+        _parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Ok(Arc::new(Self::new()))
+    }
+
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for LocalFsProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        _update: bool,
+        _cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Result<ModResponse, ProviderError> {
+        Ok(ModResponse::Resolve(Box::new(inspect(spec)?)))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        _update: bool,
+        _cache: ProviderCache,
+        _blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let path = local_path(&res.url.0).context(InvalidUrlSnafu { url: &res.url.0 })?;
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
+        inspect(spec).ok()
+    }
+
+    fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        true
+    }
+
+    fn get_version_name(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<String> {
+        Some("local".to_string())
+    }
+}
@@ -1,7 +1,20 @@
+pub mod auth;
+pub mod delta;
+pub mod direct;
 pub mod file;
-pub mod http;#[macro_use]
+pub mod gamebanana;
+pub mod github;
+pub mod http;
+pub mod local;#[macro_use]
 pub mod cache;
 pub mod mod_store;
+pub mod nexus;
+pub mod offline;
+pub mod proxy;
+pub mod rate_limit;
+pub mod search;
+pub mod source;
+pub mod updates;
 
 use snafu::prelude::*;
 use tokio::sync::mpsc::Sender;
@@ -9,13 +22,25 @@ use tokio::sync::mpsc::Sender;
 use std::collections::HashMap;
 use std::io::{Read, Seek};
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Instant, SystemTime};
 
+pub use auth::*;
 pub use cache::*;
+pub use delta::*;
 pub use mint_lib::mod_info::*;
 pub use mod_store::*;
+pub use offline::*;
+pub use proxy::*;
+pub use rate_limit::*;
+pub use search::*;
+pub use source::*;
+pub use updates::*;
 
-type Providers = RwLock<HashMap<&'static str, Arc<dyn ModProvider>>>;
+type Providers = Arc<RwLock<HashMap<&'static str, Arc<dyn ModProvider>>>>;
+/// Parameters from the config of providers which are created when they are first used, only then
+/// their credentials are loaded from the keychain
+type PendingProviders = Arc<Mutex<HashMap<&'static str, HashMap<String, String>>>>;
 
 pub trait ReadSeek: Read + Seek + Send {}
 impl<T: Seek + Read + Send> ReadSeek for T {}
@@ -41,6 +66,103 @@ impl FetchProgress {
     }
 }
 
+/// Downloads the response of `request` into the blob cache while reporting progress for `res`
+pub(crate) async fn download_blob(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    res: &ModResolution,
+    blob_cache: &BlobCache,
+    expected_sha256: Option<[u8; 32]>,
+    tx: Option<&Sender<FetchProgress>>,
+) -> Result<BlobRef, ProviderError> {
+    use futures::stream::TryStreamExt;
+    use sha2::{Digest, Sha256};
+    use tokio::io::AsyncWriteExt;
+
+    if let Some(blob) = expected_sha256.and_then(|hash| blob_cache.get_by_hash(hash)) {
+        return Ok(blob);
+    }
+
+    let response = request
+        .send()
+        .await
+        .context(RequestFailedSnafu { url })?
+        .error_for_status()
+        .context(ResponseSnafu { url })?;
+    let size = response.content_length();
+
+    let mut cursor = std::io::Cursor::new(vec![]);
+    let mut stream = response.bytes_stream();
+    while let Some(bytes) = stream
+        .try_next()
+        .await
+        .with_context(|_| FetchSnafu { url })?
+    {
+        cursor
+            .write_all(&bytes)
+            .await
+            .with_context(|_| BufferIoSnafu { url })?;
+        if let Some(size) = size
+            && let Some(tx) = tx
+        {
+            tx.send(FetchProgress::Progress {
+                resolution: res.clone(),
+                progress: cursor.get_ref().len() as u64,
+                size,
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    let blob = cursor.into_inner();
+    if let Some(expected) = expected_sha256 {
+        let found: [u8; 32] = Sha256::digest(&blob).into();
+        ensure!(
+            found == expected,
+            ChecksumMismatchSnafu {
+                url,
+                expected: hex::encode(expected),
+                found: hex::encode(found),
+            }
+        );
+    }
+    Ok(blob_cache.write(&blob)?)
+}
+
+/// Key providers cache resolved mods under, so differently formatted URLs of the same mod such as
+/// `github:Owner/Repo` and `github:owner/repo` share an entry
+pub(crate) fn cache_key(url: &str) -> String {
+    ModIdentifier::normalize(url).0
+}
+
+/// Reads a hex encoded SHA-256 as published by providers
+pub(crate) fn parse_sha256(hex: &str) -> Option<[u8; 32]> {
+    hex::decode(hex.trim()).ok()?.try_into().ok()
+}
+
+/// Reads the minimum game version from a `Game version: 1.2.3` (or `Minimum game version: ...`)
+/// line of release notes or a mod description. Markdown list and emphasis markers are ignored and
+/// missing components are zero, so `1.2` is read as `1.2.0`.
+pub(crate) fn game_version_from_notes(notes: &str) -> Option<SemverVersion> {
+    notes.lines().find_map(|line| {
+        let line = line
+            .trim()
+            .trim_start_matches(['#', '*', '-', ' '])
+            .to_lowercase();
+        let rest = ["minimum game version", "min game version", "game version"]
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))?;
+        let version = rest
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?;
+        let version = version.trim_end_matches('.');
+        let padding = ".0".repeat(2usize.saturating_sub(version.matches('.').count()));
+        SemverVersion::parse(&format!("{version}{padding}")).ok()
+    })
+}
+
 #[async_trait::async_trait]
 pub trait ModProvider: Send + Sync {
     async fn resolve_mod(
@@ -48,6 +170,7 @@ pub trait ModProvider: Send + Sync {
         spec: &ModSpecification,
         update: bool,
         cache: ProviderCache,
+        blob_cache: &BlobCache,
     ) -> Result<ModResponse, ProviderError>;
     async fn fetch_mod(
         &self,
@@ -63,6 +186,43 @@ pub trait ModProvider: Send + Sync {
     fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo>;
     fn is_pinned(&self, spec: &ModSpecification, cache: ProviderCache) -> bool;
     fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String>;
+    /// Changelog of the resolved mod. Providers requiring extra requests for it fetch it here
+    /// instead of when resolving, by default it is the changelog returned when resolving.
+    async fn changelog(
+        &self,
+        info: &ModInfo,
+        _cache: ProviderCache,
+    ) -> Result<Option<String>, ProviderError> {
+        Ok(info.changelog.clone())
+    }
+    /// Mods matching the query, empty for providers without a search API
+    async fn search(
+        &self,
+        _query: &str,
+        _cache: ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        Ok(vec![])
+    }
+    /// Mods by the author, by default the results of searching for the name of the author
+    async fn search_by_author(
+        &self,
+        author: &str,
+        cache: ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        let mut mods = self.search(author, cache).await?;
+        mods.retain(|m| {
+            m.authors
+                .iter()
+                .any(|a| a.name.eq_ignore_ascii_case(author))
+        });
+        Ok(mods)
+    }
+    /// Times `check`, providers with an API quota override this to report it
+    async fn health_check(&self) -> Result<ProviderHealth, ProviderError> {
+        let start = Instant::now();
+        let result = self.check().await;
+        Ok(ProviderHealth::from_result(start, None, result))
+    }
 }
 
 #[derive(Debug, Snafu)]
@@ -114,6 +274,49 @@ pub enum ProviderError {
     AmbiguousModNameId { name_id: String },
     #[snafu(display("no mods returned for name \"{name_id}\""))]
     NoModsForNameId { name_id: String },
+    #[snafu(display("{provider} requires a valid API key"))]
+    AuthRequired { provider: &'static str },
+    #[snafu(display("invalid checksum at <{url}>"))]
+    InvalidChecksum { url: String },
+    #[snafu(display("<{url}> produced SHA-256 {found} but {expected} was expected"))]
+    ChecksumMismatch {
+        url: String,
+        expected: String,
+        found: String,
+    },
+    #[snafu(display("could not recognize the mod format of <{url}>"))]
+    UnrecognizedFormat { url: String },
+    #[snafu(display(
+        "invalid version constraint {constraint:?} in <{url}>, expected a version optionally prefixed by >=, ^, ~ or ="
+    ))]
+    InvalidVersionConstraint { url: String, constraint: String },
+    #[snafu(display("no version of <{url}> satisfies {constraint}"))]
+    NoMatchingVersion { url: String, constraint: String },
+    #[snafu(display("invalid mod <{url}>: {source}"))]
+    InvalidSpec { source: SpecError, url: String },
+    #[snafu(display("mod <{url}> requires authentication, sign in at <{auth_url}>"))]
+    RequiresAuth { url: String, auth_url: String },
+    #[snafu(display("could not access the keychain entry of {provider}: {source}"))]
+    KeyringError {
+        source: keyring::Error,
+        provider: String,
+    },
+    #[snafu(display("query to <{url}> failed: {message}"))]
+    QueryError { url: String, message: String },
+    #[snafu(display("<{url}> redirected more than {MAX_REDIRECTS} times"))]
+    TooManyRedirects { url: String },
+    #[snafu(display("provider is unreachable, {}", describe_last_seen(*last_seen)))]
+    Offline { last_seen: Option<SystemTime> },
+}
+
+fn describe_last_seen(last_seen: Option<SystemTime>) -> String {
+    match last_seen {
+        Some(time) => format!(
+            "the mod was last resolved at {}",
+            chrono::DateTime::<chrono::Local>::from(time).format("%Y-%m-%d %H:%M")
+        ),
+        None => "the mod has not been resolved before".to_string(),
+    }
 }
 
 impl ProviderError {
@@ -124,6 +327,18 @@ impl ProviderError {
             _ => None,
         }
     }
+
+    /// Whether the error was caused by the provider being unreachable rather than by its response
+    pub fn is_network_error(&self) -> bool {
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+                return error.is_connect() || error.is_timeout() || error.is_request();
+            }
+            source = error.source();
+        }
+        false
+    }
 }
 
 #[derive(Clone)]
@@ -132,6 +347,8 @@ pub struct ProviderFactory {
     #[allow(clippy::type_complexity)]
     new: fn(&HashMap<String, String>) -> Result<Arc<dyn ModProvider>, ProviderError>,
     can_provide: fn(&str) -> bool,
+    /// URL schemes handled by the provider, empty for providers of local paths
+    pub schemes: &'static [&'static str],
     pub parameters: &'static [ProviderParameter<'static>],
 }
 
@@ -139,6 +356,7 @@ impl std::fmt::Debug for ProviderFactory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ProviderFactory")
             .field("id", &self.id)
+            .field("schemes", &self.schemes)
             .field("parameters", &self.parameters)
             .finish()
     }
@@ -150,6 +368,36 @@ pub struct ProviderParameter<'a> {
     pub name: &'a str,
     pub description: &'a str,
     pub link: Option<&'a str>,
+    /// Providers are created without it and it is not prompted for
+    pub optional: bool,
 }
 
 inventory::collect!(ProviderFactory);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_version_from_notes() {
+        let version = |major, minor, patch| SemverVersion {
+            major,
+            minor,
+            patch,
+            pre_release: None,
+        };
+        assert_eq!(
+            game_version_from_notes("Fixes\n- **Game version:** 1.2.3"),
+            Some(version(1, 2, 3))
+        );
+        assert_eq!(
+            game_version_from_notes("Minimum game version: 1.2."),
+            Some(version(1, 2, 0))
+        );
+        assert_eq!(
+            game_version_from_notes("game version 2"),
+            Some(version(2, 0, 0))
+        );
+        assert_eq!(game_version_from_notes("no version"), None);
+    }
+}
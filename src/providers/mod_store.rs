@@ -1,16 +1,28 @@
 use std::collections::HashSet;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
 
 use snafu::prelude::*;
 use tracing::*;
 
+use crate::mod_lints::lint_metadata;
 use crate::providers::*;
 use crate::state::config::ConfigWrapper;
 
+/// Redirects followed while resolving a mod before giving up, providers redirecting to each other
+/// would otherwise never finish resolving
+pub const MAX_REDIRECTS: usize = 8;
+
 pub struct ModStore {
     providers: Providers,
+    pending_providers: PendingProviders,
     cache: ProviderCache,
     blob_cache: BlobCache,
+    resolutions: Mutex<ResolutionCache>,
+    /// See `set_offline`
+    offline: AtomicBool,
 }
 
 impl ModStore {
@@ -19,16 +31,23 @@ impl ModStore {
         parameters: &HashMap<String, HashMap<String, String>>,
     ) -> Result<Self, ProviderError> {
         let mut providers = HashMap::new();
+        let mut pending_providers = HashMap::new();
         for prov in Self::get_provider_factories() {
+            for scheme in prov.schemes {
+                register_url_scheme(scheme);
+            }
             let params = parameters.get(prov.id).cloned().unwrap_or_default();
-            if prov.parameters.iter().all(|p| params.contains_key(p.id)) {
+            if prov.parameters.is_empty() {
                 let Ok(provider) = (prov.new)(&params) else {
                     return Err(ProviderError::InitProviderFailed {
                         id: prov.id,
-                        parameters: params.to_owned(),
+                        parameters: params,
                     });
                 };
                 providers.insert(prov.id, provider);
+            } else {
+                // the keychain is only queried for providers which are actually used
+                pending_providers.insert(prov.id, params);
             }
         }
 
@@ -39,12 +58,47 @@ impl ModStore {
         cache.save().unwrap();
 
         Ok(Self {
-            providers: RwLock::new(providers),
+            providers: Arc::new(RwLock::new(providers)),
+            pending_providers: Arc::new(Mutex::new(pending_providers)),
             cache: Arc::new(RwLock::new(cache)),
-            blob_cache: BlobCache::new(cache_path.as_ref().join("blobs")),
+            blob_cache: BlobCache::new(
+                cache_path.as_ref().join("blobs"),
+                DownloadCache::load(&cache_path),
+            ),
+            resolutions: Mutex::new(ResolutionCache::load(
+                cache_path.as_ref().join("resolutions.json"),
+                DEFAULT_RESOLUTION_TTL,
+            )),
+            offline: AtomicBool::new(false),
         })
     }
 
+    /// In offline mode cached resolutions are used regardless of their age, even when updating,
+    /// and network errors are reported as `ProviderError::Offline`.
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    /// Reports network errors in offline mode as `ProviderError::Offline`, `last_seen` picks the
+    /// time the mod was resolved from the cached resolutions
+    fn check_offline<T>(
+        &self,
+        result: Result<T, ProviderError>,
+        last_seen: impl FnOnce(&ResolutionCache) -> Option<SystemTime>,
+    ) -> Result<T, ProviderError> {
+        match result {
+            Err(e) if self.is_offline() && e.is_network_error() => OfflineSnafu {
+                last_seen: last_seen(&self.resolutions.lock().unwrap()),
+            }
+            .fail(),
+            result => result,
+        }
+    }
+
     pub fn get_provider_factories() -> impl Iterator<Item = &'static ProviderFactory> {
         inventory::iter::<ProviderFactory>()
     }
@@ -55,6 +109,10 @@ impl ModStore {
         parameters: &HashMap<String, String>,
     ) -> Result<(), ProviderError> {
         let provider = (provider_factory.new)(parameters)?;
+        self.pending_providers
+            .lock()
+            .unwrap()
+            .remove(provider_factory.id);
         self.providers
             .write()
             .unwrap()
@@ -69,6 +127,10 @@ impl ModStore {
     ) -> Result<(), ProviderError> {
         let provider = (provider_factory.new)(parameters)?;
         provider.check().await?;
+        self.pending_providers
+            .lock()
+            .unwrap()
+            .remove(provider_factory.id);
         self.providers
             .write()
             .unwrap()
@@ -76,13 +138,32 @@ impl ModStore {
         Ok(())
     }
 
-    pub fn get_provider(&self, url: &str) -> Result<Arc<dyn ModProvider>, ProviderError> {
-        let factory = Self::get_provider_factories()
+    fn get_factory(url: &str) -> Result<&'static ProviderFactory, ProviderError> {
+        // the direct URL provider accepts any https URL so it is only used as a fallback
+        let (fallback, mut factories): (Vec<_>, Vec<_>) =
+            Self::get_provider_factories().partition(|f| f.id == direct::DIRECT_URL_PROVIDER_ID);
+        factories.extend(fallback);
+        factories
+            .into_iter()
             .find(|f| (f.can_provide)(url))
             .context(ProviderNotFoundSnafu {
                 url: url.to_string(),
-            })?;
-        let lock = self.providers.read().unwrap();
+            })
+    }
+
+    pub fn get_provider(&self, url: &str) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        Self::get_provider_in(&self.providers, &self.pending_providers, url)
+    }
+
+    /// Provider handling `url` among `providers`, for sources resolving redirects without the store
+    pub(super) fn get_provider_in(
+        providers: &Providers,
+        pending_providers: &PendingProviders,
+        url: &str,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        let factory = Self::get_factory(url)?;
+        Self::init_pending_provider(providers, pending_providers, factory)?;
+        let lock = providers.read().unwrap();
         Ok(match lock.get(factory.id) {
             Some(e) => e.clone(),
             None => NoProviderSnafu {
@@ -93,6 +174,48 @@ impl ModStore {
         })
     }
 
+    /// Creates the provider of `factory` with its credentials from the keychain if it is pending.
+    /// Providers missing required parameters are not created and have to be added by the caller.
+    fn init_pending_provider(
+        providers: &Providers,
+        pending_providers: &PendingProviders,
+        factory: &'static ProviderFactory,
+    ) -> Result<(), ProviderError> {
+        let mut pending = pending_providers.lock().unwrap();
+        let Some(mut params) = pending.remove(factory.id) else {
+            return Ok(());
+        };
+        if providers.read().unwrap().contains_key(factory.id) {
+            return Ok(());
+        }
+        params.extend(load_credentials(factory.id));
+        if factory
+            .parameters
+            .iter()
+            .all(|p| p.optional || params.contains_key(p.id))
+        {
+            let Ok(provider) = (factory.new)(&params) else {
+                return Err(ProviderError::InitProviderFailed {
+                    id: factory.id,
+                    parameters: params,
+                });
+            };
+            providers.write().unwrap().insert(factory.id, provider);
+        }
+        Ok(())
+    }
+
+    /// Creates every pending provider, for operations using all configured providers
+    fn init_pending_providers(&self) {
+        for factory in Self::get_provider_factories() {
+            if let Err(e) =
+                Self::init_pending_provider(&self.providers, &self.pending_providers, factory)
+            {
+                warn!("{e}");
+            }
+        }
+    }
+
     pub async fn resolve_mods(
         &self,
         mods: &[ModSpecification],
@@ -138,21 +261,128 @@ impl ModStore {
         original_spec: ModSpecification,
         update: bool,
     ) -> Result<(ModSpecification, ModInfo), ProviderError> {
-        let mut spec = original_spec.clone();
+        ModSpecification::checked_new(original_spec.url.clone()).context(InvalidSpecSnafu {
+            url: &original_spec.url,
+        })?;
+
+        let offline = self.is_offline();
+        let cached = {
+            let resolutions = self.resolutions.lock().unwrap();
+            if offline {
+                resolutions.get_ignoring_ttl(&original_spec).cloned()
+            } else {
+                (!update)
+                    .then(|| resolutions.get(&original_spec).cloned())
+                    .flatten()
+            }
+        };
+        if let Some(m) = cached {
+            return Ok((original_spec, m));
+        }
+
+        let mut constraint = match original_spec.version_constraint_str() {
+            Some(constraint) => Some(original_spec.version_constraint().context(
+                InvalidVersionConstraintSnafu {
+                    url: &original_spec.url,
+                    constraint,
+                },
+            )?),
+            None => None,
+        };
+
+        let mut spec = original_spec.without_version_constraint();
+        let mut redirects = 0;
         loop {
-            match self
+            let response = self
                 .get_provider(&spec.url)?
-                .resolve_mod(&spec, update, self.cache.clone())
-                .await?
-            {
+                .resolve_mod(
+                    &spec,
+                    update && !offline,
+                    self.cache.clone(),
+                    &self.blob_cache,
+                )
+                .await;
+            match self.check_offline(response, |_| None)? {
                 ModResponse::Resolve(m) => {
-                    return Ok((original_spec, m));
+                    match &m.resolution.status {
+                        ResolvableStatus::RequiresAuth(auth_url) => {
+                            return RequiresAuthSnafu {
+                                url: &spec.url,
+                                auth_url,
+                            }
+                            .fail();
+                        }
+                        ResolvableStatus::Deprecated { replaced_by } => {
+                            // mods replacing each other would otherwise never finish resolving
+                            redirects += 1;
+                            ensure!(
+                                redirects <= MAX_REDIRECTS,
+                                TooManyRedirectsSnafu {
+                                    url: &original_spec.url
+                                }
+                            );
+                            warn!(
+                                "mod {:?} is deprecated, using {:?} instead",
+                                spec.url, replaced_by.url
+                            );
+                            spec = replaced_by.clone();
+                            constraint = None;
+                            continue;
+                        }
+                        ResolvableStatus::Resolvable | ResolvableStatus::Unresolvable(_) => {}
+                    }
+                    if let Some(constraint) = constraint.take() {
+                        let selected = self.select_version(&m, &constraint)?;
+                        if selected.url != m.resolution.url.0 {
+                            spec = selected;
+                            continue;
+                        }
+                    }
+                    for error in lint_metadata(&m) {
+                        warn!("mod {:?}: {error}", spec.url);
+                    }
+                    if let Err(e) = self
+                        .resolutions
+                        .lock()
+                        .unwrap()
+                        .insert(original_spec.clone(), m.clone())
+                    {
+                        warn!("failed to save resolution cache: {e}");
+                    }
+                    return Ok((original_spec, *m));
+                }
+                ModResponse::Redirect(redirected_spec) => {
+                    redirects += 1;
+                    ensure!(
+                        redirects <= MAX_REDIRECTS,
+                        TooManyRedirectsSnafu {
+                            url: &original_spec.url
+                        }
+                    );
+                    spec = redirected_spec;
                 }
-                ModResponse::Redirect(redirected_spec) => spec = redirected_spec,
             };
         }
     }
 
+    /// Newest of the mod's versions satisfying the constraint.
+    fn select_version(
+        &self,
+        m: &ModInfo,
+        constraint: &VersionConstraint,
+    ) -> Result<ModSpecification, ProviderError> {
+        let provider = self.get_provider(&m.spec.url)?;
+        constraint
+            .select(m.versions.iter().filter_map(|v| {
+                let name = provider.get_version_name(v, self.cache.clone())?;
+                Some((SemverVersion::parse(&name).ok()?, v.clone()))
+            }))
+            .context(NoMatchingVersionSnafu {
+                url: &m.spec.url,
+                constraint: constraint.to_string(),
+            })
+    }
+
     pub async fn fetch_mods(
         &self,
         mods: &[&ModResolution],
@@ -195,18 +425,23 @@ impl ModStore {
         update: bool,
         tx: Option<Sender<FetchProgress>>,
     ) -> Result<PathBuf, ProviderError> {
-        self.get_provider(&res.url.0)?
+        let path = self
+            .get_provider(&res.url.0)?
             .fetch_mod(
                 res,
-                update,
+                update && !self.is_offline(),
                 self.cache.clone(),
                 &self.blob_cache.clone(),
                 tx,
             )
-            .await
+            .await;
+        self.check_offline(path, |resolutions| {
+            resolutions.last_cached_where(|m| m.resolution == *res)
+        })
     }
 
     pub async fn update_cache(&self) -> Result<(), ProviderError> {
+        self.init_pending_providers();
         let providers = self.providers.read().unwrap().clone();
         for (name, provider) in providers.iter() {
             info!("updating cache for {name} provider");
@@ -215,21 +450,221 @@ impl ModStore {
         Ok(())
     }
 
+    /// Every configured provider as a `ModSource` sharing the caches of the store.
+    pub fn sources(&self) -> Vec<Arc<dyn ModSource>> {
+        self.init_pending_providers();
+        self.providers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, provider)| {
+                Arc::new(ProviderSource::new(
+                    id,
+                    provider.clone(),
+                    self.providers.clone(),
+                    self.pending_providers.clone(),
+                    self.cache.clone(),
+                    self.blob_cache.clone(),
+                )) as Arc<dyn ModSource>
+            })
+            .collect()
+    }
+
+    /// Source of the provider handling `url`
+    pub fn source(&self, url: &str) -> Result<Arc<dyn ModSource>, ProviderError> {
+        let factory = Self::get_factory(url)?;
+        let provider = self.get_provider(url)?;
+        Ok(Arc::new(ProviderSource::new(
+            factory.id,
+            provider,
+            self.providers.clone(),
+            self.pending_providers.clone(),
+            self.cache.clone(),
+            self.blob_cache.clone(),
+        )))
+    }
+
+    /// Cached resolutions, invalidate entries to force them to be resolved again.
+    pub fn resolutions(&self) -> &Mutex<ResolutionCache> {
+        &self.resolutions
+    }
+
+    /// Changelog of the resolved mod, see `ModProvider::changelog`
+    pub async fn changelog(&self, info: &ModInfo) -> Result<Option<String>, ProviderError> {
+        self.get_provider(&info.spec.url)?
+            .changelog(info, self.cache.clone())
+            .await
+    }
+
     pub fn get_mod_info(&self, spec: &ModSpecification) -> Option<ModInfo> {
+        let spec = spec.without_version_constraint();
         self.get_provider(&spec.url)
             .ok()?
-            .get_mod_info(spec, self.cache.clone())
+            .get_mod_info(&spec, self.cache.clone())
     }
 
     pub fn is_pinned(&self, spec: &ModSpecification) -> bool {
+        let spec = spec.without_version_constraint();
         self.get_provider(&spec.url)
             .unwrap()
-            .is_pinned(spec, self.cache.clone())
+            .is_pinned(&spec, self.cache.clone())
     }
 
+    /// `None` if the provider of the mod is not configured or does not know the version
     pub fn get_version_name(&self, spec: &ModSpecification) -> Option<String> {
+        let spec = spec.without_version_constraint();
         self.get_provider(&spec.url)
+            .ok()?
+            .get_version_name(&spec, self.cache.clone())
+    }
+}
+
+/// Resolves each spec on its own so one failing mod does not abort the others. Results are in the
+/// order of `specs`. With `update` cached resolutions are not used.
+pub async fn resolve_batch(
+    specs: &[ModSpecification],
+    store: &ModStore,
+    concurrency: usize,
+    update: bool,
+) -> Vec<Result<ModInfo, ProviderError>> {
+    use futures::stream::{self, StreamExt};
+
+    stream::iter(
+        specs
+            .iter()
+            .map(|spec| store.resolve_mod(spec.clone(), update)),
+    )
+    .boxed() // without this the future becomes !Send https://github.com/rust-lang/rust/issues/104382
+    .buffered(concurrency.max(1))
+    .map(|result| result.map(|(_, info)| info))
+    .collect()
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolves `a` as deprecated in favor of `b` and `b` in favor of `a`
+    struct DeprecationCycleProvider;
+
+    #[async_trait::async_trait]
+    impl ModProvider for DeprecationCycleProvider {
+        async fn resolve_mod(
+            &self,
+            spec: &ModSpecification,
+            _update: bool,
+            _cache: ProviderCache,
+            _blob_cache: &BlobCache,
+        ) -> Result<ModResponse, ProviderError> {
+            let replaced_by = match spec.url.as_str() {
+                "https://example.com/a.pak" => "https://example.com/b.pak",
+                _ => "https://example.com/a.pak",
+            };
+            Ok(ModResponse::Resolve(Box::new(
+                ModInfo::builder()
+                    .provider(direct::DIRECT_URL_PROVIDER_ID)
+                    .name("cycle")
+                    .spec(spec.clone())
+                    .resolution(ModResolution {
+                        url: spec.url.as_str().into(),
+                        status: ResolvableStatus::Deprecated {
+                            replaced_by: ModSpecification::new(replaced_by.to_string()),
+                        },
+                    })
+                    .mod_type(ModType::Pak)
+                    .build()
+                    .unwrap(),
+            )))
+        }
+        async fn fetch_mod(
+            &self,
+            _url: &ModResolution,
+            _update: bool,
+            _cache: ProviderCache,
+            _blob_cache: &BlobCache,
+            _tx: Option<Sender<FetchProgress>>,
+        ) -> Result<PathBuf, ProviderError> {
+            unimplemented!()
+        }
+        async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+            Ok(())
+        }
+        async fn check(&self) -> Result<(), ProviderError> {
+            Ok(())
+        }
+        fn get_mod_info(&self, _spec: &ModSpecification, _cache: ProviderCache) -> Option<ModInfo> {
+            None
+        }
+        fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
+            false
+        }
+        fn get_version_name(
+            &self,
+            _spec: &ModSpecification,
+            _cache: ProviderCache,
+        ) -> Option<String> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_mod_fails_on_deprecation_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+        {
+            let mut providers = store.providers.write().unwrap();
+            for factory in ModStore::get_provider_factories() {
+                providers.insert(factory.id, Arc::new(DeprecationCycleProvider));
+            }
+        }
+
+        let result = store
+            .resolve_mod(
+                ModSpecification::new("https://example.com/a.pak".to_string()),
+                false,
+            )
+            .await;
+        assert!(
+            matches!(result, Err(ProviderError::TooManyRedirects { .. })),
+            "{result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_mod_reports_decoded_constraint() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+        let spec = ModSpecification::with_version_constraint("https://example.com/a.pak", ">=one");
+        match store.resolve_mod(spec, false).await {
+            Err(ProviderError::InvalidVersionConstraint { constraint, .. }) => {
+                assert_eq!(constraint, ">=one")
+            }
+            result => panic!("{result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_providers_with_parameters_are_created_on_first_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ModStore::new(dir.path(), &HashMap::new()).unwrap();
+        assert!(!store.providers.read().unwrap().contains_key("github"));
+        assert!(store
+            .pending_providers
+            .lock()
+            .unwrap()
+            .contains_key("github"));
+
+        // added providers must not be replaced by creating the pending one on first use
+        let factory = ModStore::get_provider_factories()
+            .find(|f| f.id == "github")
+            .unwrap();
+        store.add_provider(factory, &HashMap::new()).unwrap();
+        assert!(store.providers.read().unwrap().contains_key("github"));
+        assert!(!store
+            .pending_providers
+            .lock()
             .unwrap()
-            .get_version_name(spec, self.cache.clone())
+            .contains_key("github"));
     }
 }
@@ -0,0 +1,766 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::providers::*;
+
+inventory::submit! {
+    super::ProviderFactory {
+        id: NEXUS_PROVIDER_ID,
+        new: NexusModsProvider::new_provider,
+        can_provide: |url| re_mod().is_match(url),
+        schemes: &["nexusmods"],
+        parameters: &[
+            ProviderParameter {
+                id: "api_key",
+                name: "API Key",
+                description: "Nexus Mods personal API key",
+                link: Some("https://www.nexusmods.com/users/myaccount?tab=api"),
+                optional: false,
+            },
+        ]
+    }
+}
+
+const NEXUS_PROVIDER_ID: &str = "nexusmods";
+/// Only used for what the v2 API does not offer: download links, changelogs and validating keys
+const NEXUS_API_URL: &str = "https://api.nexusmods.com";
+const NEXUS_GRAPHQL_URL: &str = "https://api.nexusmods.com/v2/graphql";
+
+const MOD_QUERY: &str = "query($ids: [CompositeDomainWithIdInput!]!) { legacyModsByDomain(ids: $ids) { nodes { name author summary description endorsements gameId modCategory { categoryId } incompatibilities { modId gameDomainName } } } }";
+const MOD_FILES_QUERY: &str = "query($modId: ID!, $gameId: ID!) { modFiles(modId: $modId, gameId: $gameId) { fileId uri version category date sha256 } }";
+const SEARCH_QUERY: &str = "query($filter: ModsFilter, $count: Int) { mods(filter: $filter, count: $count) { nodes { modId gameId } } }";
+
+/// Mod category IDs of the Dragon Ball: Sparking! ZERO section, other categories are `Other`
+static CATEGORIES: &[(u32, ModCategory)] = &[
+    (1, ModCategory::Other),    // Miscellaneous
+    (2, ModCategory::Cosmetic), // Characters
+    (3, ModCategory::Cosmetic), // Stages
+    (4, ModCategory::Audio),    // Audio
+    (5, ModCategory::UI),       // User Interface
+    (6, ModCategory::Gameplay), // Gameplay
+    (7, ModCategory::Utility),  // Utilities
+    (8, ModCategory::Debug),    // Modders Resources
+];
+
+fn lookup_category(id: u32) -> ModCategory {
+    CATEGORIES
+        .iter()
+        .find_map(|(i, c)| (*i == id).then_some(*c))
+        .unwrap_or(ModCategory::Other)
+}
+
+/// Mods resolved per search, each requires fetching its files
+const SEARCH_LIMIT: usize = 10;
+
+/// Nexus Mods domain names for the short game names accepted in `nexusmods:` URLs
+const GAME_DOMAINS: &[(&str, &str)] = &[("dbsz", "dragonballsparkingzero")];
+
+static RE_MOD: OnceLock<regex::Regex> = OnceLock::new();
+fn re_mod() -> &'static regex::Regex {
+    RE_MOD.get_or_init(|| {
+        regex::Regex::new(r"^nexusmods:(?P<game>[^/]+)/(?P<mod_id>\d+)/(?P<file_id>\d+)$").unwrap()
+    })
+}
+
+struct NexusUrl<'a> {
+    domain: &'a str,
+    mod_id: u32,
+    file_id: u32,
+}
+
+fn parse_url(url: &str) -> Result<NexusUrl<'_>, ProviderError> {
+    let invalid = || ProviderError::InvalidUrl {
+        url: url.to_string(),
+    };
+    let captures = re_mod().captures(url).ok_or_else(invalid)?;
+    let game = captures.name("game").unwrap().as_str();
+    let domain = GAME_DOMAINS
+        .iter()
+        .find_map(|(name, domain)| name.eq_ignore_ascii_case(game).then_some(*domain))
+        .ok_or_else(invalid)?;
+    Ok(NexusUrl {
+        domain,
+        mod_id: captures["mod_id"].parse().map_err(|_| invalid())?,
+        file_id: captures["file_id"].parse().map_err(|_| invalid())?,
+    })
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NexusProviderCache {
+    /// Keyed by `cache_key` of the URL
+    mods: HashMap<String, NexusModInfo>,
+    url_blobs: HashMap<String, BlobRef>,
+}
+
+#[typetag::serde]
+impl ModProviderCache for NexusProviderCache {
+    fn new() -> Self {
+        Default::default()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NexusModInfo {
+    pub name: String,
+    pub author: String,
+    pub summary: Option<String>,
+    pub version: Option<String>,
+    pub file_name: String,
+    /// Changelog entries of `version`, only fetched when requested as it requires another request
+    #[serde(default)]
+    pub changelog: Option<String>,
+    /// Minimum game version stated in the mod description
+    #[serde(default)]
+    pub min_game_version: Option<SemverVersion>,
+    #[serde(default)]
+    pub endorsement_count: u64,
+    #[serde(default)]
+    pub category_id: Option<u32>,
+    /// Mods the author declared incompatible
+    #[serde(default)]
+    pub conflicts_with: Vec<ModSpecification>,
+    /// Hex encoded SHA-256 of the file, if Nexus published one
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+impl NexusModInfo {
+    fn to_mod_info(&self, spec: &ModSpecification) -> ModInfo {
+        ModInfo::builder()
+            .provider(NEXUS_PROVIDER_ID)
+            .name(self.name.clone())
+            .spec(spec.clone())
+            .resolution(ModResolution::resolvable(spec.url.as_str().into()))
+            .mod_type(if self.file_name.to_lowercase().ends_with(".pak") {
+                ModType::Pak
+            } else {
+                ModType::ModPlugin
+            })
+            .authors(vec![Author::new(self.author.clone())])
+            .homepage(parse_url(&spec.url).ok().map(|url| {
+                format!(
+                    "https://www.nexusmods.com/{}/mods/{}",
+                    url.domain, url.mod_id
+                )
+            }))
+            .changelog(self.changelog.clone())
+            .description(self.summary.clone())
+            .category(self.category_id.map(lookup_category))
+            .min_game_version(self.min_game_version.clone())
+            .conflicts_with(self.conflicts_with.clone())
+            .extra_metadata(serde_json::json!({ "endorsement_count": self.endorsement_count }))
+            .build()
+            .expect("required fields are set")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModQueryData {
+    legacy_mods_by_domain: Page<ModV2>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModV2 {
+    name: String,
+    author: String,
+    summary: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    endorsements: u64,
+    game_id: serde_json::Value,
+    mod_category: Option<CategoryV2>,
+    #[serde(default)]
+    incompatibilities: Vec<IncompatibilityV2>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CategoryV2 {
+    category_id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncompatibilityV2 {
+    mod_id: u32,
+    game_domain_name: String,
+}
+
+impl IncompatibilityV2 {
+    /// Specification matching every file of the mod, `None` for mods of unsupported games
+    fn to_spec(&self) -> Option<ModSpecification> {
+        let (game, _) = GAME_DOMAINS
+            .iter()
+            .find(|(_, domain)| *domain == self.game_domain_name)?;
+        Some(ModSpecification::new(format!(
+            "nexusmods:{game}/{}/",
+            self.mod_id
+        )))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModFilesQueryData {
+    mod_files: Vec<ModFileV2>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModFileV2 {
+    file_id: u32,
+    /// File name
+    uri: String,
+    version: Option<String>,
+    category: String,
+    /// Upload timestamp
+    #[serde(default)]
+    date: u64,
+    #[serde(default)]
+    sha256: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQueryData {
+    mods: Page<SearchNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchNode {
+    mod_id: u32,
+    game_id: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadLink {
+    #[serde(rename = "URI")]
+    uri: String,
+}
+
+pub struct NexusModsProvider {
+    client: reqwest::Client,
+    auth: AuthConfig,
+    limiter: Arc<RateLimiter>,
+    /// Time at which the rate limit resets if it has been exhausted
+    rate_limit_reset: std::sync::Mutex<Option<SystemTime>>,
+}
+
+impl NexusModsProvider {
+    pub fn new_provider(
+        parameters: &HashMap<String, String>,
+    ) -> Result<Arc<dyn ModProvider>, ProviderError> {
+        let api_key = parameters
+            .get("api_key")
+            .filter(|k| !k.is_empty())
+            .context(AuthRequiredSnafu {
+                provider: NEXUS_PROVIDER_ID,
+            })?;
+        Ok(Arc::new(Self::new(api_key)))
+    }
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: http_client(),
+            auth: AuthConfig::ApiKey(api_key.to_string()),
+            limiter: rate_limiter(NEXUS_PROVIDER_ID),
+            rate_limit_reset: Default::default(),
+        }
+    }
+
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, ProviderError> {
+        self.send(self.client.get(url), url).await
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        url: &str,
+    ) -> Result<reqwest::Response, ProviderError> {
+        let reset = *self.rate_limit_reset.lock().unwrap();
+        if let Some(wait) = reset.and_then(|r| r.duration_since(SystemTime::now()).ok()) {
+            warn!("Nexus Mods rate limit reached, waiting {}s", wait.as_secs());
+            tokio::time::sleep(wait).await;
+        }
+
+        self.limiter.acquire().await;
+        let response = self
+            .auth
+            .authenticate(request)
+            .send()
+            .await
+            .context(RequestFailedSnafu { url })?;
+        *self.rate_limit_reset.lock().unwrap() = rate_limit_reset(response.headers());
+
+        if [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN].contains(&response.status()) {
+            return AuthRequiredSnafu {
+                provider: NEXUS_PROVIDER_ID,
+            }
+            .fail();
+        }
+        response.error_for_status().context(ResponseSnafu { url })
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, ProviderError> {
+        self.get(url)
+            .await?
+            .json()
+            .await
+            .context(ResponseSnafu { url })
+    }
+
+    /// Sends `query` to the v2 GraphQL API, failing if it reports any error
+    async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, ProviderError> {
+        let body = serde_json::json!({ "query": query, "variables": variables });
+        let response: GraphQlResponse<T> = self
+            .send(
+                self.client.post(NEXUS_GRAPHQL_URL).json(&body),
+                NEXUS_GRAPHQL_URL,
+            )
+            .await?
+            .json()
+            .await
+            .context(ResponseSnafu {
+                url: NEXUS_GRAPHQL_URL,
+            })?;
+        match response.data {
+            Some(data) if response.errors.is_empty() => Ok(data),
+            _ => QuerySnafu {
+                url: NEXUS_GRAPHQL_URL,
+                message: response
+                    .errors
+                    .into_iter()
+                    .map(|e| e.message)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }
+            .fail(),
+        }
+    }
+
+    /// Mods of the game `domain` whose name contains `query`
+    async fn search_mods(
+        &self,
+        domain: &str,
+        query: &str,
+    ) -> Result<Vec<SearchNode>, ProviderError> {
+        let data: SearchQueryData = self
+            .graphql(
+                SEARCH_QUERY,
+                serde_json::json!({
+                    "filter": {
+                        "gameDomainName": [{ "value": domain, "op": "EQUALS" }],
+                        "name": [{ "value": format!("*{query}*"), "op": "WILDCARD" }],
+                    },
+                    "count": SEARCH_LIMIT,
+                }),
+            )
+            .await?;
+        Ok(data.mods.nodes)
+    }
+
+    async fn mod_files(
+        &self,
+        mod_id: u32,
+        game_id: &serde_json::Value,
+    ) -> Result<Vec<ModFileV2>, ProviderError> {
+        let data: ModFilesQueryData = self
+            .graphql(
+                MOD_FILES_QUERY,
+                serde_json::json!({ "modId": mod_id, "gameId": game_id }),
+            )
+            .await?;
+        Ok(data.mod_files)
+    }
+
+    /// Most recently uploaded file of the main category, `None` for mods without one
+    async fn main_file_id(
+        &self,
+        mod_id: u32,
+        game_id: &serde_json::Value,
+    ) -> Result<Option<u32>, ProviderError> {
+        Ok(self
+            .mod_files(mod_id, game_id)
+            .await?
+            .into_iter()
+            .filter(|f| f.category == "MAIN")
+            .max_by_key(|f| f.date)
+            .map(|f| f.file_id))
+    }
+
+    /// Cached mod info unless `update` is set
+    async fn mod_info(
+        &self,
+        url: &str,
+        update: bool,
+        cache: &ProviderCache,
+    ) -> Result<NexusModInfo, ProviderError> {
+        let cached = (!update)
+            .then(|| {
+                cache
+                    .read()
+                    .unwrap()
+                    .get::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+                    .and_then(|c| c.mods.get(&cache_key(url)).cloned())
+            })
+            .flatten();
+        if let Some(info) = cached {
+            return Ok(info);
+        }
+        let info = self.fetch_mod_info(url).await?;
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+            .mods
+            .insert(cache_key(url), info.clone());
+        Ok(info)
+    }
+
+    /// Changelog entries of `version` as a markdown list
+    async fn fetch_changelog(
+        &self,
+        url: &str,
+        version: &str,
+    ) -> Result<Option<String>, ProviderError> {
+        let NexusUrl { domain, mod_id, .. } = parse_url(url)?;
+        let mut changelogs: HashMap<String, Vec<String>> = self
+            .get_json(&format!(
+                "{NEXUS_API_URL}/v1/games/{domain}/mods/{mod_id}/changelogs.json"
+            ))
+            .await?;
+        Ok(changelogs
+            .remove(version)
+            .filter(|entries| !entries.is_empty())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| format!("- {entry}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }))
+    }
+
+    async fn fetch_mod_info(&self, url: &str) -> Result<NexusModInfo, ProviderError> {
+        let NexusUrl {
+            domain,
+            mod_id,
+            file_id,
+        } = parse_url(url)?;
+        let data: ModQueryData = self
+            .graphql(
+                MOD_QUERY,
+                serde_json::json!({ "ids": [{ "gameDomain": domain, "modId": mod_id }] }),
+            )
+            .await?;
+        let mod_response = data
+            .legacy_mods_by_domain
+            .nodes
+            .into_iter()
+            .next()
+            .context(NoAssociatedModfileSnafu { url })?;
+        let file_response = self
+            .mod_files(mod_id, &mod_response.game_id)
+            .await?
+            .into_iter()
+            .find(|f| f.file_id == file_id)
+            .context(NoAssociatedModfileSnafu { url })?;
+        Ok(NexusModInfo {
+            name: mod_response.name,
+            author: mod_response.author,
+            summary: mod_response.summary,
+            version: file_response.version,
+            file_name: file_response.uri,
+            changelog: None,
+            min_game_version: mod_response
+                .description
+                .as_deref()
+                .and_then(game_version_from_notes),
+            endorsement_count: mod_response.endorsements,
+            category_id: mod_response.mod_category.map(|c| c.category_id),
+            conflicts_with: mod_response
+                .incompatibilities
+                .iter()
+                .filter_map(IncompatibilityV2::to_spec)
+                .collect(),
+            sha256: file_response.sha256,
+        })
+    }
+}
+
+/// Smaller of the remaining hourly and daily requests
+fn api_quota_remaining(headers: &HeaderMap) -> Option<u32> {
+    ["x-rl-hourly-remaining", "x-rl-daily-remaining"]
+        .iter()
+        .filter_map(|name| headers.get(*name)?.to_str().ok()?.trim().parse().ok())
+        .min()
+}
+
+/// Returns the reset time if the `X-RL-Remaining` (or hourly) rate limit header has reached zero.
+fn rate_limit_reset(headers: &HeaderMap) -> Option<SystemTime> {
+    let header = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| headers.get(*name))
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+    };
+    let remaining = header(&["x-rl-remaining", "x-rl-hourly-remaining"])?;
+    if remaining.parse::<u64>().ok()? > 0 {
+        return None;
+    }
+    let reset = header(&["x-rl-reset", "x-rl-hourly-reset"])?;
+    if let Ok(timestamp) = reset.parse::<u64>() {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp))
+    } else {
+        chrono::DateTime::parse_from_str(&reset, "%Y-%m-%d %H:%M:%S %z")
+            .ok()
+            .map(SystemTime::from)
+    }
+}
+
+#[async_trait::async_trait]
+impl ModProvider for NexusModsProvider {
+    async fn resolve_mod(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+        cache: ProviderCache,
+        _blob_cache: &BlobCache,
+    ) -> Result<ModResponse, ProviderError> {
+        let info = self.mod_info(&spec.url, update, &cache).await?;
+        Ok(ModResponse::Resolve(Box::new(info.to_mod_info(spec))))
+    }
+
+    async fn fetch_mod(
+        &self,
+        res: &ModResolution,
+        update: bool,
+        cache: ProviderCache,
+        blob_cache: &BlobCache,
+        tx: Option<Sender<FetchProgress>>,
+    ) -> Result<PathBuf, ProviderError> {
+        let url = &res.url;
+        if let Some(path) = if update {
+            None
+        } else {
+            cache
+                .read()
+                .unwrap()
+                .get::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+                .and_then(|c| c.url_blobs.get(&url.0))
+                .and_then(|r| blob_cache.get_path(r))
+        } {
+            if let Some(tx) = tx {
+                tx.send(FetchProgress::Complete {
+                    resolution: res.clone(),
+                })
+                .await
+                .unwrap();
+            }
+            return Ok(path);
+        }
+
+        let NexusUrl {
+            domain,
+            mod_id,
+            file_id,
+        } = parse_url(&url.0)?;
+        let links: Vec<DownloadLink> = self
+            .get_json(&format!(
+                "{NEXUS_API_URL}/v1/games/{domain}/mods/{mod_id}/files/{file_id}/download_link.json"
+            ))
+            .await?;
+        let download_url = links
+            .into_iter()
+            .next()
+            .context(NoAssociatedModfileSnafu { url: &url.0 })?
+            .uri;
+        let sha256 = cache
+            .read()
+            .unwrap()
+            .get::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+            .and_then(|c| {
+                c.mods
+                    .get(&cache_key(&url.0))?
+                    .sha256
+                    .as_deref()
+                    .and_then(parse_sha256)
+            });
+
+        info!("downloading mod {url:?}...");
+        self.limiter.acquire().await;
+        let blob = download_blob(
+            self.client.get(&download_url),
+            &download_url,
+            res,
+            blob_cache,
+            sha256,
+            tx.as_ref(),
+        )
+        .await?;
+        let path = blob_cache.get_path(&blob).unwrap();
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+            .url_blobs
+            .insert(url.0.to_owned(), blob);
+
+        if let Some(tx) = tx {
+            tx.send(FetchProgress::Complete {
+                resolution: res.clone(),
+            })
+            .await
+            .unwrap();
+        }
+        Ok(path)
+    }
+
+    async fn update_cache(&self, _cache: ProviderCache) -> Result<(), ProviderError> {
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        cache: ProviderCache,
+    ) -> Result<Vec<ModInfo>, ProviderError> {
+        let mut mods = vec![];
+        for (game, domain) in GAME_DOMAINS {
+            for node in self.search_mods(domain, query).await? {
+                // mods without a main file cannot be installed
+                let Some(file_id) = self.main_file_id(node.mod_id, &node.game_id).await? else {
+                    continue;
+                };
+                let spec =
+                    ModSpecification::new(format!("nexusmods:{game}/{}/{file_id}", node.mod_id));
+                let info = self.mod_info(&spec.url, false, &cache).await?;
+                mods.push(info.to_mod_info(&spec));
+            }
+        }
+        Ok(mods)
+    }
+
+    async fn check(&self) -> Result<(), ProviderError> {
+        self.get(&format!("{NEXUS_API_URL}/v1/users/validate.json"))
+            .await
+            .map(|_| ())
+    }
+
+    /// Sends the request directly instead of through `get` so an exhausted quota is reported
+    /// instead of waited out
+    async fn health_check(&self) -> Result<ProviderHealth, ProviderError> {
+        let url = format!("{NEXUS_API_URL}/v1/users/validate.json");
+        self.limiter.acquire().await;
+        let start = Instant::now();
+        let response = self
+            .auth
+            .authenticate(self.client.get(&url))
+            .send()
+            .await
+            .context(RequestFailedSnafu { url: &url });
+        let remaining = response
+            .as_ref()
+            .ok()
+            .and_then(|r| api_quota_remaining(r.headers()));
+        let result =
+            response.and_then(|r| r.error_for_status().context(ResponseSnafu { url: &url }));
+        Ok(ProviderHealth::from_result(start, remaining, result))
+    }
+
+    /// Fetched once and kept with the cached mod info
+    async fn changelog(
+        &self,
+        info: &ModInfo,
+        cache: ProviderCache,
+    ) -> Result<Option<String>, ProviderError> {
+        let url = &info.spec.url;
+        let mut nexus_info = self.mod_info(url, false, &cache).await?;
+        if nexus_info.changelog.is_some() {
+            return Ok(nexus_info.changelog);
+        }
+        let Some(version) = &nexus_info.version else {
+            return Ok(None);
+        };
+        // mods without a changelog are common, so a failure here is not an error
+        nexus_info.changelog = self
+            .fetch_changelog(url, version)
+            .await
+            .unwrap_or_else(|e| {
+                debug!("no changelog for {url}: {e}");
+                None
+            });
+        cache
+            .write()
+            .unwrap()
+            .get_mut::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+            .mods
+            .insert(cache_key(url), nexus_info.clone());
+        Ok(nexus_info.changelog)
+    }
+
+    fn get_mod_info(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<ModInfo> {
+        cache
+            .read()
+            .unwrap()
+            .get::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+            .and_then(|c| c.mods.get(&cache_key(&spec.url)))
+            .map(|info| info.to_mod_info(spec))
+    }
+
+    fn is_pinned(&self, _spec: &ModSpecification, _cache: ProviderCache) -> bool {
+        true
+    }
+
+    fn get_version_name(&self, spec: &ModSpecification, cache: ProviderCache) -> Option<String> {
+        cache
+            .read()
+            .unwrap()
+            .get::<NexusProviderCache>(NEXUS_PROVIDER_ID)
+            .and_then(|c| c.mods.get(&cache_key(&spec.url)))
+            .and_then(|info| info.version.clone())
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use crate::providers::*;
+
+/// Wraps a source so mods resolved before can be used without a connection. Cached resolutions
+/// are returned regardless of their age and network errors are reported as
+/// `ProviderError::Offline`.
+pub struct OfflineModeProvider {
+    inner: Arc<dyn ModSource>,
+    cache: ResolutionCache,
+}
+
+impl OfflineModeProvider {
+    /// Reads the resolutions cached by the `ModStore` using `cache_path`
+    pub fn new<P: AsRef<Path>>(inner: Arc<dyn ModSource>, cache_path: P) -> Self {
+        Self {
+            inner,
+            cache: ResolutionCache::load(
+                cache_path.as_ref().join("resolutions.json"),
+                DEFAULT_RESOLUTION_TTL,
+            ),
+        }
+    }
+
+    /// Reports network errors as offline, with the time `spec` was last resolved if it was
+    fn check<T>(
+        &self,
+        spec: Option<&ModSpecification>,
+        result: Result<T, ProviderError>,
+    ) -> Result<T, ProviderError> {
+        match result {
+            Err(e) if e.is_network_error() => OfflineSnafu {
+                last_seen: spec.and_then(|spec| self.cache.cached_at(spec)),
+            }
+            .fail(),
+            result => result,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ModSource for OfflineModeProvider {
+    fn id(&self) -> &'static str {
+        self.inner.id()
+    }
+
+    async fn resolve(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError> {
+        if let Some(info) = self.cache.get_ignoring_ttl(spec) {
+            return Ok(info.clone());
+        }
+        self.check(Some(spec), self.inner.resolve(spec).await)
+    }
+
+    /// Not answered from the cache, as cached resolutions may be outdated
+    async fn resolve_latest(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError> {
+        self.check(Some(spec), self.inner.resolve_latest(spec).await)
+    }
+
+    fn version_name(&self, info: &ModInfo) -> Option<String> {
+        self.inner.version_name(info)
+    }
+
+    async fn download(&self, info: &ModInfo, dest: &Path) -> Result<(), ProviderError> {
+        self.check(Some(&info.spec), self.inner.download(info, dest).await)
+    }
+
+    /// Falls back to the names of cached mods if the provider is unreachable
+    async fn search(&self, query: &str) -> Result<Vec<ModInfo>, ProviderError> {
+        match self.check(None, self.inner.search(query).await) {
+            Err(ProviderError::Offline { .. }) => {
+                let query = query.to_lowercase();
+                Ok(self
+                    .cache
+                    .mods()
+                    .filter(|m| m.provider == self.id() && m.name.to_lowercase().contains(&query))
+                    .cloned()
+                    .collect())
+            }
+            result => result,
+        }
+    }
+
+    /// Falls back to the authors of cached mods if the provider is unreachable
+    async fn search_by_author(&self, author: &str) -> Result<Vec<ModInfo>, ProviderError> {
+        match self.check(None, self.inner.search_by_author(author).await) {
+            Err(ProviderError::Offline { .. }) => Ok(self
+                .cache
+                .mods()
+                .filter(|m| {
+                    m.provider == self.id()
+                        && m.authors
+                            .iter()
+                            .any(|a| a.name.eq_ignore_ascii_case(author))
+                })
+                .cloned()
+                .collect()),
+            result => result,
+        }
+    }
+
+    async fn health_check(&self) -> Result<ProviderHealth, ProviderError> {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_uses_expired_resolutions() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = ModSpecification::new("github:owner/repo".to_string());
+        let info = ModInfo::builder()
+            .provider("github")
+            .name("repo")
+            .spec(spec.clone())
+            .resolution(ModResolution::resolvable("github:owner/repo@v1".into()))
+            .mod_type(ModType::Pak)
+            .build()
+            .unwrap();
+        let mut cache: ResolutionCache =
+            ResolutionCache::load(dir.path().join("resolutions.json"), Default::default());
+        cache.insert(spec.clone(), info.clone()).unwrap();
+
+        // the mock panics if the provider is contacted
+        let offline = OfflineModeProvider::new(Arc::new(MockModSource::new()), dir.path());
+        assert_eq!(offline.resolve(&spec).await.unwrap(), info);
+    }
+}
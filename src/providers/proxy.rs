@@ -0,0 +1,207 @@
+//! Proxy the HTTP clients of providers send their requests through.
+
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::providers::*;
+
+/// Port of SOCKS5 proxies whose url does not specify one
+const DEFAULT_SOCKS_PORT: u16 = 1080;
+
+/// Keychain entry of the proxy password, see `store_credentials`
+const PROXY_CREDENTIALS: &str = "proxy";
+
+/// HTTP or SOCKS5 proxy, e.g. `http://proxy.example.org:3128` or `socks5://localhost:1080`.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Stored in the OS keychain, passwords found in the config file are moved there
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// Hosts connected to directly, in the format of `NO_PROXY`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+/// The password is redacted so it does not end up in logs
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("no_proxy", &self.no_proxy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProxyConfig {
+    /// Proxy set by the `HTTPS_PROXY` and `NO_PROXY` environment variables
+    pub fn from_env() -> Option<Self> {
+        Self::from_env_with(|name| std::env::var(name).ok())
+    }
+
+    /// `from_env` reading the environment variables through `lookup`
+    fn from_env_with(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |name: &str| {
+            lookup(name)
+                .or_else(|| lookup(&name.to_lowercase()))
+                .filter(|v| !v.is_empty())
+        };
+        Some(Self {
+            url: var("HTTPS_PROXY")?,
+            username: None,
+            password: None,
+            no_proxy: var("NO_PROXY")
+                .map(|hosts| hosts.split(',').map(|h| h.trim().to_string()).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    fn to_proxy(&self) -> Result<reqwest::Proxy, reqwest::Error> {
+        let mut proxy = reqwest::Proxy::all(&self.url)?;
+        if let Some(username) = &self.username {
+            proxy = proxy.basic_auth(username, self.password.as_deref().unwrap_or_default());
+        }
+        Ok(proxy.no_proxy(reqwest::NoProxy::from_string(&self.no_proxy.join(","))))
+    }
+
+    /// Host and port connections to the proxy are made to
+    fn address(&self) -> Option<(String, u16)> {
+        let url = url::Url::parse(&self.url).ok()?;
+        let port = url.port_or_known_default().or_else(|| {
+            url.scheme()
+                .starts_with("socks")
+                .then_some(DEFAULT_SOCKS_PORT)
+        })?;
+        Some((url.host_str()?.to_string(), port))
+    }
+}
+
+fn proxy() -> &'static RwLock<Option<ProxyConfig>> {
+    static PROXY: RwLock<Option<ProxyConfig>> = RwLock::new(None);
+    &PROXY
+}
+
+/// Sets the proxy of clients created afterwards, falling back to the environment if `config` is
+/// `None`. Providers created before keep their proxy.
+pub fn configure_proxy(config: Option<&ProxyConfig>) {
+    let config = config.cloned().map(|mut config| {
+        if let Some(password) = &config.password {
+            let credentials = HashMap::from([("password".to_string(), password.clone())]);
+            if let Err(e) = store_credentials(PROXY_CREDENTIALS, &credentials) {
+                warn!("proxy password will not be remembered: {e}");
+            }
+        } else if config.username.is_some() {
+            config.password = load_credentials(PROXY_CREDENTIALS).remove("password");
+        }
+        config
+    });
+    *proxy().write().unwrap() = config.or_else(ProxyConfig::from_env);
+}
+
+/// The configured proxy, if any
+pub fn active_proxy() -> Option<ProxyConfig> {
+    proxy().read().unwrap().clone()
+}
+
+/// Builder of the HTTP client shared by providers, sending requests through the configured proxy.
+/// An invalid proxy is ignored with a warning.
+pub fn http_client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match active_proxy().map(|p| p.to_proxy()) {
+        Some(Ok(proxy)) => builder.proxy(proxy),
+        Some(Err(e)) => {
+            warn!("ignoring invalid proxy: {e}");
+            builder
+        }
+        None => builder,
+    }
+}
+
+pub fn http_client() -> reqwest::Client {
+    http_client_builder()
+        .build()
+        .expect("HTTP client is configured correctly")
+}
+
+/// Connects to the proxy within `timeout`, returning how long connecting took.
+pub async fn check_proxy(proxy: &ProxyConfig, timeout: Duration) -> std::io::Result<Duration> {
+    use std::io::{Error, ErrorKind};
+
+    let (host, port) = proxy
+        .address()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid proxy url"))?;
+    let start = Instant::now();
+    tokio::time::timeout(
+        timeout,
+        tokio::net::TcpStream::connect((host.as_str(), port)),
+    )
+    .await
+    .map_err(|_| Error::new(ErrorKind::TimedOut, "connection timed out"))??;
+    Ok(start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(url: &str) -> ProxyConfig {
+        ProxyConfig {
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_address() {
+        assert_eq!(
+            proxy("http://proxy.example.org:3128").address(),
+            Some(("proxy.example.org".to_string(), 3128))
+        );
+        assert_eq!(
+            proxy("https://proxy.example.org").address(),
+            Some(("proxy.example.org".to_string(), 443))
+        );
+        assert_eq!(
+            proxy("socks5://localhost").address(),
+            Some(("localhost".to_string(), DEFAULT_SOCKS_PORT))
+        );
+        assert_eq!(proxy("proxy.example.org").address(), None);
+    }
+
+    fn from_env(vars: &[(&str, &str)]) -> Option<ProxyConfig> {
+        ProxyConfig::from_env_with(|name| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_from_env() {
+        assert_eq!(from_env(&[]), None);
+        assert_eq!(
+            from_env(&[
+                ("https_proxy", "http://proxy.example.org:3128"),
+                ("NO_PROXY", "localhost, .example.com"),
+            ]),
+            Some(ProxyConfig {
+                url: "http://proxy.example.org:3128".to_string(),
+                username: None,
+                password: None,
+                no_proxy: vec!["localhost".to_string(), ".example.com".to_string()],
+            })
+        );
+
+        assert_eq!(from_env(&[("https_proxy", "")]), None);
+        assert_eq!(
+            from_env(&[("HTTPS_PROXY", "socks5://localhost")]).map(|p| p.url),
+            Some("socks5://localhost".to_string())
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Client side rate limiting of provider requests.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::providers::*;
+
+/// Requests per second of providers without a configured limit
+pub const DEFAULT_PERMITS_PER_SECOND: f64 = 10.0;
+
+/// Token bucket holding up to `burst` permits, refilled with `permits_per_second` permits. The
+/// bucket is refilled when a permit is acquired, according to the time passed since the last
+/// acquisition.
+#[derive(Debug)]
+pub struct RateLimiter {
+    permits_per_second: f64,
+    burst: u32,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    permits: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    /// Takes a permit, or returns how long to wait until one is available.
+    fn try_take(
+        &mut self,
+        now: Instant,
+        permits_per_second: f64,
+        burst: u32,
+    ) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.permits =
+            (self.permits + elapsed.as_secs_f64() * permits_per_second).min(burst as f64);
+        self.refilled_at = now;
+        if self.permits >= 1.0 {
+            self.permits -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.permits) / permits_per_second,
+            ))
+        }
+    }
+}
+
+impl RateLimiter {
+    /// The bucket starts full and holds as many permits as are refilled in one second
+    pub fn new(permits_per_second: f64) -> Self {
+        let burst = permits_per_second.ceil().max(1.0) as u32;
+        Self {
+            permits_per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                permits: burst as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn permits_per_second(&self) -> f64 {
+        self.permits_per_second
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// Waits until a request may be sent.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                match bucket.try_take(Instant::now(), self.permits_per_second, self.burst) {
+                    Ok(()) => return,
+                    Err(wait) => wait,
+                }
+            };
+            debug!("rate limited, waiting {} ms", wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+type RateLimits = Mutex<(HashMap<String, f64>, HashMap<String, Arc<RateLimiter>>)>;
+
+fn rate_limits() -> &'static RateLimits {
+    static RATE_LIMITS: OnceLock<RateLimits> = OnceLock::new();
+    RATE_LIMITS.get_or_init(Default::default)
+}
+
+/// Sets the requests per second of providers by id, replacing previously configured limits.
+/// Providers created afterwards pick up the new limits.
+pub fn configure_rate_limits(limits: &HashMap<String, f64>) {
+    let mut lock = rate_limits().lock().unwrap();
+    lock.0 = limits
+        .iter()
+        .filter(|(_, limit)| **limit > 0.0)
+        .map(|(id, limit)| (id.clone(), *limit))
+        .collect();
+    lock.1.clear();
+}
+
+/// Rate limiter shared by every instance of the provider
+pub fn rate_limiter(provider: &str) -> Arc<RateLimiter> {
+    let mut lock = rate_limits().lock().unwrap();
+    let limit = lock
+        .0
+        .get(provider)
+        .copied()
+        .unwrap_or(DEFAULT_PERMITS_PER_SECOND);
+    lock.1
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(RateLimiter::new(limit)))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_lazily() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            permits: 2.0,
+            refilled_at: start,
+        };
+        assert_eq!(bucket.try_take(start, 2.0, 2), Ok(()));
+        assert_eq!(bucket.try_take(start, 2.0, 2), Ok(()));
+        assert_eq!(
+            bucket.try_take(start, 2.0, 2),
+            Err(Duration::from_millis(500))
+        );
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.try_take(later, 2.0, 2), Ok(()));
+        assert!(bucket.try_take(later, 2.0, 2).is_err());
+    }
+
+    #[test]
+    fn test_bucket_is_capped_at_burst() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            permits: 0.0,
+            refilled_at: start,
+        };
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(later, 1.0, 3), Ok(()));
+        }
+        assert!(bucket.try_take(later, 1.0, 3).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter = RateLimiter::new(20.0);
+        let start = Instant::now();
+        for _ in 0..limiter.burst() {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(40));
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
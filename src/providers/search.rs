@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+use crate::providers::*;
+
+/// Minimum Jaro-Winkler similarity of names returned by `fuzzy_search`
+const FUZZY_THRESHOLD: f64 = 0.6;
+
+/// How long the mods found for an author are reused
+pub const AUTHOR_SEARCH_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Relevance to the query, higher is better
+    pub score: f32,
+    pub info: ModInfo,
+    pub provider_name: &'static str,
+}
+
+/// Scores a result by how well its name matches the query: 1 for an exact match, then prefix
+/// and substring matches. `rank` is the position in the provider's results and breaks ties.
+fn score(query: &str, name: &str, rank: usize) -> f32 {
+    let query = query.trim().to_lowercase();
+    let name = name.to_lowercase();
+    let base = if name == query {
+        1.0
+    } else if name.starts_with(&query) {
+        0.75
+    } else if name.contains(&query) {
+        0.5
+    } else {
+        0.25
+    };
+    base - (rank as f32 * 0.001).min(0.2)
+}
+
+/// Mods whose name is similar to the query ignoring case, most similar first, paired with the
+/// Jaro-Winkler similarity of their name.
+pub fn fuzzy_search<'a>(query: &str, mods: &'a [ModInfo]) -> Vec<(&'a ModInfo, f32)> {
+    let query = query.trim().to_lowercase();
+    let mut found = mods
+        .iter()
+        .map(|m| (m, strsim::jaro_winkler(&query, &m.name.to_lowercase())))
+        .filter(|(_, similarity)| *similarity > FUZZY_THRESHOLD)
+        .map(|(m, similarity)| (m, similarity as f32))
+        .collect::<Vec<_>>();
+    found.sort_by(|a, b| b.1.total_cmp(&a.1));
+    found
+}
+
+/// Searches every source at once and merges the results. Mods with the same normalized name are
+/// only returned once, with the highest score. Sources failing to search are skipped.
+pub async fn search_all(
+    query: &str,
+    providers: &[Arc<dyn ModSource>],
+    limit: usize,
+) -> Vec<SearchResult> {
+    let mut tasks = JoinSet::new();
+    for source in providers {
+        let source = source.clone();
+        let query = query.to_string();
+        tasks.spawn(async move {
+            let start = Instant::now();
+            let result = source.search(&query).await;
+            debug!(
+                "searching {} took {}ms",
+                source.id(),
+                start.elapsed().as_millis()
+            );
+            (source.id(), query, result)
+        });
+    }
+
+    let mut results: HashMap<ModIdentifier, SearchResult> = HashMap::new();
+    while let Some(task) = tasks.join_next().await {
+        let (provider_name, query, found) = match task {
+            Ok((id, query, Ok(found))) => (id, query, found),
+            Ok((id, _, Err(e))) => {
+                warn!("search of {id} failed: {e}");
+                continue;
+            }
+            Err(e) => {
+                warn!("search task failed: {e}");
+                continue;
+            }
+        };
+        for (rank, info) in found.into_iter().enumerate() {
+            let result = SearchResult {
+                score: score(&query, &info.name, rank),
+                info,
+                provider_name,
+            };
+            let key = ModIdentifier::normalize(&result.info.name);
+            if results.get(&key).is_none_or(|r| r.score < result.score) {
+                results.insert(key, result);
+            }
+        }
+    }
+
+    let mut results = results.into_values().collect::<Vec<_>>();
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit);
+    results
+}
+
+/// Mods of `author` published through the source. Results are cached for `AUTHOR_SEARCH_TTL` in
+/// `cache_path`, the cache directory of the `ModStore`.
+pub async fn search_by_author<P: AsRef<Path>>(
+    author: &str,
+    provider: &dyn ModSource,
+    cache_path: P,
+) -> Result<Vec<ModInfo>, ProviderError> {
+    let mut cache: ResolutionCache<String, Vec<ModInfo>> = ResolutionCache::load(
+        cache_path.as_ref().join("author_searches.json"),
+        AUTHOR_SEARCH_TTL,
+    );
+    let key = format!("{}/{}", provider.id(), author.to_lowercase());
+    if let Some(mods) = cache.get(&key) {
+        return Ok(mods.clone());
+    }
+
+    let mut seen = HashSet::new();
+    let mut mods = provider.search_by_author(author).await?;
+    mods.retain(|m| seen.insert(ModIdentifier::normalize(&m.name)));
+    if let Err(e) = cache.insert(key, mods.clone()) {
+        warn!("failed to cache mods of {author}: {e}");
+    }
+    Ok(mods)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mod_info(provider: &'static str, name: &str) -> ModInfo {
+        ModInfo::builder()
+            .provider(provider)
+            .name(name)
+            .spec(ModSpecification::new(format!("{provider}:{name}")))
+            .resolution(ModResolution::resolvable(
+                format!("{provider}:{name}").into(),
+            ))
+            .mod_type(ModType::Pak)
+            .build()
+            .unwrap()
+    }
+
+    /// Source finding `mods`, or failing to search if `None`
+    fn source(id: &'static str, mods: Option<Vec<ModInfo>>) -> Arc<dyn ModSource> {
+        let mut source = MockModSource::new();
+        source.expect_id().return_const(id);
+        source.expect_search().returning(move |query| {
+            mods.clone()
+                .context(NoModsForNameIdSnafu { name_id: query })
+        });
+        Arc::new(source)
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let mods = [
+            mod_info("github", "Camera Shake"),
+            mod_info("github", "Unrelated"),
+            mod_info("github", "Camrea"),
+            mod_info("github", "Camera"),
+        ];
+        let found = fuzzy_search(" CAMERA", &mods)
+            .into_iter()
+            .map(|(m, _)| m.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["Camera", "Camrea", "Camera Shake"]);
+        assert!(fuzzy_search("xyz", &mods).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_all_deduplicates_by_name() {
+        let sources = [
+            source(
+                "github",
+                Some(vec![
+                    mod_info("github", "Better Camera"),
+                    mod_info("github", "Other"),
+                ]),
+            ),
+            source(
+                "nexusmods",
+                Some(vec![mod_info("nexusmods", "better-camera")]),
+            ),
+            source("gamebanana", None),
+        ];
+        let results = search_all("better camera", &sources, 10).await;
+        let found = results
+            .iter()
+            .map(|r| (r.provider_name, r.info.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [("github", "Better Camera"), ("github", "Other")]);
+
+        assert_eq!(search_all("better camera", &sources[..1], 1).await.len(), 1);
+    }
+}
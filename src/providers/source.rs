@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use crate::providers::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderStatus {
+    Healthy,
+    /// Reachable but not usable without restrictions, e.g. because the API quota is exhausted
+    Degraded(String),
+    /// Unreachable
+    Down,
+}
+
+impl std::fmt::Display for ProviderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderStatus::Healthy => write!(f, "healthy"),
+            ProviderStatus::Degraded(reason) => write!(f, "degraded: {reason}"),
+            ProviderStatus::Down => write!(f, "down"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderHealth {
+    /// Duration of the request checking the provider
+    pub latency_ms: u64,
+    /// Requests left before the provider starts rejecting them, if it reports a quota
+    pub api_quota_remaining: Option<u32>,
+    pub status: ProviderStatus,
+}
+
+impl ProviderHealth {
+    /// Health of a provider whose check was started at `start` and returned `result`. Network
+    /// errors mean the provider is down, any other error that it is degraded.
+    pub fn from_result<T>(
+        start: Instant,
+        api_quota_remaining: Option<u32>,
+        result: Result<T, ProviderError>,
+    ) -> Self {
+        let status = match result {
+            Ok(_) if api_quota_remaining == Some(0) => {
+                ProviderStatus::Degraded("API quota exhausted".to_string())
+            }
+            Ok(_) => ProviderStatus::Healthy,
+            Err(e) if e.is_network_error() => ProviderStatus::Down,
+            Err(e) => ProviderStatus::Degraded(e.to_string()),
+        };
+        Self {
+            latency_ms: start.elapsed().as_millis() as u64,
+            api_quota_remaining,
+            status,
+        }
+    }
+}
+
+/// Repository of mods that can resolve, download and search mods without the caller managing
+/// provider caches, so operations spanning several providers can treat them uniformly. Named
+/// `ModSource` as `ModStore` is the registry owning every provider.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait ModSource: Send + Sync {
+    /// ID of the provider backing the source
+    fn id(&self) -> &'static str;
+    async fn resolve(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError>;
+    /// Like `resolve` but ignores responses cached by the provider, to find new versions
+    async fn resolve_latest(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError>;
+    /// Name of the version the mod was resolved to, `None` if the provider does not know it
+    fn version_name(&self, info: &ModInfo) -> Option<String>;
+    /// Downloads the resolved mod to the file `dest`
+    async fn download(&self, info: &ModInfo, dest: &Path) -> Result<(), ProviderError>;
+    async fn search(&self, query: &str) -> Result<Vec<ModInfo>, ProviderError>;
+    async fn search_by_author(&self, author: &str) -> Result<Vec<ModInfo>, ProviderError>;
+    async fn health_check(&self) -> Result<ProviderHealth, ProviderError>;
+}
+
+/// Source backed by a single provider sharing the caches of the `ModStore` it was created by.
+/// Redirects to mods of other providers are resolved by the providers of the store.
+pub struct ProviderSource {
+    id: &'static str,
+    provider: Arc<dyn ModProvider>,
+    providers: Providers,
+    pending_providers: PendingProviders,
+    cache: ProviderCache,
+    blob_cache: BlobCache,
+}
+
+impl ProviderSource {
+    pub(crate) fn new(
+        id: &'static str,
+        provider: Arc<dyn ModProvider>,
+        providers: Providers,
+        pending_providers: PendingProviders,
+        cache: ProviderCache,
+        blob_cache: BlobCache,
+    ) -> Self {
+        Self {
+            id,
+            provider,
+            providers,
+            pending_providers,
+            cache,
+            blob_cache,
+        }
+    }
+
+    async fn resolve_with(
+        &self,
+        spec: &ModSpecification,
+        update: bool,
+    ) -> Result<ModInfo, ProviderError> {
+        let mut response = self
+            .provider
+            .resolve_mod(spec, update, self.cache.clone(), &self.blob_cache)
+            .await?;
+        for _ in 0..MAX_REDIRECTS {
+            let redirected = match response {
+                ModResponse::Resolve(info) => return Ok(*info),
+                ModResponse::Redirect(redirected) => redirected,
+            };
+            response = ModStore::get_provider_in(
+                &self.providers,
+                &self.pending_providers,
+                &redirected.url,
+            )?
+            .resolve_mod(&redirected, update, self.cache.clone(), &self.blob_cache)
+            .await?;
+        }
+        match response {
+            ModResponse::Resolve(info) => Ok(*info),
+            ModResponse::Redirect(_) => TooManyRedirectsSnafu { url: &spec.url }.fail(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ModSource for ProviderSource {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    async fn resolve(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError> {
+        self.resolve_with(spec, false).await
+    }
+
+    async fn resolve_latest(&self, spec: &ModSpecification) -> Result<ModInfo, ProviderError> {
+        self.resolve_with(spec, true).await
+    }
+
+    fn version_name(&self, info: &ModInfo) -> Option<String> {
+        self.provider
+            .get_version_name(&info.spec, self.cache.clone())
+    }
+
+    async fn download(&self, info: &ModInfo, dest: &Path) -> Result<(), ProviderError> {
+        let path = self
+            .provider
+            .fetch_mod(
+                &info.resolution,
+                false,
+                self.cache.clone(),
+                &self.blob_cache,
+                None,
+            )
+            .await?;
+        tokio::fs::copy(&path, dest).await.context(BufferIoSnafu {
+            url: &info.resolution.url.0,
+        })?;
+        Ok(())
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<ModInfo>, ProviderError> {
+        self.provider.search(query, self.cache.clone()).await
+    }
+
+    async fn search_by_author(&self, author: &str) -> Result<Vec<ModInfo>, ProviderError> {
+        self.provider
+            .search_by_author(author, self.cache.clone())
+            .await
+    }
+
+    async fn health_check(&self) -> Result<ProviderHealth, ProviderError> {
+        self.provider.health_check().await
+    }
+}
@@ -0,0 +1,197 @@
+//! Checking installed mods for newer versions without installing them.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::providers::*;
+
+/// Number of mods checked at once
+const CHECK_CONCURRENCY: usize = 5;
+
+/// Version of an installed mod, read from the version name recorded at installation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledVersion {
+    pub name: String,
+    pub version: SemverVersion,
+}
+
+impl InstalledVersion {
+    /// `None` if the name is not a version, see `SemverVersion::parse`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(Self {
+            name: name.to_string(),
+            version: SemverVersion::parse(name).ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModUpdateAvailable {
+    pub current: ModInfo,
+    pub latest: ModInfo,
+    pub current_version: SemverVersion,
+    pub latest_version: SemverVersion,
+}
+
+/// Resolves the latest version of each installed mod and returns those newer than the installed
+/// version. Nothing is downloaded. Mods failing to resolve or without a version name are skipped.
+pub async fn check_for_mod_updates(
+    installed: &[(ModInfo, InstalledVersion)],
+    provider: &dyn ModSource,
+) -> Vec<ModUpdateAvailable> {
+    use futures::stream::{self, StreamExt};
+
+    stream::iter(installed)
+        .map(|(current, installed)| async move {
+            let spec = current.spec.without_version_constraint();
+            let latest = match provider.resolve_latest(&spec).await {
+                Ok(latest) => latest,
+                Err(e) => {
+                    warn!("failed to check {} for updates: {e}", current.name);
+                    return None;
+                }
+            };
+            let latest_version = provider
+                .version_name(&latest)
+                .and_then(|name| SemverVersion::parse(&name).ok())?;
+            (latest_version > installed.version).then(|| ModUpdateAvailable {
+                current: current.clone(),
+                latest,
+                current_version: installed.version.clone(),
+                latest_version,
+            })
+        })
+        .boxed() // without this the future becomes !Send https://github.com/rust-lang/rust/issues/104382
+        .buffered(CHECK_CONCURRENCY)
+        .filter_map(|update| async move { update })
+        .collect()
+        .await
+}
+
+/// Checks the installed mods of `provider` for updates in a background task and merges the result
+/// into the `PendingUpdates` stored in `cache_dir`. The installed version of each mod is the
+/// version it was resolved to, mods without a version name are skipped.
+pub fn spawn_background_update_check(
+    installed: Vec<ModInfo>,
+    provider: Arc<dyn ModSource>,
+    cache_dir: PathBuf,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let installed = installed
+            .into_iter()
+            .filter_map(|info| {
+                let version = InstalledVersion::from_name(&provider.version_name(&info)?)?;
+                Some((info, version))
+            })
+            .collect::<Vec<_>>();
+        let updates = check_for_mod_updates(&installed, provider.as_ref()).await;
+        debug!("{} updates available from {}", updates.len(), provider.id());
+        if let Err(e) = PendingUpdates::merge(&cache_dir, provider.id(), updates) {
+            warn!("failed to store available updates: {e}");
+        }
+    })
+}
+
+/// Result of the last update check stored in `pending_updates.json` in the cache directory.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingUpdates {
+    pub checked_at: SystemTime,
+    pub updates: Vec<ModUpdateAvailable>,
+}
+
+impl PendingUpdates {
+    fn path<P: AsRef<Path>>(cache_dir: P) -> PathBuf {
+        cache_dir.as_ref().join("pending_updates.json")
+    }
+
+    /// `None` if no check was stored or the file is malformed
+    pub fn load<P: AsRef<Path>>(cache_dir: P) -> Option<Self> {
+        serde_json::from_slice(&fs::read(Self::path(cache_dir)).ok()?).ok()
+    }
+
+    /// Replaces the stored check with `updates` found now
+    pub fn save<P: AsRef<Path>>(
+        cache_dir: P,
+        updates: Vec<ModUpdateAvailable>,
+    ) -> std::io::Result<Self> {
+        let pending = Self {
+            checked_at: SystemTime::now(),
+            updates,
+        };
+        fs::write(Self::path(cache_dir), serde_json::to_vec(&pending)?)?;
+        Ok(pending)
+    }
+
+    /// Replaces the stored updates from `provider` with `updates`, keeping those of other
+    /// providers so checks of several providers can run at once.
+    pub fn merge<P: AsRef<Path>>(
+        cache_dir: P,
+        provider: &str,
+        updates: Vec<ModUpdateAvailable>,
+    ) -> std::io::Result<Self> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let _lock = LOCK.lock().unwrap();
+
+        let mut merged = Self::load(&cache_dir).map_or(vec![], |p| p.updates);
+        merged.retain(|u| u.latest.provider != provider);
+        merged.extend(updates);
+        Self::save(cache_dir, merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(provider: &'static str, name: &str) -> ModUpdateAvailable {
+        let info = ModInfo::builder()
+            .provider(provider)
+            .name(name)
+            .spec(ModSpecification::new(format!("{provider}:{name}")))
+            .resolution(ModResolution::resolvable(
+                format!("{provider}:{name}").into(),
+            ))
+            .mod_type(ModType::Pak)
+            .build()
+            .unwrap();
+        ModUpdateAvailable {
+            current: info.clone(),
+            latest: info,
+            current_version: SemverVersion::parse("1.0.0").unwrap(),
+            latest_version: SemverVersion::parse("1.1.0").unwrap(),
+        }
+    }
+
+    fn names(pending: &PendingUpdates) -> Vec<&str> {
+        let mut names = pending
+            .updates
+            .iter()
+            .map(|u| u.latest.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_merge_replaces_updates_of_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        PendingUpdates::merge(dir.path(), "github", vec![update("github", "a")]).unwrap();
+        PendingUpdates::merge(dir.path(), "nexusmods", vec![update("nexusmods", "b")]).unwrap();
+        let merged =
+            PendingUpdates::merge(dir.path(), "github", vec![update("github", "c")]).unwrap();
+        assert_eq!(names(&merged), ["b", "c"]);
+        assert_eq!(
+            names(&PendingUpdates::load(dir.path()).unwrap()),
+            ["b", "c"]
+        );
+
+        let merged = PendingUpdates::merge(dir.path(), "nexusmods", vec![]).unwrap();
+        assert_eq!(names(&merged), ["c"]);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod recent;
 
 use std::{
     collections::{BTreeMap, HashMap},
@@ -12,13 +13,19 @@ use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
 
 use self::config::ConfigWrapper;
+use self::recent::RecentlyInstalled;
 use crate::{
     gui::GuiTheme,
-    providers::{ModSpecification, ModStore},
+    mod_lints::{LintConfig, DEFAULT_MAX_FILE_BYTES},
+    providers::{
+        configure_delta_downloads, configure_proxy, configure_rate_limits, load_credentials,
+        store_credentials, ModInfo, ModSpecification, ModStore, ProxyConfig,
+    },
     Dirs,
 };
 use crate::{gui::SortBy, providers::ProviderError};
-use mint_lib::{mod_info::MetaConfig, DBSZInstallation};
+use mint_lib::update::UpdateChannel;
+use mint_lib::{mod_info::MetaConfig, DBSZInstallation, LogFormat};
 
 /// Mod configuration, holds ModSpecification as well as other metadata
 #[derive(Debug, Clone, Hash, Serialize, Deserialize)]
@@ -345,6 +352,63 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "is_false")]
     pub disable_fix_exploding_gas: bool,
     pub sorting_config: Option<SortingConfig>,
+    /// Size above which the oversized files lint reports a file
+    #[serde(default = "default_lint_max_file_bytes")]
+    pub lint_max_file_bytes: u64,
+    /// Abort integration on lint warnings as well as errors
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub lint_warn_as_error: bool,
+    /// Rule names of lints that are never reported
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed_lints: Vec<String>,
+    /// Never install optional dependencies of mods
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub skip_optional_deps: bool,
+    /// Format of the log file
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// Abort integration if a mod does not declare a license
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub require_license: bool,
+    /// Abort integration if a pak is not signed by `pak_signing_key`, which must then be set
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub require_pak_signatures: bool,
+    /// PEM file of the ed25519 public key paks are verified against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pak_signing_key: Option<PathBuf>,
+    /// Requests per second sent to each provider by id, providers not listed use
+    /// `DEFAULT_PERMITS_PER_SECOND`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub provider_rate_limits: HashMap<String, f64>,
+    /// Size of the cache directory above which `mint doctor` suggests cleaning it
+    #[serde(default = "default_max_cache_bytes")]
+    pub max_cache_bytes: u64,
+    /// Releases installed by `mint self-update`
+    #[serde(default)]
+    pub update_channel: UpdateChannel,
+    /// Download updates of mods as patches of the previously downloaded version if the server
+    /// offers them. Asking for a patch sends the SHA-256 of the previously downloaded file to the
+    /// server.
+    #[serde(default = "default_enable_delta_downloads")]
+    pub enable_delta_downloads: bool,
+    /// Proxy of provider requests, the `HTTPS_PROXY` environment variable is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyConfig>,
+}
+
+fn default_lint_max_file_bytes() -> u64 {
+    DEFAULT_MAX_FILE_BYTES
+}
+
+/// 4 GiB
+pub const DEFAULT_MAX_CACHE_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+fn default_max_cache_bytes() -> u64 {
+    DEFAULT_MAX_CACHE_BYTES
+}
+
+fn default_enable_delta_downloads() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -366,7 +430,7 @@ impl Default for SortingConfig {
 #[serde(tag = "version")]
 pub enum VersionAnnotatedConfig {
     #[serde(rename = "0.0.0")]
-    V0_0_0(Config!["0.0.0"]),
+    V0_0_0(Box<Config!["0.0.0"]>),
     #[serde(other)]
     Unsupported,
 }
@@ -375,7 +439,7 @@ pub enum VersionAnnotatedConfig {
 #[serde(untagged)]
 pub enum MaybeVersionedConfig {
     Versioned(VersionAnnotatedConfig),
-    Legacy(Config!["0.0.0"]),
+    Legacy(Box<Config!["0.0.0"]>),
 }
 
 impl Default for MaybeVersionedConfig {
@@ -414,12 +478,38 @@ impl Default for Config!["0.0.0"] {
     fn default() -> Self {
         Self {
             provider_parameters: Default::default(),
-            dbsz_path: DBSZInstallation::find()
+            dbsz_path: DBSZInstallation::find_any()
                 .as_ref()
                 .map(DBSZInstallation::mods_path),
             gui_theme: None,
             disable_fix_exploding_gas: false,
             sorting_config: None,
+            lint_max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            lint_warn_as_error: false,
+            suppressed_lints: vec![],
+            skip_optional_deps: false,
+            log_format: LogFormat::Pretty,
+            require_license: false,
+            require_pak_signatures: false,
+            pak_signing_key: None,
+            provider_rate_limits: Default::default(),
+            max_cache_bytes: DEFAULT_MAX_CACHE_BYTES,
+            update_channel: UpdateChannel::Stable,
+            enable_delta_downloads: default_enable_delta_downloads(),
+            proxy: None,
+        }
+    }
+}
+
+impl Config!["0.0.0"] {
+    pub fn lint_config(&self) -> LintConfig {
+        LintConfig {
+            max_file_bytes: self.lint_max_file_bytes,
+            warn_as_error: self.lint_warn_as_error,
+            suppressed_lints: self.suppressed_lints.clone(),
+            require_license: self.require_license,
+            require_pak_signatures: self.require_pak_signatures,
+            pak_signing_key: self.pak_signing_key.clone(),
         }
     }
 }
@@ -468,7 +558,8 @@ impl State {
         let config_path = dirs.config_dir.join("config.json");
 
         let config = read_config_or_default(&config_path)?;
-        let config = ConfigWrapper::<VersionAnnotatedConfig>::new(&config_path, config);
+        let mut config = ConfigWrapper::<VersionAnnotatedConfig>::new(&config_path, config);
+        migrate_provider_parameters(&mut config);
         config.save().unwrap();
 
         let legacy_mod_profiles_path = dirs.config_dir.join("profiles.json");
@@ -477,6 +568,9 @@ impl State {
         let mod_data = ConfigWrapper::<VersionAnnotatedModData>::new(mod_data_path, mod_data);
         mod_data.save().unwrap();
 
+        configure_proxy(config.proxy.as_ref());
+        configure_rate_limits(&config.provider_rate_limits);
+        configure_delta_downloads(config.enable_delta_downloads);
         let store = ModStore::new(&dirs.cache_dir, &config.provider_parameters)?.into();
 
         Ok(Self {
@@ -486,6 +580,56 @@ impl State {
             store,
         })
     }
+
+    /// Adds successfully installed mods to the `RecentlyInstalled` history. Failing to save the
+    /// history is only logged as the mods are installed regardless.
+    pub fn record_installed(&self, mods: impl IntoIterator<Item = ModInfo>) {
+        let mut recent = RecentlyInstalled::load(&self.dirs.data_dir);
+        for info in mods {
+            if let Err(e) = recent.push(info) {
+                tracing::warn!("failed to record installed mod: {e}");
+                return;
+            }
+        }
+    }
+}
+
+/// Log format of the user config, read on its own so logging can be set up before `State::init`.
+pub fn read_log_format(dirs: &Dirs) -> LogFormat {
+    read_config_or_default(&dirs.config_dir.join("config.json"))
+        .map(|config| config.log_format)
+        .unwrap_or_default()
+}
+
+/// User config read without initializing `State`, for work done alongside a command which holds
+/// the `State`. Defaults if the config cannot be read.
+pub fn read_config(dirs: &Dirs) -> Config {
+    match read_config_or_default(&dirs.config_dir.join("config.json")) {
+        Ok(VersionAnnotatedConfig::V0_0_0(config)) => *config,
+        _ => Default::default(),
+    }
+}
+
+pub fn read_update_channel(dirs: &Dirs) -> UpdateChannel {
+    read_config_or_default(&dirs.config_dir.join("config.json"))
+        .map(|config| config.update_channel)
+        .unwrap_or_default()
+}
+
+/// Moves provider parameters written to the config file by older versions into the keychain.
+/// Parameters are only kept in the config file if the keychain cannot store them.
+fn migrate_provider_parameters(config: &mut Config!["0.0.0"]) {
+    config.provider_parameters.retain(|id, parameters| {
+        let mut credentials = load_credentials(id);
+        credentials.extend(parameters.clone());
+        match store_credentials(id, &credentials) {
+            Ok(()) => false,
+            Err(e) => {
+                tracing::warn!("keeping credentials in the config file: {e}");
+                true
+            }
+        }
+    });
 }
 
 fn read_config_or_default(config_path: &PathBuf) -> Result<VersionAnnotatedConfig, StateError> {
@@ -499,11 +643,11 @@ fn read_config_or_default(config_path: &PathBuf) -> Result<VersionAnnotatedConfi
                     VersionAnnotatedConfig::Unsupported => UnsupportedCfgVersionSnafu.fail()?,
                 },
                 MaybeVersionedConfig::Legacy(legacy) => {
-                    VersionAnnotatedConfig::V0_0_0(Config_v0_0_0 {
+                    VersionAnnotatedConfig::V0_0_0(Box::new(Config_v0_0_0 {
                         provider_parameters: legacy.provider_parameters,
                         dbsz_path: legacy.dbsz_path,
                         ..Default::default()
-                    })
+                    }))
                 }
             }
         }
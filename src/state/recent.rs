@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+
+use crate::providers::ModInfo;
+
+/// Number of mods remembered by default
+pub const DEFAULT_MAX_RECENT: usize = 50;
+
+/// History of installed mods stored in `recent.json` in the data directory, newest first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecentlyInstalled {
+    #[serde(skip)]
+    path: PathBuf,
+    /// Kept contiguous so it can be returned as a slice
+    entries: VecDeque<(ModInfo, SystemTime)>,
+    max_entries: usize,
+}
+
+impl RecentlyInstalled {
+    /// Starts an empty history if the file is missing or malformed
+    pub fn load<P: AsRef<Path>>(data_dir: P) -> Self {
+        let path = data_dir.as_ref().join("recent.json");
+        let mut recent = fs::read(&path)
+            .ok()
+            .and_then(|buf| serde_json::from_slice::<Self>(&buf).ok())
+            .unwrap_or(Self {
+                path: PathBuf::new(),
+                entries: VecDeque::new(),
+                max_entries: DEFAULT_MAX_RECENT,
+            });
+        recent.path = path;
+        recent.entries.make_contiguous();
+        recent
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        fs::write(&self.path, serde_json::to_vec(self)?)
+    }
+
+    /// Records the mod as installed now, moving it to the front if it was installed before.
+    pub fn push(&mut self, info: ModInfo) -> std::io::Result<()> {
+        self.entries.retain(|(m, _)| m.spec != info.spec);
+        self.entries.push_front((info, SystemTime::now()));
+        self.entries.truncate(self.max_entries);
+        self.entries.make_contiguous();
+        self.save()
+    }
+
+    pub fn list(&self) -> &[(ModInfo, SystemTime)] {
+        self.entries.as_slices().0
+    }
+
+    pub fn clear(&mut self) -> std::io::Result<()> {
+        self.entries.clear();
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{ModResolution, ModSpecification, ModType};
+
+    fn mod_info(name: &str) -> ModInfo {
+        ModInfo::builder()
+            .provider("file")
+            .name(name)
+            .spec(ModSpecification::new(name.to_string()))
+            .resolution(ModResolution::resolvable(name.into()))
+            .mod_type(ModType::Pak)
+            .build()
+            .unwrap()
+    }
+
+    fn names(recent: &RecentlyInstalled) -> Vec<&str> {
+        recent.list().iter().map(|(m, _)| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_push_truncates_and_moves_reinstalled_to_front() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recent = RecentlyInstalled::load(dir.path());
+        recent.max_entries = 2;
+        for name in ["a", "b", "c"] {
+            recent.push(mod_info(name)).unwrap();
+        }
+        assert_eq!(names(&recent), ["c", "b"]);
+
+        recent.push(mod_info("b")).unwrap();
+        assert_eq!(names(&recent), ["b", "c"]);
+        assert_eq!(names(&RecentlyInstalled::load(dir.path())), ["b", "c"]);
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use mint::mod_lints::{LintId, LintReport, SplitAssetPair};
+use mint::mod_lints::{LintConfig, LintError, LintId, LintReport, SplitAssetPair};
 use mint::providers::ModSpecification;
+use mint_lib::mod_info::{Author, MetaMod};
 
 #[test]
 pub fn test_lint_conflicting_files() {
@@ -23,7 +25,14 @@ pub fn test_lint_conflicting_files() {
 
     let LintReport {
         conflicting_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::CONFLICTING].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::CONFLICTING].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", conflicting_mods);
 
@@ -51,7 +60,14 @@ pub fn test_lint_shader() {
 
     let LintReport {
         shader_file_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::SHADER_FILES].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::SHADER_FILES].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", shader_file_mods);
 
@@ -80,8 +96,14 @@ pub fn test_lint_asset_registry_bin() {
     let LintReport {
         asset_register_bin_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::ASSET_REGISTRY_BIN].into(), mods.into(), None)
-        .unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::ASSET_REGISTRY_BIN].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", asset_register_bin_mods);
 
@@ -105,8 +127,14 @@ pub fn test_lint_outdated_pak_version() {
     let LintReport {
         outdated_pak_version_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::OUTDATED_PAK_VERSION].into(), mods.into(), None)
-        .unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::OUTDATED_PAK_VERSION].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", outdated_pak_version_mods);
 
@@ -129,7 +157,14 @@ pub fn test_lint_empty_archive() {
 
     let LintReport {
         empty_archive_mods, ..
-    } = mint::mod_lints::run_lints(&[LintId::EMPTY_ARCHIVE].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::EMPTY_ARCHIVE].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", empty_archive_mods);
 
@@ -161,7 +196,9 @@ pub fn test_lint_only_non_pak_files() {
     } = mint::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_ONLY_NON_PAK_FILES].into(),
         mods.into(),
+        &Default::default(),
         None,
+        &LintConfig::default(),
     )
     .unwrap();
 
@@ -189,7 +226,9 @@ pub fn test_lint_multi_pak_archive() {
     } = mint::mod_lints::run_lints(
         &[LintId::ARCHIVE_WITH_MULTIPLE_PAKS].into(),
         mods.into(),
+        &Default::default(),
         None,
+        &LintConfig::default(),
     )
     .unwrap();
 
@@ -214,7 +253,14 @@ pub fn test_lint_non_asset_files() {
     let LintReport {
         non_asset_file_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::NON_ASSET_FILES].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::NON_ASSET_FILES].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", non_asset_file_mods);
 
@@ -238,7 +284,14 @@ pub fn test_lint_split_asset_pairs() {
     let LintReport {
         split_asset_pairs_mods,
         ..
-    } = mint::mod_lints::run_lints(&[LintId::SPLIT_ASSET_PAIRS].into(), mods.into(), None).unwrap();
+    } = mint::mod_lints::run_lints(
+        &[LintId::SPLIT_ASSET_PAIRS].into(),
+        mods.into(),
+        &Default::default(),
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
 
     println!("{:#?}", split_asset_pairs_mods);
 
@@ -282,7 +335,9 @@ pub fn test_lint_unmodified_game_assets() {
     } = mint::mod_lints::run_lints(
         &[LintId::UNMODIFIED_GAME_ASSETS].into(),
         mods.into(),
+        &Default::default(),
         Some(reference_pak_path),
+        &LintConfig::default(),
     )
     .unwrap();
 
@@ -295,3 +350,48 @@ pub fn test_lint_unmodified_game_assets() {
         Some(&["a.uexp".to_string(), "a.uasset".to_string()].into())
     );
 }
+
+#[test]
+pub fn test_lint_meta_mod() {
+    let base_path = PathBuf::from_str("test_assets/lints/").unwrap();
+    assert!(base_path.exists());
+    let a_path = base_path.clone().join("A.pak");
+    assert!(a_path.exists());
+    let b_path = base_path.clone().join("B.pak");
+    assert!(b_path.exists());
+    let a_spec = ModSpecification {
+        url: "A".to_string(),
+    };
+    let b_spec = ModSpecification {
+        url: "B".to_string(),
+    };
+    let meta = |version: &str| MetaMod {
+        name: "mod".to_string(),
+        version: version.to_string(),
+        url: "https://example.org/mod".to_string(),
+        authors: vec![Author::new("someone")],
+        license: None,
+        changelog: None,
+        required: false,
+    };
+    let metas = BTreeMap::from([(a_spec.clone(), meta("")), (b_spec.clone(), meta("1.0.0"))]);
+    let mods = [(a_spec.clone(), a_path), (b_spec.clone(), b_path)];
+
+    let LintReport {
+        meta_mod_errors, ..
+    } = mint::mod_lints::run_lints(
+        &[LintId::META_MOD].into(),
+        mods.into(),
+        &metas,
+        None,
+        &LintConfig::default(),
+    )
+    .unwrap();
+
+    let meta_mod_errors = meta_mod_errors.unwrap();
+    assert!(!meta_mod_errors.contains_key(&b_spec));
+    assert!(matches!(
+        meta_mod_errors[&a_spec].as_slice(),
+        [LintError::MissingMetadata { field: "version" }]
+    ));
+}